@@ -0,0 +1,307 @@
+// Copyright (c) 2021 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Conversion of binlog rows to Avro records (requires the `apache-avro` feature).
+//!
+//! Only scalar values are supported:
+//!
+//! | Column type                        | Avro type                          |
+//! | ---------------------------------- | ---------------------------------- |
+//! | TINYINT, SMALLINT, MEDIUMINT, YEAR | `int`                              |
+//! | ENUM                               | `int` (1-based member index)       |
+//! | INT                                | `int` (`long` if unsigned)         |
+//! | BIGINT                             | `long` (must fit into `i64`)       |
+//! | FLOAT, DOUBLE                      | `float`, `double`                  |
+//! | DECIMAL                            | `bytes` with the `decimal` type    |
+//! | DATE                               | `int` with the `date` type         |
+//! | TIME                               | `long` with the `time-micros` type |
+//! | DATETIME                           | `long` with `local-timestamp-micros` |
+//! | TIMESTAMP                          | `long` with `timestamp-micros`     |
+//! | JSON                               | `string` (serialized JSON)         |
+//! | text columns                       | `string`                           |
+//! | binary columns, BIT, SET, GEOMETRY | `bytes` (SET is a bitmask)         |
+//!
+//! NULL values are converted to `null`, so corresponding fields of the record schema
+//! should be declared as `["null", <type>]` unions.
+
+use std::{convert::TryFrom, io};
+
+use apache_avro::types::Value as AvroValue;
+
+use crate::{
+    constants::{ColumnFlags, ColumnType},
+    packets::Column,
+    value::Value::*,
+};
+
+use super::{decimal::Decimal, value::BinlogValue};
+
+fn unsupported(column: &Column, msg: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidInput,
+        format!(
+            "Can't convert column `{}` to Avro: {}",
+            column.name_str(),
+            msg
+        ),
+    )
+}
+
+/// Returns the number of days since the Unix epoch of the given proleptic Gregorian date.
+fn days_from_civil(y: i64, m: u8, d: u8) -> i64 {
+    let y = if m <= 2 { y - 1 } else { y };
+    let era = y.div_euclid(400);
+    let yoe = y.rem_euclid(400);
+    let mp = (m as i64 + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + d as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+/// Converts a single value of the given column to Avro (see the module docs).
+pub fn value_to_avro(column: &Column, value: &BinlogValue<'_>) -> io::Result<AvroValue> {
+    use ColumnType::*;
+
+    let out_of_range = || unsupported(column, "value is out of range");
+    let is_unsigned = column.flags().contains(ColumnFlags::UNSIGNED_FLAG);
+
+    let value = match value {
+        BinlogValue::Value(NULL) => return Ok(AvroValue::Null),
+        BinlogValue::Value(x) => x,
+        BinlogValue::Decimal(x) if column.column_type() == ColumnType::MYSQL_TYPE_NEWDECIMAL => {
+            let unscaled = x.to_scaled_bigint().to_signed_bytes_be();
+            return Ok(AvroValue::Decimal(unscaled.into()));
+        }
+        BinlogValue::Jsonb(x) => {
+            let json = serde_json::Value::try_from(x.clone())
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+            return Ok(AvroValue::String(json.to_string()));
+        }
+        _ => return Err(unsupported(column, "only scalar values are supported")),
+    };
+
+    let micros = |h: u8, i: u8, s: u8, us: u32| {
+        (h as i64 * 3600 + i as i64 * 60 + s as i64) * 1_000_000 + us as i64
+    };
+
+    match (column.column_type(), value) {
+        // the Avro type of an unsigned INT column is `long` regardless of the value
+        (MYSQL_TYPE_LONG, Int(x)) if is_unsigned => Ok(AvroValue::Long(*x)),
+        (
+            MYSQL_TYPE_TINY | MYSQL_TYPE_SHORT | MYSQL_TYPE_INT24 | MYSQL_TYPE_LONG
+            | MYSQL_TYPE_ENUM,
+            Int(x),
+        ) => i32::try_from(*x)
+            .map(AvroValue::Int)
+            .map_err(|_| out_of_range()),
+        // YEAR is decoded as a four-digit string
+        (MYSQL_TYPE_YEAR, Bytes(x)) => std::str::from_utf8(x)
+            .ok()
+            .and_then(|x| x.parse::<i32>().ok())
+            .map(AvroValue::Int)
+            .ok_or_else(out_of_range),
+        (MYSQL_TYPE_LONGLONG, Int(x)) => Ok(AvroValue::Long(*x)),
+        (MYSQL_TYPE_LONGLONG, UInt(x)) => i64::try_from(*x)
+            .map(AvroValue::Long)
+            .map_err(|_| out_of_range()),
+        (MYSQL_TYPE_FLOAT, Float(x)) => Ok(AvroValue::Float(*x)),
+        (MYSQL_TYPE_DOUBLE, Double(x)) => Ok(AvroValue::Double(*x)),
+        (MYSQL_TYPE_NEWDECIMAL, Bytes(x)) => {
+            let decimal = Decimal::parse_bytes(x)
+                .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "invalid decimal"))?;
+            let unscaled = decimal.to_scaled_bigint().to_signed_bytes_be();
+            Ok(AvroValue::Decimal(unscaled.into()))
+        }
+        (MYSQL_TYPE_DATE | MYSQL_TYPE_NEWDATE, Date(y, m, d, ..)) => {
+            if *m == 0 || *d == 0 {
+                return Err(out_of_range());
+            }
+            let days = days_from_civil(*y as i64, *m, *d);
+            Ok(AvroValue::Date(days as i32))
+        }
+        (MYSQL_TYPE_TIME | MYSQL_TYPE_TIME2, Time(is_neg, days, h, i, s, us)) => {
+            if *is_neg || *days > 0 || *h >= 24 {
+                return Err(out_of_range());
+            }
+            Ok(AvroValue::TimeMicros(micros(*h, *i, *s, *us)))
+        }
+        (MYSQL_TYPE_DATETIME | MYSQL_TYPE_DATETIME2, Date(y, m, d, h, i, s, us)) => {
+            if *m == 0 || *d == 0 {
+                return Err(out_of_range());
+            }
+            let days = days_from_civil(*y as i64, *m, *d);
+            Ok(AvroValue::LocalTimestampMicros(
+                days * 86_400_000_000 + micros(*h, *i, *s, *us),
+            ))
+        }
+        (MYSQL_TYPE_TIMESTAMP | MYSQL_TYPE_TIMESTAMP2, _) => {
+            let (secs, micros) = BinlogValue::Value(value.clone())
+                .as_timestamp()
+                .ok_or_else(out_of_range)?;
+            Ok(AvroValue::TimestampMicros(secs * 1_000_000 + micros as i64))
+        }
+        (
+            MYSQL_TYPE_VARCHAR
+            | MYSQL_TYPE_VAR_STRING
+            | MYSQL_TYPE_STRING
+            | MYSQL_TYPE_SET
+            | MYSQL_TYPE_TINY_BLOB
+            | MYSQL_TYPE_MEDIUM_BLOB
+            | MYSQL_TYPE_LONG_BLOB
+            | MYSQL_TYPE_BLOB
+            | MYSQL_TYPE_BIT
+            | MYSQL_TYPE_GEOMETRY,
+            Bytes(x),
+        ) => {
+            let is_text = !matches!(
+                column.column_type(),
+                MYSQL_TYPE_BIT | MYSQL_TYPE_SET | MYSQL_TYPE_GEOMETRY
+            ) && !BinlogValue::Value(value.clone()).is_binary(column);
+            match std::str::from_utf8(x) {
+                Ok(string) if is_text => Ok(AvroValue::String(string.into())),
+                _ => Ok(AvroValue::Bytes(x.clone())),
+            }
+        }
+        _ => Err(unsupported(column, "unsupported column type or value")),
+    }
+}
+
+/// Converts a row to an Avro record (see the module docs).
+///
+/// Field names are taken from `columns`, so they should be valid Avro names.
+/// Returns an error if the number of columns and values differs or if some value
+/// couldn't be converted.
+pub fn row_to_avro(columns: &[Column], row: &[BinlogValue<'_>]) -> io::Result<AvroValue> {
+    if columns.len() != row.len() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "number of columns and values differs",
+        ));
+    }
+
+    let fields = columns
+        .iter()
+        .zip(row)
+        .map(|(column, value)| {
+            Ok((
+                column.name_str().into_owned(),
+                value_to_avro(column, value)?,
+            ))
+        })
+        .collect::<io::Result<Vec<_>>>()?;
+
+    Ok(AvroValue::Record(fields))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::ColumnFlags;
+
+    #[test]
+    fn should_convert_row_to_avro() -> io::Result<()> {
+        let columns = [
+            Column::new(ColumnType::MYSQL_TYPE_LONG)
+                .with_name(b"id")
+                .with_flags(ColumnFlags::UNSIGNED_FLAG),
+            Column::new(ColumnType::MYSQL_TYPE_VARCHAR)
+                .with_name(b"title")
+                .with_character_set(255),
+            Column::new(ColumnType::MYSQL_TYPE_NEWDECIMAL).with_name(b"price"),
+        ];
+        let row = [
+            BinlogValue::int(u32::MAX as i64),
+            BinlogValue::bytes("foo"),
+            BinlogValue::bytes("-12.50"),
+        ];
+
+        let record = row_to_avro(&columns, &row)?;
+        assert_eq!(
+            record,
+            AvroValue::Record(vec![
+                ("id".into(), AvroValue::Long(u32::MAX as i64)),
+                ("title".into(), AvroValue::String("foo".into())),
+                (
+                    "price".into(),
+                    AvroValue::Decimal((-1250_i16).to_be_bytes().into())
+                ),
+            ])
+        );
+
+        let record = row_to_avro(
+            &columns,
+            &[
+                BinlogValue::int(1),
+                BinlogValue::Value(NULL),
+                BinlogValue::bytes("0.00"),
+            ],
+        )?;
+        assert_eq!(
+            record,
+            AvroValue::Record(vec![
+                ("id".into(), AvroValue::Long(1)),
+                ("title".into(), AvroValue::Null),
+                ("price".into(), AvroValue::Decimal(vec![0].into())),
+            ])
+        );
+
+        let err = row_to_avro(&columns, &row[..2]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_convert_temporal_values_to_avro() -> io::Result<()> {
+        let column = |column_type| Column::new(column_type).with_name(b"x");
+
+        let date = BinlogValue::datetime(2021, 12, 31, 0, 0, 0, 0);
+        assert_eq!(
+            value_to_avro(&column(ColumnType::MYSQL_TYPE_NEWDATE), &date)?,
+            AvroValue::Date(18_992)
+        );
+        let datetime = BinlogValue::datetime(1969, 12, 31, 23, 59, 59, 500_000);
+        assert_eq!(
+            value_to_avro(&column(ColumnType::MYSQL_TYPE_DATETIME2), &datetime)?,
+            AvroValue::LocalTimestampMicros(-500_000)
+        );
+        let timestamp = BinlogValue::bytes("1640995199.500000");
+        assert_eq!(
+            value_to_avro(&column(ColumnType::MYSQL_TYPE_TIMESTAMP2), &timestamp)?,
+            AvroValue::TimestampMicros(1_640_995_199_500_000)
+        );
+        let time = BinlogValue::Value(Time(false, 0, 1, 2, 3, 4));
+        assert_eq!(
+            value_to_avro(&column(ColumnType::MYSQL_TYPE_TIME2), &time)?,
+            AvroValue::TimeMicros(3_723_000_004)
+        );
+
+        let time = BinlogValue::Value(Time(true, 0, 1, 2, 3, 4));
+        let err = value_to_avro(&column(ColumnType::MYSQL_TYPE_TIME2), &time).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+
+        let year = BinlogValue::bytes("2021");
+        assert_eq!(
+            value_to_avro(&column(ColumnType::MYSQL_TYPE_YEAR), &year)?,
+            AvroValue::Int(2021)
+        );
+        let int = BinlogValue::int(1);
+        assert_eq!(
+            value_to_avro(&column(ColumnType::MYSQL_TYPE_LONG), &int)?,
+            AvroValue::Int(1)
+        );
+
+        let json = BinlogValue::json(serde_json::json!({ "a": [1] }));
+        assert_eq!(
+            value_to_avro(&column(ColumnType::MYSQL_TYPE_JSON), &json)?,
+            AvroValue::String(r#"{"a":[1]}"#.into())
+        );
+
+        Ok(())
+    }
+}
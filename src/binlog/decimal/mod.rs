@@ -24,6 +24,12 @@ mod test;
 #[derive(Debug, Clone, Copy)]
 pub struct ParseDecimalError;
 
+/// Decimal doesn't fit into the target type (see `TryFrom<Decimal>` impls).
+#[cfg(feature = "rust_decimal")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("decimal is out of range of the target type")]
+pub struct DecimalOutOfRange;
+
 /// Type of a base 9 digit.
 pub type Digit = i32;
 
@@ -50,7 +56,9 @@ pub const POWERS_10: [i32; DIG_PER_DEC + 1] = [
 /// *   serialization/deserialization to/from binary format
 ///     (see `read_bin` and `write_bin` functions);
 /// *   parsing from decimal string/buffer (see `Decimal::parse_bytes`, `FromStr` impl);
-/// *   conversion to decimal string (using `Display`).
+/// *   conversion to decimal string (using `Display`);
+/// *   lossless conversion to `bigdecimal::BigDecimal` (see `Decimal::to_bigdecimal`)
+///     and checked conversion to `rust_decimal::Decimal` (see `TryFrom` impl).
 ///
 /// # Notes
 ///
@@ -58,7 +66,7 @@ pub const POWERS_10: [i32; DIG_PER_DEC + 1] = [
 ///     i.e. both `rhs` and `lhs` will be serialized into temporary buffers;
 /// *   even though MySql's `string2decimal` function allows scientific notation,
 ///     this implementation denies it.
-#[derive(Default, Debug, Eq)]
+#[derive(Default, Debug, Clone, Eq)]
 pub struct Decimal {
     /// The number of *decimal* digits (NOT number of `Digit`s!) before the point.
     intg: usize,
@@ -75,6 +83,71 @@ impl Decimal {
         decimal_bin_size(self.intg + self.frac, self.frac)
     }
 
+    /// Returns the number of decimal digits after the point.
+    pub fn scale(&self) -> usize {
+        self.frac
+    }
+
+    /// Returns the unscaled integer representation of this decimal, i.e. `self * 10^scale`.
+    ///
+    /// Returns `None` if the value doesn't fit into `i128`.
+    pub fn to_scaled_i128(&self) -> Option<i128> {
+        let mut out = 0_i128;
+        for (base, x) in self.scaled_digits() {
+            out = out.checked_mul(base as i128)?.checked_add(x as i128)?;
+        }
+
+        if self.sign {
+            out = -out;
+        }
+
+        Some(out)
+    }
+
+    /// Same as [`Decimal::to_scaled_i128`] but never overflows.
+    pub fn to_scaled_bigint(&self) -> num_bigint::BigInt {
+        let mut out = num_bigint::BigInt::from(0);
+        for (base, x) in self.scaled_digits() {
+            out = out * base + x;
+        }
+
+        if self.sign {
+            out = -out;
+        }
+
+        out
+    }
+
+    /// Converts this decimal to `bigdecimal::BigDecimal` keeping all its digits and its scale.
+    #[cfg(feature = "bigdecimal03")]
+    pub fn to_bigdecimal(&self) -> bigdecimal03::BigDecimal {
+        bigdecimal03::BigDecimal::new(self.to_scaled_bigint(), self.frac as i64)
+    }
+
+    /// Returns `(base, digit)` pairs, so that the unscaled absolute value of this decimal
+    /// is the fold of `acc * base + digit` over them.
+    fn scaled_digits(&self) -> impl Iterator<Item = (u32, u32)> + '_ {
+        let intg_digits = (self.intg + DIG_PER_DEC - 1) / DIG_PER_DEC;
+        let (intg_buf, frac_buf) = self
+            .buf
+            .split_at(std::cmp::min(intg_digits, self.buf.len()));
+
+        let mut frac = self.frac;
+        let frac_digits = frac_buf.iter().map(move |x| {
+            let len = std::cmp::min(DIG_PER_DEC, frac);
+            frac -= len;
+            (
+                POWERS_10[len] as u32,
+                (*x / POWERS_10[DIG_PER_DEC - len]) as u32,
+            )
+        });
+
+        intg_buf
+            .iter()
+            .map(|x| (DIG_BASE as u32, *x as u32))
+            .chain(frac_digits)
+    }
+
     pub fn parse_bytes(bytes: &[u8]) -> Result<Self, ParseDecimalError> {
         match std::str::from_utf8(bytes) {
             Ok(string) => Decimal::from_str(string),
@@ -140,16 +213,33 @@ impl Decimal {
     }
 
     pub fn read_bin<T: Read>(
-        mut input: T,
+        input: T,
         precision: usize,
         scale: usize,
         keep_prec: bool,
     ) -> io::Result<Self> {
         let mut out = Self::default();
+        out.read_bin_into(input, precision, scale, keep_prec, &mut Vec::new())?;
+        Ok(out)
+    }
+
+    /// Same as `read_bin`, but reuses allocations of `self` and of the given `buffer`
+    /// (see [`DecimalScratch`]).
+    fn read_bin_into<T: Read>(
+        &mut self,
+        mut input: T,
+        precision: usize,
+        scale: usize,
+        keep_prec: bool,
+        buffer: &mut Vec<u8>,
+    ) -> io::Result<()> {
+        let out = self;
+        out.buf.clear();
 
         let bin_size = decimal_bin_size(precision, scale);
-        let mut buffer = vec![0_u8; bin_size];
-        input.read_exact(&mut buffer)?;
+        buffer.clear();
+        buffer.resize(bin_size, 0);
+        input.read_exact(buffer)?;
 
         // we should invert back the very first bit of a binary representation
         if let Some(x) = buffer.get_mut(0) {
@@ -229,7 +319,72 @@ impl Decimal {
             out.buf[0] = 0;
         }
 
-        Ok(out)
+        Ok(())
+    }
+}
+
+/// Reusable buffers for decoding of binary decimals.
+///
+/// Decoding a decimal via [`Decimal::read_bin`] and then converting it to a string allocates
+/// several times per value, so the scratch could be used to decode a batch of decimals.
+/// Use [`DecimalScratch::read_into`] to decode into a caller-owned `Decimal` without
+/// allocating once its buffers are large enough.
+#[derive(Debug, Default)]
+pub struct DecimalScratch {
+    decimal: Decimal,
+    bin: Vec<u8>,
+    string: String,
+}
+
+impl DecimalScratch {
+    /// Creates an empty scratch.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reads a binary decimal (see [`Decimal::read_bin`]).
+    ///
+    /// The decimal is only valid until the next call.
+    pub fn read<T: Read>(
+        &mut self,
+        input: T,
+        precision: usize,
+        scale: usize,
+        keep_prec: bool,
+    ) -> io::Result<&Decimal> {
+        self.decimal
+            .read_bin_into(input, precision, scale, keep_prec, &mut self.bin)?;
+        Ok(&self.decimal)
+    }
+
+    /// Reads a binary decimal (see [`Decimal::read_bin`]) into `out` reusing its allocation.
+    pub fn read_into<T: Read>(
+        &mut self,
+        input: T,
+        precision: usize,
+        scale: usize,
+        keep_prec: bool,
+        out: &mut Decimal,
+    ) -> io::Result<()> {
+        out.read_bin_into(input, precision, scale, keep_prec, &mut self.bin)
+    }
+
+    /// Reads a binary decimal (see [`Decimal::read_bin`]) and returns its string representation.
+    ///
+    /// The string is only valid until the next call.
+    pub fn read_str<T: Read>(
+        &mut self,
+        input: T,
+        precision: usize,
+        scale: usize,
+        keep_prec: bool,
+    ) -> io::Result<&str> {
+        use std::fmt::Write;
+
+        self.read(input, precision, scale, keep_prec)?;
+        self.string.clear();
+        write!(&mut self.string, "{}", self.decimal).expect("OOM");
+        Ok(&self.string)
     }
 }
 
@@ -255,6 +410,19 @@ impl PartialEq for Decimal {
     }
 }
 
+#[cfg(feature = "rust_decimal")]
+impl std::convert::TryFrom<Decimal> for rust_decimal::Decimal {
+    type Error = DecimalOutOfRange;
+
+    /// Fails if the precision or the scale of `value` exceeds what `rust_decimal::Decimal`
+    /// can represent (i.e. 96-bit mantissa and scale of at most 28).
+    fn try_from(value: Decimal) -> Result<Self, Self::Error> {
+        let mantissa = value.to_scaled_i128().ok_or(DecimalOutOfRange)?;
+        rust_decimal::Decimal::try_from_i128_with_scale(mantissa, value.frac as u32)
+            .map_err(|_| DecimalOutOfRange)
+    }
+}
+
 impl fmt::Display for Decimal {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let prefix_len = self.intg % DIG_PER_DEC;
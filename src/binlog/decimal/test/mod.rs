@@ -192,3 +192,78 @@ proptest! {
         assert_eq!(dec, dec2);
     }
 }
+
+#[test]
+fn decimal_max_precision() {
+    // DECIMAL(65,30) is 35 integral digits (4 + 3*4 bytes) and 30 fractional digits (3*4 + 2 bytes)
+    for num in [
+        "12345678901234567890123456789012345.123456789012345678901234567890",
+        "-99999999999999999999999999999999999.999999999999999999999999999999",
+        "10000000000000000000000000000000000.000000000000000000000000000001",
+    ] {
+        let size = unsafe { c_decimal_bin_size(65, 30) as usize };
+        assert_eq!(size, 30);
+        assert_eq!(super::decimal_bin_size(65, 30), size);
+
+        let dec = super::Decimal::parse_bytes(num.as_bytes()).unwrap();
+        assert_eq!((dec.intg, dec.frac), (35, 30));
+
+        let mut bin = Vec::new();
+        dec.write_bin(&mut bin).unwrap();
+        let mut mysql_bin = vec![0b11111111_u8; 32];
+        let mysql_dec = decimal_t::rust_string2decimal(num).unwrap();
+        mysql_dec.rust_decimal2bin(&mut mysql_bin).unwrap();
+        assert_eq!(&bin[..], &mysql_bin[..size]);
+
+        // the trailing byte must stay on the stream
+        let mut input = &mysql_bin[..size + 1];
+        let dec2 = super::Decimal::read_bin(&mut input, 65, 30, false).unwrap();
+        assert_eq!(input.len(), 1);
+        assert_eq!(dec2, dec);
+        assert_eq!(dec2.to_string(), num);
+    }
+}
+
+#[test]
+#[cfg(feature = "bigdecimal03")]
+fn decimal_to_bigdecimal() {
+    for num in [
+        "0.00",
+        "-12.50",
+        "123456789.000000001",
+        "-12345678901234567890123456789012345.123456789012345678901234567890",
+    ] {
+        let dec = super::Decimal::parse_bytes(num.as_bytes()).unwrap();
+        let bigdecimal = dec.to_bigdecimal();
+        assert_eq!(bigdecimal.to_string(), num);
+        assert_eq!(bigdecimal.as_bigint_and_exponent().1, dec.scale() as i64);
+    }
+}
+
+#[test]
+#[cfg(feature = "rust_decimal")]
+fn decimal_to_rust_decimal() {
+    use std::convert::TryFrom;
+
+    for num in ["0.00", "-12.50", "-79228162514264337593543950335"] {
+        let dec = super::Decimal::parse_bytes(num.as_bytes()).unwrap();
+        let rust_decimal = rust_decimal::Decimal::try_from(dec).unwrap();
+        assert_eq!(rust_decimal.to_string(), num);
+    }
+
+    for num in [
+        // precision 38 doesn't fit into the 96-bit mantissa
+        "12345678901234567890123456789012345678",
+        "-1234567890123456789012345678901234567.8",
+        // scale 29 is too big
+        "0.00000000000000000000000000001",
+        // precision above 38 doesn't even fit into `i128`
+        "12345678901234567890123456789012345.123456789012345678901234567890",
+    ] {
+        let dec = super::Decimal::parse_bytes(num.as_bytes()).unwrap();
+        assert_eq!(
+            rust_decimal::Decimal::try_from(dec),
+            Err(super::DecimalOutOfRange)
+        );
+    }
+}
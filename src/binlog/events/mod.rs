@@ -31,7 +31,7 @@ pub use self::{
     query_event::{QueryEvent, StatusVar, StatusVarVal, StatusVars, StatusVarsIterator},
     rand_event::RandEvent,
     rotate_event::RotateEvent,
-    rows_event::{RowsEvent, RowsEventRows},
+    rows_event::{RowsEvent, RowsEventRows, SchemaMismatchError},
     rows_query_event::RowsQueryEvent,
     table_map_event::*,
     update_rows_event::UpdateRowsEvent,
@@ -15,7 +15,7 @@ use saturating::Saturating as S;
 use crate::{
     binlog::{
         consts::{BinlogVersion, EventType, RowsEventFlags},
-        row::BinlogRow,
+        row::{BinlogRow, ColumnsMeta},
         BinlogCtx,
     },
     io::ParseBuf,
@@ -149,11 +149,7 @@ impl<'a> RowsEvent<'a> {
 
     /// Returns an iterator over event's rows given the corresponding `TableMapEvent`.
     pub fn rows<'b>(&'b self, table_map_event: &'b TableMapEvent<'b>) -> RowsEventRows<'b> {
-        RowsEventRows {
-            rows_event: self,
-            table_map_event,
-            rows_data: ParseBuf(self.rows_data.as_bytes()),
-        }
+        RowsEventRows::new(self, table_map_event, ParseBuf(self.rows_data.as_bytes()))
     }
 
     pub fn into_owned(self) -> RowsEvent<'static> {
@@ -276,12 +272,34 @@ impl MySerialize for RowsEvent<'_> {
     }
 }
 
+/// An error returned by [`RowsEventRows`] if row images doesn't match the `TableMapEvent`
+/// (i.e. the table was altered concurrently, so that some column changed its type).
+///
+/// It is returned wrapped into an `io::Error` of the `InvalidData` kind:
+///
+/// *   always, if the number of columns of the rows event differs from the one given
+///     by the `TableMapEvent` (note, that such events were previously decoded using
+///     the number of columns of the rows event);
+/// *   if [`RowsEventRows::detect_schema_drift`] is enabled and row images doesn't consume
+///     the rows event body exactly. Otherwise an `UnexpectedEof` error of the failed read
+///     is returned as is.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("column count/schema mismatch: {}", _0)]
+pub struct SchemaMismatchError(pub String);
+
 /// Iterator over rows of a `RowsEvent`.
+///
+/// Note, that an ENUM or SET value that refers to a member that isn't given by the optional
+/// metadata fails the row with an `InvalidData` error (see [`BinlogRow`]).
 #[derive(Clone, Eq, PartialEq)]
 pub struct RowsEventRows<'a> {
     rows_event: &'a RowsEvent<'a>,
     table_map_event: &'a TableMapEvent<'a>,
     rows_data: ParseBuf<'a>,
+    columns_meta: ColumnsMeta,
+    skip_invisible: bool,
+    detect_schema_drift: bool,
+    drift_checked: bool,
 }
 
 impl<'a> RowsEventRows<'a> {
@@ -294,6 +312,78 @@ impl<'a> RowsEventRows<'a> {
             rows_event,
             table_map_event,
             rows_data,
+            columns_meta: ColumnsMeta::new(table_map_event),
+            skip_invisible: false,
+            detect_schema_drift: false,
+            drift_checked: false,
+        }
+    }
+
+    /// Makes the iterator omit values of invisible columns, such as hidden generated columns
+    /// of functional indexes (see [`TableMapEvent::column_visibility`]).
+    ///
+    /// Requires the `COLUMN_VISIBILITY` optional metadata, i.e. `binlog_row_metadata=FULL`.
+    pub fn skip_invisible_columns(mut self) -> Self {
+        self.skip_invisible = true;
+        self
+    }
+
+    /// Makes the iterator check that row images consume the rows event body exactly
+    /// before yielding the first row.
+    ///
+    /// If a column type differs from the one given by the `TableMapEvent` (i.e. the table
+    /// was altered concurrently), then values are read at wrong offsets. Without this check
+    /// such desync is only detected when the end of the body is reached, i.e. after some
+    /// corrupted rows were already yielded. With this check the iterator yields
+    /// a [`SchemaMismatchError`] instead of any rows. Note, that this requires to decode
+    /// the whole event twice.
+    pub fn detect_schema_drift(mut self) -> Self {
+        self.detect_schema_drift = true;
+        self
+    }
+}
+
+impl<'a> RowsEventRows<'a> {
+    /// Stops the iteration and returns a schema mismatch error.
+    fn schema_mismatch(&mut self, msg: String) -> io::Error {
+        self.rows_data = ParseBuf(&[]);
+        io::Error::new(io::ErrorKind::InvalidData, SchemaMismatchError(msg))
+    }
+
+    /// Parses a row image.
+    ///
+    /// If schema drift detection is enabled, then an unexpected end of the rows data means
+    /// that row images doesn't match the `TableMapEvent` (i.e. the table was altered
+    /// concurrently), so it is reported as a schema mismatch.
+    ///
+    /// The iteration stops after an error.
+    fn parse_row(&mut self, cols: &'a BitSlice<u8>, is_partial: bool) -> io::Result<BinlogRow> {
+        let remaining = self.rows_data.len();
+        let ctx = (
+            self.rows_event.num_columns(),
+            cols,
+            is_partial,
+            self.table_map_event,
+        );
+        match BinlogRow::deserialize_row(
+            ctx,
+            &self.columns_meta,
+            None,
+            self.skip_invisible,
+            &mut self.rows_data,
+        ) {
+            Ok(row) => Ok(row),
+            Err(err) if self.detect_schema_drift && err.kind() == io::ErrorKind::UnexpectedEof => {
+                Err(self.schema_mismatch(format!(
+                    "{} trailing bytes of the rows event body can't be decoded as a row image ({})",
+                    remaining, err
+                )))
+            }
+            Err(err) => {
+                // the rest of the rows data can't be decoded after a failed read
+                self.rows_data = ParseBuf(&[]);
+                Err(err)
+            }
         }
     }
 }
@@ -309,27 +399,34 @@ impl<'a> Iterator for RowsEventRows<'a> {
             return None;
         }
 
-        if let Some(cols) = self.rows_event.columns_before_image() {
-            let ctx = (
+        // row images must consume the whole body
+        if self.detect_schema_drift && !self.drift_checked {
+            self.drift_checked = true;
+            if let Some(Err(err)) = self.clone().find(Result::is_err) {
+                self.rows_data = ParseBuf(&[]);
+                return Some(Err(err));
+            }
+        }
+
+        if self.rows_event.num_columns() != self.table_map_event.columns_count() {
+            let msg = format!(
+                "rows event contains {} columns while the table map event defines {}",
                 self.rows_event.num_columns(),
-                cols,
-                false,
-                self.table_map_event,
+                self.table_map_event.columns_count()
             );
-            row_before = match self.rows_data.parse(ctx) {
+            return Some(Err(self.schema_mismatch(msg)));
+        }
+
+        if let Some(cols) = self.rows_event.columns_before_image() {
+            row_before = match self.parse_row(cols, false) {
                 Ok(row_before) => Some(row_before),
                 Err(err) => return Some(Err(err)),
             };
         }
 
         if let Some(cols) = self.rows_event.columns_after_image() {
-            let ctx = (
-                self.rows_event.num_columns(),
-                cols,
-                self.rows_event.event_type == EventType::PARTIAL_UPDATE_ROWS_EVENT,
-                self.table_map_event,
-            );
-            row_after = match self.rows_data.parse(ctx) {
+            let is_partial = self.rows_event.event_type == EventType::PARTIAL_UPDATE_ROWS_EVENT;
+            row_after = match self.parse_row(cols, is_partial) {
                 Ok(row_after) => Some(row_after),
                 Err(err) => return Some(Err(err)),
             };
@@ -344,3 +441,354 @@ impl fmt::Debug for RowsEventRows<'_> {
         f.debug_list().entries(self.clone()).finish()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::binlog::{
+        events::{FormatDescriptionEvent, OptionalMetadataField},
+        value::BinlogValue,
+    };
+
+    const TABLE_MAP: &[u8] = &[
+        1, 0, 0, 0, 0, 0, // table id
+        0, 0, // flags
+        2, b'd', b'b', 0, // database name
+        1, b't', 0, // table name
+        1, // columns count
+        3, // MYSQL_TYPE_LONG
+        0, // columns metadata
+        0, // null bitmask
+    ];
+
+    type Rows = Vec<io::Result<(Option<BinlogRow>, Option<BinlogRow>)>>;
+
+    fn parse_table_map(table_map: &[u8]) -> io::Result<TableMapEvent<'static>> {
+        let fde = FormatDescriptionEvent::new(BinlogVersion::Version4);
+        let table_map: TableMapEvent =
+            ParseBuf(table_map).parse(BinlogCtx::new(table_map.len(), &fde))?;
+        Ok(table_map.into_owned())
+    }
+
+    /// Parses rows of a rows event of the given type using the iterator
+    /// configured by `configure` (e.g. `|rows| rows.skip_invisible_columns()`).
+    fn parse_rows(
+        table_map: &[u8],
+        event_type: EventType,
+        rows_event: &[u8],
+        configure: impl for<'a> FnOnce(RowsEventRows<'a>) -> RowsEventRows<'a>,
+    ) -> io::Result<Rows> {
+        let fde = FormatDescriptionEvent::new(BinlogVersion::Version4);
+        let table_map = parse_table_map(table_map)?;
+        let ctx = RowsEventCtx {
+            event_type,
+            binlog_ctx: BinlogCtx::new(rows_event.len(), &fde),
+        };
+        let rows_event: RowsEvent = ParseBuf(rows_event).parse(ctx)?;
+        let rows = configure(rows_event.rows(&table_map)).collect();
+        Ok(rows)
+    }
+
+    #[test]
+    fn should_detect_schema_mismatch() -> io::Result<()> {
+        let header = [1, 0, 0, 0, 0, 0, 0, 0, 2, 0];
+
+        // a single `LONG` column
+        let event = [&header[..], &[1, 0b1, 0, 42, 0, 0, 0]].concat();
+        let rows = parse_rows(TABLE_MAP, EventType::WRITE_ROWS_EVENT, &event, |rows| rows)?;
+        assert_eq!(rows.len(), 1);
+        let (_, after) = rows.into_iter().next().unwrap()?;
+        assert_eq!(
+            after.unwrap().unwrap(),
+            vec![BinlogValue::Value(crate::value::Value::Int(42))]
+        );
+
+        // one column too long
+        let event = [&header[..], &[2, 0b11, 0, 42, 0, 0, 0, 43, 0, 0, 0]].concat();
+        let rows = parse_rows(TABLE_MAP, EventType::WRITE_ROWS_EVENT, &event, |rows| rows)?;
+        assert_eq!(rows.len(), 1);
+        let err = rows.into_iter().next().unwrap().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err.to_string().contains("schema mismatch"));
+
+        // row image contains a value unknown to the table map
+        let event = [&header[..], &[1, 0b1, 0, 42, 0, 0, 0, 42, 0, 0, 0]].concat();
+        let rows = parse_rows(TABLE_MAP, EventType::WRITE_ROWS_EVENT, &event, |rows| rows)?;
+        assert_eq!(rows.len(), 2);
+        let err = rows.into_iter().nth(1).unwrap().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+        assert_eq!(
+            err.to_string(),
+            "unexpected EOF at offset 1, 3 bytes remaining"
+        );
+
+        // it is reported as a schema mismatch if drift detection is enabled
+        let rows = parse_rows(TABLE_MAP, EventType::WRITE_ROWS_EVENT, &event, |rows| {
+            rows.detect_schema_drift()
+        })?;
+        assert_eq!(rows.len(), 1);
+        let err = rows.into_iter().next().unwrap().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err.to_string().contains("schema mismatch"));
+        assert!(err.to_string().contains("unexpected EOF at offset 1"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_detect_schema_drift() -> io::Result<()> {
+        use EventType::WRITE_ROWS_EVENT;
+
+        let mut table_map = TABLE_MAP.to_vec();
+        let len = table_map.len();
+        // the table map says `TINY` while the row images contain `LONG` values
+        table_map[len - 3] = 1;
+
+        let event = [1, 0, 0, 0, 0, 0, 0, 0, 2, 0, 1, 0b1, 0, 42, 0, 0, 0];
+
+        // residual bytes are decoded as a corrupted row before the desync is detected
+        let rows = parse_rows(&table_map, WRITE_ROWS_EVENT, &event, |rows| rows)?;
+        assert_eq!(rows.len(), 3);
+        assert!(rows[1].is_ok());
+        assert!(rows[2].is_err());
+
+        let mut rows = parse_rows(&table_map, WRITE_ROWS_EVENT, &event, |rows| {
+            rows.detect_schema_drift()
+        })?;
+        assert_eq!(rows.len(), 1);
+        let err = rows.pop().unwrap().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        let err = err.get_ref().unwrap();
+        assert!(err.downcast_ref::<SchemaMismatchError>().is_some());
+
+        // rows are yielded as is if there is no desync
+        let rows = parse_rows(TABLE_MAP, WRITE_ROWS_EVENT, &event, |rows| {
+            rows.detect_schema_drift()
+        })?;
+        assert_eq!(rows.len(), 1);
+        assert!(rows[0].is_ok());
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_read_set_packed_as_string() -> io::Result<()> {
+        let mut table_map = TABLE_MAP.to_vec();
+        let len = table_map.len();
+        // MYSQL_TYPE_STRING with (real_type, pack_length) metadata of a 10-member SET
+        table_map.splice((len - 3).., [254, 2, 0xF8, 2, 0]);
+
+        // members 1 and 10 are set
+        let event = [1, 0, 0, 0, 0, 0, 0, 0, 2, 0, 1, 0b1, 0, 0x01, 0x02];
+        let mut rows = parse_rows(&table_map, EventType::WRITE_ROWS_EVENT, &event, |rows| rows)?;
+        assert_eq!(rows.len(), 1);
+        let (_, after) = rows.pop().unwrap()?;
+        let after = after.unwrap();
+        assert_eq!(
+            after.columns_ref()[0].column_type(),
+            crate::constants::ColumnType::MYSQL_TYPE_SET
+        );
+        assert_eq!(after.unwrap(), vec![BinlogValue::bytes(vec![0x01, 0x02])]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_bound_check_enum_packed_as_string() -> io::Result<()> {
+        let mut table_map = TABLE_MAP.to_vec();
+        let len = table_map.len();
+        // MYSQL_TYPE_STRING with (real_type, pack_length) metadata of an ENUM('a', 'b')
+        // followed by the ENUM_STR_VALUE optional metadata
+        table_map.splice((len - 3).., [254, 2, 0xF7, 1, 0, 6, 5, 2, 1, b'a', 1, b'b']);
+
+        let counts = parse_table_map(&table_map)?.enum_or_set_member_counts()?;
+        assert_eq!(counts, vec![Some(2)]);
+
+        // the second member is valid
+        let event = [1, 0, 0, 0, 0, 0, 0, 0, 2, 0, 1, 0b1, 0, 2];
+        let mut rows = parse_rows(&table_map, EventType::WRITE_ROWS_EVENT, &event, |rows| rows)?;
+        let (_, after) = rows.pop().unwrap()?;
+        let after = after.unwrap();
+        assert_eq!(
+            after.columns_ref()[0].column_type(),
+            crate::constants::ColumnType::MYSQL_TYPE_ENUM
+        );
+        assert!(after.as_ref(0).unwrap().eq_i64(2));
+
+        // the third one is out of bounds
+        let event = [1, 0, 0, 0, 0, 0, 0, 0, 2, 0, 1, 0b1, 0, 3];
+        let mut rows = parse_rows(&table_map, EventType::WRITE_ROWS_EVENT, &event, |rows| rows)?;
+        let err = rows.pop().unwrap().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_read_enum_key_of_minimal_image() -> io::Result<()> {
+        // `CREATE TABLE t (k ENUM('a', 'b') PRIMARY KEY, v INT)`
+        const TABLE_MAP: &[u8] = &[
+            1, 0, 0, 0, 0, 0, // table id
+            0, 0, // flags
+            2, b'd', b'b', 0, // database name
+            1, b't', 0, // table name
+            2, // columns count
+            254, 3, // MYSQL_TYPE_STRING (packed ENUM), MYSQL_TYPE_LONG
+            2, 0xF7, 1,    // columns metadata
+            0b10, // null bitmask
+            6, 5, 2, 1, b'a', 1, b'b', // ENUM_STR_VALUE
+        ];
+
+        // `binlog_row_image=MINIMAL`: the before image contains the primary key only
+        let event = [1, 0, 0, 0, 0, 0, 0, 0, 2, 0, 2, 0b01, 0, 2];
+        let rows = parse_rows(TABLE_MAP, EventType::DELETE_ROWS_EVENT, &event, |rows| rows)?
+            .into_iter()
+            .collect::<io::Result<Vec<_>>>()?;
+        assert_eq!(rows.len(), 1);
+
+        let (before, after) = rows.into_iter().next().unwrap();
+        assert!(after.is_none());
+        let before = before.unwrap();
+        assert_eq!(before.len(), 1);
+        assert_eq!(
+            before.columns_ref()[0].column_type(),
+            crate::constants::ColumnType::MYSQL_TYPE_ENUM
+        );
+        let index = before.as_ref(0).unwrap();
+        assert!(index.eq_i64(2));
+
+        // resolve the member name using the optional metadata
+        let table_map = parse_table_map(TABLE_MAP)?;
+        let name = table_map
+            .iter_optional_meta()
+            .find_map(|field| match field {
+                Ok(OptionalMetadataField::EnumStrValue(values)) => values
+                    .iter_values()
+                    .next()
+                    .and_then(|x| x.ok())
+                    .map(|x| x.values()[1].value().into_owned()),
+                _ => None,
+            });
+        assert_eq!(name.as_deref(), Some("b"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_skip_invisible_columns() -> io::Result<()> {
+        // `CREATE TABLE t (a INT, INDEX ((a + 1)))` – the functional index adds
+        // a hidden generated `BIGINT` column, that is present in row images
+        const TABLE_MAP: &[u8] = &[
+            1, 0, 0, 0, 0, 0, // table id
+            0, 0, // flags
+            2, b'd', b'b', 0, // database name
+            1, b't', 0, // table name
+            2, // columns count
+            3, 8,    // MYSQL_TYPE_LONG, MYSQL_TYPE_LONGLONG
+            0,    // columns metadata
+            0b10, // null bitmask
+            12, 1, 0x80, // COLUMN_VISIBILITY: 0b1000_0000, i.e. the second column is hidden
+        ];
+
+        let table_map = parse_table_map(TABLE_MAP)?;
+        let visibility = table_map.column_visibility().unwrap();
+        assert!(visibility[0]);
+        assert!(!visibility[1]);
+
+        let header = [1, 0, 0, 0, 0, 0, 0, 0, 2, 0];
+        let row = [0, 42, 0, 0, 0, 43, 0, 0, 0, 0, 0, 0, 0];
+        let event = [&header[..], &[2, 0b11], &row[..], &row[..]].concat();
+
+        let rows = parse_rows(TABLE_MAP, EventType::WRITE_ROWS_EVENT, &event, |rows| rows)?
+            .into_iter()
+            .collect::<io::Result<Vec<_>>>()?;
+        assert_eq!(rows.len(), 2);
+        let (_, after) = rows.into_iter().next().unwrap();
+        assert_eq!(after.unwrap().len(), 2);
+
+        let rows = parse_rows(TABLE_MAP, EventType::WRITE_ROWS_EVENT, &event, |rows| {
+            rows.skip_invisible_columns()
+        })?
+        .into_iter()
+        .collect::<io::Result<Vec<_>>>()?;
+        assert_eq!(rows.len(), 2);
+        for (_, after) in rows {
+            let after = after.unwrap();
+            assert_eq!(after.columns_ref()[0].name_str(), "@0");
+            assert_eq!(
+                after.unwrap(),
+                vec![BinlogValue::Value(crate::value::Value::Int(42))]
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_read_rows_event_with_and_without_checksum() -> io::Result<()> {
+        use crate::binlog::{
+            consts::{BinlogChecksumAlg, EventFlags},
+            events::{BinlogEventFooter, Event, EventData, RowsEventData},
+        };
+
+        let table_map = parse_table_map(TABLE_MAP)?;
+
+        let body = [1, 0, 0, 0, 0, 0, 0, 0, 2, 0, 1, 0b1, 0, 42, 0, 0, 0];
+        let read_rows = |fde: &FormatDescriptionEvent<'_>, with_checksum: bool| {
+            let event_size = BinlogEventHeader::LEN
+                + body.len()
+                + if with_checksum {
+                    BinlogEventFooter::BINLOG_CHECKSUM_LEN
+                } else {
+                    0
+                };
+            let header = BinlogEventHeader::new(
+                0,
+                EventType::WRITE_ROWS_EVENT,
+                1,
+                event_size as u32,
+                0,
+                EventFlags::empty(),
+            );
+            let mut bytes = Vec::new();
+            header.serialize(&mut bytes);
+            bytes.extend_from_slice(&body);
+            if with_checksum {
+                let crc = crc32fast::hash(&bytes);
+                bytes.extend_from_slice(&crc.to_le_bytes());
+            }
+
+            let event = Event::read(fde, &bytes[..])?;
+            if let Some(checksum) = event.checksum() {
+                assert_eq!(event.data(), &body[..]);
+                assert_eq!(
+                    u32::from_le_bytes(checksum),
+                    event.calc_checksum(BinlogChecksumAlg::BINLOG_CHECKSUM_ALG_CRC32)
+                );
+            }
+
+            match event.read_data()? {
+                Some(EventData::RowsEvent(RowsEventData::WriteRowsEvent(ev))) => ev
+                    .rows(&table_map)
+                    .map(|row| row.map(|(_, after)| after.unwrap().unwrap()))
+                    .collect::<io::Result<Vec<_>>>(),
+                other => panic!("unexpected event data: {:?}", other),
+            }
+        };
+
+        let no_checksum = FormatDescriptionEvent::new(BinlogVersion::Version4);
+        let crc32 = FormatDescriptionEvent::new(BinlogVersion::Version4).with_footer(
+            BinlogEventFooter::new(BinlogChecksumAlg::BINLOG_CHECKSUM_ALG_CRC32),
+        );
+
+        let expected = vec![vec![BinlogValue::Value(crate::value::Value::Int(42))]];
+        assert_eq!(read_rows(&no_checksum, false)?, expected);
+        assert_eq!(read_rows(&crc32, true)?, expected);
+
+        // checksum is misread as row data if the format description does not declare it
+        let err = read_rows(&no_checksum, true).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+
+        Ok(())
+    }
+}
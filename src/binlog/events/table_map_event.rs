@@ -180,6 +180,102 @@ impl<'a> TableMapEvent<'a> {
         None
     }
 
+    /// Returns charset+collation id of every column as given by the optional metadata.
+    ///
+    /// Only character columns (see [`ColumnType::is_character_type`]) have a charset,
+    /// so the value is `None` for other columns and for tables without
+    /// [`OptionalMetadataField::DefaultCharset`] or [`OptionalMetadataField::ColumnCharset`].
+    /// Returns an error if the optional metadata is malformed.
+    pub fn column_charsets(&'a self) -> io::Result<Vec<Option<u16>>> {
+        let count = self.columns_type.len();
+        let mut charsets = vec![None; count];
+        let character_columns = (0..count)
+            .filter(|&i| matches!(self.get_column_type(i), Ok(Some(ty)) if ty.is_character_type()))
+            .collect::<Vec<_>>();
+
+        for field in self.iter_optional_meta() {
+            match field? {
+                OptionalMetadataField::DefaultCharset(default_charset) => {
+                    for &i in &character_columns {
+                        charsets[i] = Some(default_charset.default_charset());
+                    }
+                    for non_default in default_charset.iter_non_default() {
+                        let non_default = non_default?;
+                        let i = character_columns
+                            .get(non_default.column_index() as usize)
+                            .ok_or_else(|| {
+                                io::Error::new(
+                                    io::ErrorKind::InvalidData,
+                                    "charset column index is out of range",
+                                )
+                            })?;
+                        charsets[*i] = Some(non_default.charset());
+                    }
+                }
+                OptionalMetadataField::ColumnCharset(column_charsets) => {
+                    for (i, charset) in character_columns
+                        .iter()
+                        .zip(column_charsets.iter_charsets())
+                    {
+                        charsets[*i] = Some(charset?);
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        Ok(charsets)
+    }
+
+    /// Returns the number of members of every ENUM and SET column as given by the optional
+    /// metadata (see [`OptionalMetadataField::EnumStrValue`] and
+    /// [`OptionalMetadataField::SetStrValue`]).
+    ///
+    /// ENUM and SET columns are recognized by their real type, so columns packed
+    /// as `MYSQL_TYPE_STRING` are accounted as well (see [`TableMapEvent::get_column_type`]).
+    /// The value is `None` for other columns and for tables without member names.
+    /// Returns an error if the optional metadata is malformed.
+    pub fn enum_or_set_member_counts(&'a self) -> io::Result<Vec<Option<u64>>> {
+        let count = self.columns_type.len();
+        let mut counts = vec![None; count];
+        let columns_of_type = |expected: ColumnType| {
+            (0..count)
+                .filter(|&i| matches!(self.get_column_type(i), Ok(Some(ty)) if ty == expected))
+                .collect::<Vec<_>>()
+        };
+
+        for field in self.iter_optional_meta() {
+            match field? {
+                OptionalMetadataField::EnumStrValue(values) => {
+                    let columns = columns_of_type(ColumnType::MYSQL_TYPE_ENUM);
+                    for (i, values) in columns.iter().zip(values.iter_values()) {
+                        counts[*i] = Some(values?.num_variants());
+                    }
+                }
+                OptionalMetadataField::SetStrValue(values) => {
+                    let columns = columns_of_type(ColumnType::MYSQL_TYPE_SET);
+                    for (i, values) in columns.iter().zip(values.iter_values()) {
+                        counts[*i] = Some(values?.num_variants());
+                    }
+                }
+                _ => (),
+            }
+        }
+
+        Ok(counts)
+    }
+
+    /// Returns column visibility flags given by the optional metadata (if any).
+    ///
+    /// The flag is unset for invisible columns, i.e. for columns declared as `INVISIBLE`
+    /// and for hidden generated columns (e.g. columns of functional indexes).
+    pub fn column_visibility(&'a self) -> Option<&'a BitSlice<u8, Msb0>> {
+        self.iter_optional_meta().find_map(|m| match m {
+            Ok(OptionalMetadataField::ColumnVisibility(bits)) => Some(bits),
+            _ => None,
+        })
+    }
+
     pub fn iter_optional_meta(&'a self) -> OptionalMetadataIter<'a> {
         OptionalMetadataIter {
             columns: &self.columns_type,
@@ -10,12 +10,15 @@
 
 use std::{
     borrow::Cow,
+    cmp::min,
     convert::{TryFrom, TryInto},
     fmt, io,
     marker::PhantomData,
     str::{from_utf8, Utf8Error},
 };
 
+use bytes::BufMut;
+
 use crate::{
     constants::ColumnType,
     io::ParseBuf,
@@ -263,6 +266,29 @@ impl<'a, T: StorageFormat> ComplexValue<'a, T, Object> {
     pub fn iter(&'a self) -> ObjectIter<'a, T> {
         ObjectIter { cur: 0, obj: self }
     }
+
+    /// Returns the value of the given key (if any).
+    ///
+    /// Keys are looked up using a binary search, so they must be sorted the way MySql sorts
+    /// them (by length and then bytewise). Only the key entries visited by the search
+    /// and the resulting value are read.
+    pub fn get(&'a self, key: &[u8]) -> io::Result<Option<Value<'a>>> {
+        let (mut lo, mut hi) = (0, self.element_count);
+        while lo < hi {
+            let mid = lo + (hi - lo) / 2;
+            let mid_key = match self.key_at(mid)? {
+                Some(x) => x,
+                None => return Ok(None),
+            };
+            let mid_key = mid_key.value_raw();
+            match (mid_key.len(), mid_key).cmp(&(key.len(), key)) {
+                std::cmp::Ordering::Less => lo = mid + 1,
+                std::cmp::Ordering::Greater => hi = mid,
+                std::cmp::Ordering::Equal => return self.elem_at(mid),
+            }
+        }
+        Ok(None)
+    }
 }
 
 impl<'a, T: StorageFormat> ComplexValue<'a, T, Array> {
@@ -310,6 +336,54 @@ impl<'a, T: StorageFormat, U: ComplexType> ComplexValue<'a, T, U> {
     }
 }
 
+impl<T: StorageFormat, U: ComplexType> ComplexValue<'static, T, U> {
+    /// Builds an array (`keys` is empty) or an object (`keys.len() == values.len()`).
+    ///
+    /// Keys are stored in the given order and truncated to `u16::MAX` bytes.
+    ///
+    /// Returns `None` if the result doesn't fit into the storage format `T`.
+    pub(crate) fn from_entries(keys: &[&[u8]], values: &[Value<'_>]) -> Option<Self> {
+        let element_count = values.len() as u32;
+        let mut data = vec![0_u8; U::header_size::<T>(element_count)];
+
+        for (i, key) in keys.iter().enumerate() {
+            let key = &key[..min(key.len(), u16::MAX as usize)];
+            let entry_offset = 2 * T::OFFSET_SIZE as usize + T::KEY_ENTRY_SIZE as usize * i;
+            let key_offset = data.len();
+            T::put_offset(key_offset, &mut data[entry_offset..])?;
+            let len_offset = entry_offset + T::OFFSET_SIZE as usize;
+            data[len_offset..(len_offset + 2)].copy_from_slice(&(key.len() as u16).to_le_bytes());
+            data.extend_from_slice(key);
+        }
+
+        for (i, value) in values.iter().enumerate() {
+            let entry_offset = U::value_entry_offset::<T>(element_count, i as u32);
+            let value_type = value.value_type();
+            data[entry_offset] = value_type as u8;
+            if value_type.is_inlined::<T>() {
+                let mut inlined = Vec::with_capacity(T::OFFSET_SIZE as usize);
+                value.serialize_data(&mut inlined);
+                data[(entry_offset + 1)..(entry_offset + 1 + inlined.len())]
+                    .copy_from_slice(&inlined);
+            } else {
+                let value_offset = data.len();
+                T::put_offset(value_offset, &mut data[(entry_offset + 1)..])?;
+                value.serialize_data(&mut data);
+            }
+        }
+
+        let size = data.len();
+        T::put_offset(element_count as usize, &mut data[..])?;
+        T::put_offset(size, &mut data[T::OFFSET_SIZE as usize..])?;
+
+        Some(Self {
+            element_count,
+            data: Cow::Owned(data),
+            __phantom: PhantomData,
+        })
+    }
+}
+
 impl<'a, T: StorageFormat> TryFrom<ComplexValue<'a, T, Array>> for serde_json::Value {
     type Error = JsonbToJsonError;
 
@@ -416,6 +490,14 @@ impl<'a> OpaqueValue<'a> {
             data: self.data.into_owned(),
         }
     }
+
+    /// Returns the packed temporal value stored in the data (first 8 bytes, little-endian).
+    ///
+    /// Returns `None` if the data is too short (the value type isn't checked).
+    fn packed_temporal(&self) -> Option<i64> {
+        let data = self.data.as_bytes().get(..8)?;
+        data.try_into().ok().map(i64::from_le_bytes)
+    }
 }
 
 /// Jsonb Value.
@@ -512,6 +594,56 @@ impl<'a> Value<'a> {
         }))
     }
 
+    /// Returns the JSONB type marker of this value.
+    fn value_type(&self) -> JsonbType {
+        match self {
+            Value::Null | Value::Bool(_) => JsonbType::JSONB_TYPE_LITERAL,
+            Value::I16(_) => JsonbType::JSONB_TYPE_INT16,
+            Value::U16(_) => JsonbType::JSONB_TYPE_UINT16,
+            Value::I32(_) => JsonbType::JSONB_TYPE_INT32,
+            Value::U32(_) => JsonbType::JSONB_TYPE_UINT32,
+            Value::I64(_) => JsonbType::JSONB_TYPE_INT64,
+            Value::U64(_) => JsonbType::JSONB_TYPE_UINT64,
+            Value::F64(_) => JsonbType::JSONB_TYPE_DOUBLE,
+            Value::String(_) => JsonbType::JSONB_TYPE_STRING,
+            Value::SmallArray(_) => JsonbType::JSONB_TYPE_SMALL_ARRAY,
+            Value::LargeArray(_) => JsonbType::JSONB_TYPE_LARGE_ARRAY,
+            Value::SmallObject(_) => JsonbType::JSONB_TYPE_SMALL_OBJECT,
+            Value::LargeObject(_) => JsonbType::JSONB_TYPE_LARGE_OBJECT,
+            Value::Opaque(_) => JsonbType::JSONB_TYPE_OPAQUE,
+        }
+    }
+
+    /// Serializes this value without the type marker.
+    fn serialize_data(&self, buf: &mut Vec<u8>) {
+        match self {
+            Value::Null => buf.put_u8(LiteralType::JSONB_NULL_LITERAL as u8),
+            Value::Bool(true) => buf.put_u8(LiteralType::JSONB_TRUE_LITERAL as u8),
+            Value::Bool(false) => buf.put_u8(LiteralType::JSONB_FALSE_LITERAL as u8),
+            Value::I16(x) => buf.put_i16_le(*x),
+            Value::U16(x) => buf.put_u16_le(*x),
+            Value::I32(x) => buf.put_i32_le(*x),
+            Value::U32(x) => buf.put_u32_le(*x),
+            Value::I64(x) => buf.put_i64_le(*x),
+            Value::U64(x) => buf.put_u64_le(*x),
+            Value::F64(x) => buf.put_f64_le(*x),
+            Value::String(x) => x.serialize(buf),
+            Value::SmallArray(x) => buf.put_slice(&x.data),
+            Value::LargeArray(x) => buf.put_slice(&x.data),
+            Value::SmallObject(x) => buf.put_slice(&x.data),
+            Value::LargeObject(x) => buf.put_slice(&x.data),
+            Value::Opaque(x) => {
+                x.value_type.serialize(buf);
+                x.data.serialize(buf);
+            }
+        }
+    }
+
+    /// Returns true if this value is the JSON `null` literal.
+    pub fn is_null(&self) -> bool {
+        matches!(self, Value::Null)
+    }
+
     /// Returns true if this value is an array.
     pub fn is_array(&self) -> bool {
         matches!(self, Value::SmallArray { .. } | Value::LargeArray { .. })
@@ -548,6 +680,54 @@ impl<'a> Value<'a> {
         }
     }
 
+    /// Returns the number of elements in an array or object
+    /// and `None` for scalar values.
+    pub fn len(&self) -> Option<usize> {
+        self.element_count().map(|x| x as usize)
+    }
+
+    /// Returns `true` if this value is an empty array or object.
+    pub fn is_empty(&self) -> bool {
+        self.element_count() == Some(0)
+    }
+
+    /// Returns the value at the given path (see [`parse_json_path`]), or `None` if there is
+    /// no such value (i.e. a key is missing, an index is out of range, or a leg doesn't match
+    /// the type of the value).
+    ///
+    /// Only the headers of arrays and objects along the path are read, so values that aren't
+    /// on the path aren't parsed (see [`ComplexValue::get`]).
+    pub fn get_path(&self, path: &[JsonPathLeg]) -> io::Result<Option<Value<'static>>> {
+        let (leg, rest) = match path.split_first() {
+            Some(x) => x,
+            None => return Ok(Some(self.clone().into_owned())),
+        };
+
+        let index = |len: u32| match leg {
+            JsonPathLeg::Index(i) => i.resolve(len as usize).map(|x| x as u32),
+            JsonPathLeg::Key(_) => None,
+        };
+
+        let child = match (self, leg) {
+            (Value::SmallObject(x), JsonPathLeg::Key(key)) => x.get(key.as_bytes())?,
+            (Value::LargeObject(x), JsonPathLeg::Key(key)) => x.get(key.as_bytes())?,
+            (Value::SmallArray(x), _) => match index(x.element_count()) {
+                Some(i) => x.elem_at(i)?,
+                None => None,
+            },
+            (Value::LargeArray(x), _) => match index(x.element_count()) {
+                Some(i) => x.elem_at(i)?,
+                None => None,
+            },
+            _ => None,
+        };
+
+        match child {
+            Some(child) => child.get_path(rest),
+            None => Ok(None),
+        }
+    }
+
     /// Returns the field type of an opaque value.
     pub fn field_type(&self) -> Option<ColumnType> {
         match self {
@@ -555,6 +735,157 @@ impl<'a> Value<'a> {
             _ => None,
         }
     }
+
+    /// Returns `(year, month, day)` of an opaque DATE value.
+    pub fn as_date(&self) -> Option<(u16, u8, u8)> {
+        match self {
+            Value::Opaque(x)
+                if matches!(
+                    x.value_type(),
+                    ColumnType::MYSQL_TYPE_DATE | ColumnType::MYSQL_TYPE_NEWDATE
+                ) =>
+            {
+                match crate::binlog::misc::datetime_from_packed(x.packed_temporal()?) {
+                    crate::value::Value::Date(y, m, d, ..) => Some((y, m, d)),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns `(year, month, day, hour, minutes, seconds, micro seconds)`
+    /// of an opaque DATETIME or TIMESTAMP value.
+    pub fn as_datetime(&self) -> Option<(u16, u8, u8, u8, u8, u8, u32)> {
+        use ColumnType::*;
+
+        match self {
+            Value::Opaque(x)
+                if matches!(
+                    x.value_type(),
+                    MYSQL_TYPE_DATETIME
+                        | MYSQL_TYPE_DATETIME2
+                        | MYSQL_TYPE_TIMESTAMP
+                        | MYSQL_TYPE_TIMESTAMP2
+                ) =>
+            {
+                match crate::binlog::misc::datetime_from_packed(x.packed_temporal()?) {
+                    crate::value::Value::Date(y, m, d, h, i, s, us) => Some((y, m, d, h, i, s, us)),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns `(is_negative, days, hours, minutes, seconds, micro seconds)`
+    /// of an opaque TIME value (hours are less than 24, as in `Value::Time`).
+    pub fn as_time(&self) -> Option<(bool, u32, u8, u8, u8, u32)> {
+        match self {
+            Value::Opaque(x)
+                if matches!(
+                    x.value_type(),
+                    ColumnType::MYSQL_TYPE_TIME | ColumnType::MYSQL_TYPE_TIME2
+                ) =>
+            {
+                let (neg, h, i, s, us) =
+                    crate::binlog::misc::time_parts_from_packed(x.packed_temporal()?);
+                Some((neg, h / 24, (h % 24) as u8, i, s, us))
+            }
+            _ => None,
+        }
+    }
+
+    /// Compares two JSONB documents structurally, i.e.:
+    ///
+    /// *   object members are compared regardless of their order;
+    /// *   numbers are compared by value (i.e. `I16(1)`, `U64(1)` and `F64(1.0)` are equal);
+    /// *   storage format (small or large) of arrays and objects doesn't matter.
+    ///
+    /// Malformed documents aren't equal to anything.
+    pub fn structural_eq(&self, other: &Value<'_>) -> bool {
+        self.try_structural_eq(other).unwrap_or(false)
+    }
+
+    fn try_structural_eq(&self, other: &Value<'_>) -> io::Result<bool> {
+        if let (Some(x), Some(y)) = (self.array_elements(), other.array_elements()) {
+            let (x, y) = (x?, y?);
+            if x.len() != y.len() {
+                return Ok(false);
+            }
+            for (x, y) in x.iter().zip(y.iter()) {
+                if !x.try_structural_eq(y)? {
+                    return Ok(false);
+                }
+            }
+            return Ok(true);
+        }
+
+        if let (Some(x), Some(y)) = (self.object_entries(), other.object_entries()) {
+            let (x, mut y) = (x?, y?);
+            if x.len() != y.len() {
+                return Ok(false);
+            }
+            y.sort_by(|(a, _), (b, _)| a.value_raw().cmp(b.value_raw()));
+            for (key, x) in x.iter() {
+                let pos = y.binary_search_by(|(k, _)| k.value_raw().cmp(key.value_raw()));
+                match pos {
+                    Ok(pos) if x.try_structural_eq(&y[pos].1)? => (),
+                    _ => return Ok(false),
+                }
+            }
+            return Ok(true);
+        }
+
+        let eq = match (self, other) {
+            (Value::Null, Value::Null) => true,
+            (Value::Bool(x), Value::Bool(y)) => x == y,
+            (Value::String(x), Value::String(y)) => x.str_raw() == y.str_raw(),
+            (Value::Opaque(x), Value::Opaque(y)) => {
+                x.value_type() == y.value_type() && x.data_raw() == y.data_raw()
+            }
+            (Value::F64(x), Value::F64(y)) => x == y,
+            (Value::F64(x), y) | (y, Value::F64(x)) => {
+                y.as_i128().map(|y| y as f64 == *x).unwrap_or(false)
+            }
+            (x, y) => match (x.as_i128(), y.as_i128()) {
+                (Some(x), Some(y)) => x == y,
+                _ => false,
+            },
+        };
+        Ok(eq)
+    }
+
+    /// Returns the value of an integer JSONB value.
+    fn as_i128(&self) -> Option<i128> {
+        match *self {
+            Value::I16(x) => Some(x as i128),
+            Value::U16(x) => Some(x as i128),
+            Value::I32(x) => Some(x as i128),
+            Value::U32(x) => Some(x as i128),
+            Value::I64(x) => Some(x as i128),
+            Value::U64(x) => Some(x as i128),
+            _ => None,
+        }
+    }
+
+    /// Returns elements of a JSONB array (`None` if it isn't an array).
+    fn array_elements(&self) -> Option<io::Result<Vec<Value<'_>>>> {
+        match self {
+            Value::SmallArray(x) => Some(x.iter().collect()),
+            Value::LargeArray(x) => Some(x.iter().collect()),
+            _ => None,
+        }
+    }
+
+    /// Returns entries of a JSONB object (`None` if it isn't an object).
+    fn object_entries(&self) -> Option<io::Result<Vec<(ObjectKey<'_>, Value<'_>)>>> {
+        match self {
+            Value::SmallObject(x) => Some(x.iter().collect()),
+            Value::LargeObject(x) => Some(x.iter().collect()),
+            _ => None,
+        }
+    }
 }
 
 impl<'a> TryFrom<Value<'a>> for serde_json::Value {
@@ -581,6 +912,172 @@ impl<'a> TryFrom<Value<'a>> for serde_json::Value {
     }
 }
 
+/// Converts a JSONB value to JSON in a single pass over the JSONB tree
+/// (unlike `TryFrom<Value>`, the JSONB value isn't consumed).
+///
+/// Opaque values are converted the way MySql renders them:
+///
+/// *   DECIMAL values become strings (so that precision isn't lost);
+/// *   DATE, DATETIME, TIMESTAMP and TIME values become strings,
+///     such as `2021-12-31 23:59:59.500000`;
+/// *   other opaque values become `base64:type<type>:<base64 data>` strings.
+impl<'a> TryFrom<&Value<'a>> for serde_json::Value {
+    type Error = JsonbToJsonError;
+
+    fn try_from(value: &Value<'a>) -> Result<Self, Self::Error> {
+        fn array<T: StorageFormat>(
+            x: &ComplexValue<'_, T, Array>,
+        ) -> Result<serde_json::Value, JsonbToJsonError> {
+            let mut values = Vec::new();
+            for value in x.iter() {
+                values.push(serde_json::Value::try_from(&value?)?);
+            }
+            Ok(serde_json::Value::Array(values))
+        }
+
+        fn object<T: StorageFormat>(
+            x: &ComplexValue<'_, T, Object>,
+        ) -> Result<serde_json::Value, JsonbToJsonError> {
+            let mut k_vs = serde_json::Map::new();
+            for entry in x.iter() {
+                let (k, v) = entry?;
+                k_vs.insert(
+                    from_utf8(k.value_raw())?.to_owned(),
+                    serde_json::Value::try_from(&v)?,
+                );
+            }
+            Ok(serde_json::Value::Object(k_vs))
+        }
+
+        match value {
+            Value::Null => Ok(serde_json::Value::Null),
+            Value::Bool(x) => Ok(serde_json::Value::Bool(*x)),
+            Value::I16(x) => Ok((*x).into()),
+            Value::U16(x) => Ok((*x).into()),
+            Value::I32(x) => Ok((*x).into()),
+            Value::U32(x) => Ok((*x).into()),
+            Value::I64(x) => Ok((*x).into()),
+            Value::U64(x) => Ok((*x).into()),
+            Value::F64(x) => Ok((*x).into()),
+            Value::String(s) => Ok(from_utf8(s.str_raw())?.into()),
+            Value::SmallArray(x) => array(x),
+            Value::LargeArray(x) => array(x),
+            Value::SmallObject(x) => object(x),
+            Value::LargeObject(x) => object(x),
+            Value::Opaque(x) => opaque_to_json(x),
+        }
+    }
+}
+
+/// Renders an opaque JSONB value (see `TryFrom<&Value>` for `serde_json::Value`).
+fn opaque_to_json(value: &OpaqueValue<'_>) -> Result<serde_json::Value, JsonbToJsonError> {
+    use ColumnType::*;
+
+    let malformed = || {
+        JsonbToJsonError::InvalidJsonb(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Malformed opaque value",
+        ))
+    };
+    let data = value.data_raw();
+
+    let string = match value.value_type() {
+        MYSQL_TYPE_NEWDECIMAL => match data {
+            // decimal data is prefixed with precision and scale
+            [precision, scale, bin @ ..] if scale <= precision => {
+                crate::binlog::decimal::Decimal::read_bin(
+                    bin,
+                    *precision as usize,
+                    *scale as usize,
+                    false,
+                )?
+                .to_string()
+            }
+            _ => return Err(malformed()),
+        },
+        ty @ (MYSQL_TYPE_DATE
+        | MYSQL_TYPE_NEWDATE
+        | MYSQL_TYPE_DATETIME
+        | MYSQL_TYPE_DATETIME2
+        | MYSQL_TYPE_TIMESTAMP
+        | MYSQL_TYPE_TIMESTAMP2
+        | MYSQL_TYPE_TIME
+        | MYSQL_TYPE_TIME2) => {
+            let packed = value.packed_temporal().ok_or_else(malformed)?;
+            let value = match ty {
+                MYSQL_TYPE_TIME | MYSQL_TYPE_TIME2 => crate::binlog::misc::time_from_packed(packed),
+                _ => crate::binlog::misc::datetime_from_packed(packed),
+            };
+            match (ty, value) {
+                (MYSQL_TYPE_DATE | MYSQL_TYPE_NEWDATE, crate::value::Value::Date(y, m, d, ..)) => {
+                    format!("{:04}-{:02}-{:02}", y, m, d)
+                }
+                (_, value) => value.as_sql(false).trim_matches('\'').to_owned(),
+            }
+        }
+        ty => format!("base64:type{}:{}", ty as u8, base64::encode(data)),
+    };
+
+    Ok(string.into())
+}
+
+/// Converts a JSON value to its JSONB representation (the way MySql does it).
+///
+/// Integers are stored using the smallest suitable type, object keys are sorted by length
+/// and then bytewise, and the large storage format is only used for arrays and objects
+/// that doesn't fit into the small one.
+///
+/// # Panic
+///
+/// Will panic if an array or object doesn't fit into the large storage format (4GiB).
+impl From<serde_json::Value> for Value<'static> {
+    fn from(value: serde_json::Value) -> Self {
+        match value {
+            serde_json::Value::Null => Value::Null,
+            serde_json::Value::Bool(x) => Value::Bool(x),
+            serde_json::Value::Number(x) => {
+                if let Some(x) = x.as_i64() {
+                    if let Ok(x) = i16::try_from(x) {
+                        Value::I16(x)
+                    } else if let Ok(x) = i32::try_from(x) {
+                        Value::I32(x)
+                    } else {
+                        Value::I64(x)
+                    }
+                } else if let Some(x) = x.as_u64() {
+                    Value::U64(x)
+                } else {
+                    Value::F64(x.as_f64().unwrap_or_default())
+                }
+            }
+            serde_json::Value::String(x) => Value::String(JsonbString::new(x.into_bytes())),
+            serde_json::Value::Array(x) => {
+                let values = x.into_iter().map(Value::from).collect::<Vec<_>>();
+                ComplexValue::from_entries(&[], &values)
+                    .map(Value::SmallArray)
+                    .or_else(|| ComplexValue::from_entries(&[], &values).map(Value::LargeArray))
+                    .expect("JSONB array is too large")
+            }
+            serde_json::Value::Object(x) => {
+                let mut entries = x.into_iter().collect::<Vec<_>>();
+                entries.sort_by(|(a, _), (b, _)| (a.len(), a).cmp(&(b.len(), b)));
+                let keys = entries
+                    .iter()
+                    .map(|(k, _)| k.as_bytes())
+                    .collect::<Vec<_>>();
+                let values = entries
+                    .iter()
+                    .map(|(_, v)| Value::from(v.clone()))
+                    .collect::<Vec<_>>();
+                ComplexValue::from_entries(&keys, &values)
+                    .map(Value::SmallObject)
+                    .or_else(|| ComplexValue::from_entries(&keys, &values).map(Value::LargeObject))
+                    .expect("JSONB object is too large")
+            }
+        }
+    }
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum JsonbToJsonError {
     #[error("JSONB value is invalid: {}", _0)]
@@ -608,6 +1105,120 @@ impl<'de> MyDeserialize<'de> for Value<'de> {
     }
 }
 
+impl MySerialize for Value<'_> {
+    fn serialize(&self, buf: &mut Vec<u8>) {
+        buf.put_u8(self.value_type() as u8);
+        self.serialize_data(buf);
+    }
+}
+
+/// Array index within a [`JsonPathLeg`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum JsonArrayIndex {
+    /// `[N]` – zero-based offset from the start of an array.
+    FromStart(usize),
+    /// `[last-N]` – offset from the last element of an array.
+    FromEnd(usize),
+}
+
+impl JsonArrayIndex {
+    /// Resolves this index against an array of the given length.
+    ///
+    /// Returns `None` if a `last-N` index points before the start of the array.
+    pub fn resolve(&self, len: usize) -> Option<usize> {
+        match *self {
+            JsonArrayIndex::FromStart(n) => Some(n),
+            JsonArrayIndex::FromEnd(n) => len.checked_sub(1)?.checked_sub(n),
+        }
+    }
+}
+
+/// A leg of a JSON path without wildcards (as used within partial JSON updates).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum JsonPathLeg {
+    /// `.key` or `."key"` – object member.
+    Key(String),
+    /// `[N]` or `[last-N]` – array element.
+    Index(JsonArrayIndex),
+}
+
+/// Parses a JSON path without wildcards, such as `$.a[0]."b c"[last]`.
+///
+/// Returns `None` if the path is malformed or contains wildcards or ranges.
+pub fn parse_json_path(path: &str) -> Option<Vec<JsonPathLeg>> {
+    let mut rest = path.trim_start().strip_prefix('$')?;
+    let mut legs = Vec::new();
+
+    loop {
+        rest = rest.trim_start();
+        if rest.is_empty() {
+            break;
+        }
+
+        if let Some(member) = rest.strip_prefix('.') {
+            let member = member.trim_start();
+            if member.starts_with('"') {
+                // quoted key uses JSON string escaping
+                let mut end = None;
+                let mut escaped = false;
+                for (i, c) in member.char_indices().skip(1) {
+                    match c {
+                        '\\' if !escaped => escaped = true,
+                        '"' if !escaped => {
+                            end = Some(i);
+                            break;
+                        }
+                        _ => escaped = false,
+                    }
+                }
+                let end = end?;
+                legs.push(JsonPathLeg::Key(
+                    serde_json::from_str(&member[..=end]).ok()?,
+                ));
+                rest = &member[(end + 1)..];
+            } else {
+                let end = member
+                    .find(|c: char| c == '.' || c == '[' || c.is_whitespace())
+                    .unwrap_or(member.len());
+                let key = &member[..end];
+                if key.is_empty() || key.contains('*') {
+                    return None;
+                }
+                legs.push(JsonPathLeg::Key(key.into()));
+                rest = &member[end..];
+            }
+        } else if let Some(index) = rest.strip_prefix('[') {
+            let end = index.find(']')?;
+            let spec = index[..end].trim();
+            let index = match spec.strip_prefix("last") {
+                Some(offset) => {
+                    let offset = offset.trim();
+                    if offset.is_empty() {
+                        JsonArrayIndex::FromEnd(0)
+                    } else {
+                        let offset = offset.strip_prefix('-')?.trim();
+                        JsonArrayIndex::FromEnd(parse_path_index(offset)?)
+                    }
+                }
+                None => JsonArrayIndex::FromStart(parse_path_index(spec)?),
+            };
+            legs.push(JsonPathLeg::Index(index));
+            rest = &rest[(end + 2)..];
+        } else {
+            return None;
+        }
+    }
+
+    Some(legs)
+}
+
+fn parse_path_index(index: &str) -> Option<usize> {
+    if index.is_empty() || !index.bytes().all(|x| x.is_ascii_digit()) {
+        return None;
+    }
+    index.parse().ok()
+}
+
 /// Type of a complex jsonb value (array or object).
 pub trait ComplexType {
     const IS_ARRAY: bool;
@@ -668,6 +1279,11 @@ pub trait StorageFormat {
     const VALUE_ENTRY_SIZE: u8 = Self::OFFSET_SIZE + 1;
 
     fn eat_offset(buf: &mut ParseBuf<'_>) -> u32;
+
+    /// Writes the offset into the beginning of `buf`.
+    ///
+    /// Returns `None` if the offset doesn't fit into `Self::OFFSET_SIZE`.
+    fn put_offset(offset: usize, buf: &mut [u8]) -> Option<()>;
 }
 
 /// Small array/object storage format. See [`StorageFormat`].
@@ -681,6 +1297,12 @@ impl StorageFormat for Small {
     fn eat_offset(buf: &mut ParseBuf<'_>) -> u32 {
         buf.eat_u16_le() as u32
     }
+
+    fn put_offset(offset: usize, buf: &mut [u8]) -> Option<()> {
+        let offset = u16::try_from(offset).ok()?;
+        buf[..2].copy_from_slice(&offset.to_le_bytes());
+        Some(())
+    }
 }
 
 /// Large array/object storage format. See [`StorageFormat`].
@@ -694,6 +1316,12 @@ impl StorageFormat for Large {
     fn eat_offset(buf: &mut ParseBuf<'_>) -> u32 {
         buf.eat_u32_le()
     }
+
+    fn put_offset(offset: usize, buf: &mut [u8]) -> Option<()> {
+        let offset = u32::try_from(offset).ok()?;
+        buf[..4].copy_from_slice(&offset.to_le_bytes());
+        Some(())
+    }
 }
 
 /// 1-byte JSONB type marker
@@ -797,3 +1425,369 @@ impl TryFrom<u8> for LiteralType {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::binlog::value::BinlogValue;
+
+    fn round_trip(json: serde_json::Value) -> io::Result<Value<'static>> {
+        let mut bytes = Vec::new();
+        Value::from(json.clone()).serialize(&mut bytes);
+
+        let value: Value = ParseBuf(&bytes).parse(())?;
+        assert_eq!(serde_json::Value::try_from(value.clone()).unwrap(), json);
+        Ok(value.into_owned())
+    }
+
+    #[test]
+    fn should_convert_by_reference() -> io::Result<()> {
+        use std::str::FromStr;
+
+        use crate::binlog::{decimal::Decimal, misc::datetime_packed_from_parts};
+
+        let mut decimal = vec![10, 2];
+        Decimal::from_str("00000012.50")
+            .unwrap()
+            .write_bin(&mut decimal)?;
+        let packed = datetime_packed_from_parts(2021, 12, 31, 23, 59, 59, 500_000);
+
+        let nested = Value::from(json!({ "b": [1, "x", null] }));
+        let object = ComplexValue::<Small, Object>::from_entries(
+            &[b"a", b"d", b"e", b"g", b"t", b"u"],
+            &[
+                nested,
+                Value::Opaque(OpaqueValue::new(ColumnType::MYSQL_TYPE_NEWDECIMAL, decimal)),
+                Value::Opaque(OpaqueValue::new(
+                    ColumnType::MYSQL_TYPE_DATE,
+                    packed.to_le_bytes().to_vec(),
+                )),
+                Value::Opaque(OpaqueValue::new(
+                    ColumnType::MYSQL_TYPE_GEOMETRY,
+                    &b"\x01\x02"[..],
+                )),
+                Value::Opaque(OpaqueValue::new(
+                    ColumnType::MYSQL_TYPE_DATETIME2,
+                    packed.to_le_bytes().to_vec(),
+                )),
+                Value::U64(u64::MAX),
+            ],
+        )
+        .unwrap();
+
+        // walk the parsed document
+        let mut bytes = Vec::new();
+        Value::SmallObject(object).serialize(&mut bytes);
+        let value: Value = ParseBuf(&bytes).parse(())?;
+
+        assert_eq!(
+            serde_json::Value::try_from(&value).unwrap(),
+            json!({
+                "a": { "b": [1, "x", null] },
+                "d": "12.50",
+                "e": "2021-12-31",
+                "g": "base64:type255:AQI=",
+                "t": "2021-12-31 23:59:59.500000",
+                "u": u64::MAX,
+            })
+        );
+        assert!(matches!(
+            serde_json::Value::try_from(value),
+            Err(JsonbToJsonError::Opaque)
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_decode_opaque_temporals() -> io::Result<()> {
+        use crate::binlog::misc::{datetime_packed_from_parts, time_packed_from_parts};
+
+        let opaque = |value_type, packed: i64| {
+            Value::Opaque(OpaqueValue::new(value_type, packed.to_le_bytes().to_vec()))
+        };
+        let date = datetime_packed_from_parts(2021, 1, 1, 0, 0, 0, 0);
+        let datetime = datetime_packed_from_parts(2021, 12, 31, 23, 59, 59, 500_000);
+
+        // {"t": CAST('2021-01-01' AS DATE)}
+        let object = ComplexValue::<Small, Object>::from_entries(
+            &[b"t"],
+            &[opaque(ColumnType::MYSQL_TYPE_DATE, date)],
+        )
+        .unwrap();
+        let mut bytes = Vec::new();
+        Value::SmallObject(object).serialize(&mut bytes);
+        let value: Value = ParseBuf(&bytes).parse(())?;
+        let t = value.get_path(&[JsonPathLeg::Key("t".into())])?.unwrap();
+        assert_eq!(t.as_date(), Some((2021, 1, 1)));
+        assert_eq!(t.as_datetime(), None);
+        assert_eq!(t.as_time(), None);
+
+        let t = opaque(ColumnType::MYSQL_TYPE_DATETIME, datetime);
+        assert_eq!(t.as_datetime(), Some((2021, 12, 31, 23, 59, 59, 500_000)));
+        assert_eq!(t.as_date(), None);
+
+        let t = opaque(
+            ColumnType::MYSQL_TYPE_TIME,
+            time_packed_from_parts(true, 838, 59, 58, 999_999),
+        );
+        assert_eq!(t.as_time(), Some((true, 34, 22, 59, 58, 999_999)));
+
+        assert_eq!(
+            Value::Opaque(OpaqueValue::new(
+                ColumnType::MYSQL_TYPE_DATE,
+                &[0_u8; 4][..]
+            ))
+            .as_date(),
+            None
+        );
+        assert_eq!(Value::I16(1).as_date(), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_parse_large_document() -> io::Result<()> {
+        // offsets of the values below exceed 64KiB, so the large format is required
+        let doc = json!({
+            "a": "x".repeat(70_000),
+            "b": [1, "y".repeat(70_000), { "c": -3 }],
+            "d": "tail",
+        });
+        let value = round_trip(doc.clone())?;
+        let object = match value {
+            Value::LargeObject(ref x) => x,
+            _ => panic!("large object expected"),
+        };
+        assert_eq!(object.element_count(), 3);
+        assert!(ComplexValue::<Small, Object>::from_entries(
+            &[b"a"],
+            &[Value::from(doc["a"].clone())]
+        )
+        .is_none());
+
+        let entries = object.iter().collect::<io::Result<Vec<_>>>()?;
+        assert_eq!(entries[2].0.value_raw(), b"d");
+        assert_eq!(
+            serde_json::Value::try_from(entries[2].1.clone()).unwrap(),
+            json!("tail")
+        );
+        let array = match entries[1].1 {
+            Value::LargeArray(ref x) => x,
+            ref x => panic!("large array expected, got {:?}", x),
+        };
+        assert_eq!(
+            serde_json::Value::try_from(array.elem_at(2)?.unwrap()).unwrap(),
+            json!({ "c": -3 })
+        );
+
+        let mut bytes = Vec::new();
+        value.serialize(&mut bytes);
+        assert_eq!(bytes[0], JsonbType::JSONB_TYPE_LARGE_OBJECT as u8);
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_get_path() -> io::Result<()> {
+        let doc = json!({
+            "a": { "b": [10, { "c": "x" }, 30] },
+            "long key": true,
+            "z": null,
+        });
+        let value = round_trip(doc)?;
+
+        let get = |path: &str| value.get_path(&parse_json_path(path).unwrap());
+        assert_eq!(
+            get("$.a.b[1].c")?,
+            Some(Value::String(JsonbString::new(&b"x"[..])))
+        );
+        assert_eq!(get("$.a.b[last]")?, Some(Value::I16(30)));
+        assert_eq!(get("$.\"long key\"")?, Some(Value::Bool(true)));
+        assert_eq!(get("$.z")?, Some(Value::Null));
+        assert_eq!(
+            serde_json::Value::try_from(get("$.a")?.unwrap()).unwrap(),
+            json!({ "b": [10, { "c": "x" }, 30] })
+        );
+        assert_eq!(get("$.a.missing")?, None);
+        assert_eq!(get("$.a.b[3]")?, None);
+        assert_eq!(get("$.a.b[last-3]")?, None);
+        assert_eq!(get("$.a[0]")?, None);
+        assert_eq!(get("$.a.b.c")?, None);
+        assert_eq!(get("$.z.a")?, None);
+
+        // values that aren't on the path aren't parsed, so corrupted siblings are ignored
+        let mut bytes = Vec::new();
+        Value::from(json!({ "a": "x".repeat(100), "b": 1 })).serialize(&mut bytes);
+        let len = bytes.len();
+        // corrupt the length of the `a` string
+        bytes[len - 101] = 0xFF;
+        let value: Value = ParseBuf(&bytes).parse(())?;
+        assert!(serde_json::Value::try_from(value.clone()).is_err());
+        assert_eq!(
+            value.get_path(&parse_json_path("$.b").unwrap())?,
+            Some(Value::I16(1))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_serialize_json() -> io::Result<()> {
+        round_trip(json!(null))?;
+        round_trip(json!(true))?;
+        round_trip(json!("foo"))?;
+        round_trip(json!(-1.5))?;
+        round_trip(json!(u64::MAX))?;
+        round_trip(json!([1, -70000, i64::MIN, "bar", null, [], {}]))?;
+        round_trip(json!({"b": {"c": [false]}, "a": 1, "aa": "x".repeat(200)}))?;
+
+        // doesn't fit into the small storage format
+        let value = round_trip(json!(["x".repeat(u16::MAX as usize), 70000]))?;
+        assert!(matches!(value, Value::LargeArray(_)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_parse_inlined_int16() -> io::Result<()> {
+        // {"a": 5, "b": -3} in the small storage format
+        let small = [
+            0x00, // small object
+            0x02, 0x00, // element count
+            0x14, 0x00, // size
+            0x12, 0x00, 0x01, 0x00, // key entry "a"
+            0x13, 0x00, 0x01, 0x00, // key entry "b"
+            0x05, 0x05, 0x00, // inlined int16 `5`
+            0x05, 0xFD, 0xFF, // inlined int16 `-3`
+            b'a', b'b',
+        ];
+        // same in the large storage format
+        let large = [
+            0x01, // large object
+            0x02, 0x00, 0x00, 0x00, // element count
+            0x20, 0x00, 0x00, 0x00, // size
+            0x1E, 0x00, 0x00, 0x00, 0x01, 0x00, // key entry "a"
+            0x1F, 0x00, 0x00, 0x00, 0x01, 0x00, // key entry "b"
+            0x05, 0x05, 0x00, 0x00, 0x00, // inlined int16 `5`
+            0x05, 0xFD, 0xFF, 0x00, 0x00, // inlined int16 `-3`
+            b'a', b'b',
+        ];
+
+        for bytes in [&small[..], &large[..]] {
+            let value: Value = ParseBuf(bytes).parse(())?;
+            let object = match value {
+                Value::SmallObject(ref x) => x.elem_at(1)?,
+                Value::LargeObject(ref x) => x.elem_at(1)?,
+                _ => unreachable!(),
+            };
+            assert_eq!(object, Some(Value::I16(-3)));
+            assert_eq!(
+                serde_json::Value::try_from(value).unwrap(),
+                json!({"a": 5, "b": -3})
+            );
+        }
+
+        let mut bytes = Vec::new();
+        Value::from(json!({"a": 5, "b": -3})).serialize(&mut bytes);
+        assert_eq!(bytes, small);
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_compare_structurally() {
+        let values = [Value::I16(1), Value::from(json!([true, null]))];
+        let ab =
+            Value::SmallObject(ComplexValue::from_entries(&[&b"a"[..], b"b"], &values).unwrap());
+        let values = [Value::from(json!([true, null])), Value::F64(1.0)];
+        let ba =
+            Value::LargeObject(ComplexValue::from_entries(&[&b"b"[..], b"a"], &values).unwrap());
+        assert!(ab.structural_eq(&ba));
+        assert!(ba.structural_eq(&ab));
+        assert!(ab.structural_eq(&Value::from(json!({"b": [true, null], "a": 1}))));
+
+        assert!(!ab.structural_eq(&Value::from(json!({"a": 1, "b": [null, true]}))));
+        assert!(!ab.structural_eq(&Value::from(json!({"a": 1, "c": [true, null]}))));
+        assert!(!ab.structural_eq(&Value::from(json!({"a": 1}))));
+        assert!(!ab.structural_eq(&Value::from(json!([1, [true, null]]))));
+        assert!(Value::U64(u64::MAX).structural_eq(&Value::from(json!(u64::MAX))));
+        assert!(!Value::I64(-1).structural_eq(&Value::U64(u64::MAX)));
+
+        let x = BinlogValue::Jsonb(ab);
+        assert!(x.json_eq(&BinlogValue::Jsonb(ba)));
+        assert!(!x.json_eq(&BinlogValue::bytes(&b"{}"[..])));
+    }
+
+    #[test]
+    fn should_parse_json_path() {
+        use JsonArrayIndex::*;
+        use JsonPathLeg::*;
+
+        assert_eq!(parse_json_path("$"), Some(vec![]));
+        assert_eq!(
+            parse_json_path(r#"$.a[0]."b c\"d"[last].e[last - 2]"#),
+            Some(vec![
+                Key("a".into()),
+                Index(FromStart(0)),
+                Key("b c\"d".into()),
+                Index(FromEnd(0)),
+                Key("e".into()),
+                Index(FromEnd(2)),
+            ])
+        );
+        assert_eq!(parse_json_path("$.*"), None);
+        assert_eq!(parse_json_path("$[*]"), None);
+        assert_eq!(parse_json_path("$[1 to 2]"), None);
+        assert_eq!(parse_json_path("a.b"), None);
+        assert_eq!(parse_json_path("$."), None);
+    }
+
+    #[test]
+    fn should_serialize_varlen() -> io::Result<()> {
+        for len in [0_usize, 127, 128, 16_384] {
+            let string = JsonbString::new(vec![b'x'; len]);
+            let mut bytes = Vec::new();
+            string.serialize(&mut bytes);
+            assert_eq!(ParseBuf(&bytes).parse::<JsonbString>(())?, string);
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "nightly")]
+    mod benches {
+        use super::*;
+
+        /// A multi-megabyte document with a single small field of interest.
+        fn document() -> Vec<u8> {
+            let filler = (0..1000)
+                .map(|i| json!({ "id": i, "payload": "x".repeat(1000) }))
+                .collect::<Vec<_>>();
+            let mut bytes = Vec::new();
+            Value::from(json!({ "filler": filler, "target": { "id": 42 } })).serialize(&mut bytes);
+            bytes
+        }
+
+        #[bench]
+        fn bench_get_path(bencher: &mut test::Bencher) {
+            let bytes = document();
+            let path = parse_json_path("$.target.id").unwrap();
+            bencher.iter(|| {
+                let value: Value = ParseBuf(&bytes).parse(()).unwrap();
+                test::black_box(value.get_path(&path).unwrap().unwrap())
+            });
+        }
+
+        #[bench]
+        fn bench_get_path_via_full_conversion(bencher: &mut test::Bencher) {
+            let bytes = document();
+            bencher.iter(|| {
+                let value: Value = ParseBuf(&bytes).parse(()).unwrap();
+                let json = serde_json::Value::try_from(value).unwrap();
+                test::black_box(json["target"]["id"].clone())
+            });
+        }
+    }
+}
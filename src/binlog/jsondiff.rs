@@ -11,10 +11,10 @@ use std::{borrow::Cow, convert::TryFrom, io};
 use crate::{
     io::ParseBuf,
     misc::raw::{bytes::LenEnc, Const, RawBytes, RawInt},
-    proto::MyDeserialize,
+    proto::{MyDeserialize, MySerialize},
 };
 
-use super::jsonb;
+use super::jsonb::{self, JsonArrayIndex, JsonPathLeg, JsonbToJsonError};
 
 /// An operation kind of a JsonDiff object.
 #[allow(non_camel_case_types)]
@@ -53,6 +53,12 @@ impl TryFrom<u8> for JsonDiffOperation {
     }
 }
 
+impl From<JsonDiffOperation> for u8 {
+    fn from(x: JsonDiffOperation) -> Self {
+        x as u8
+    }
+}
+
 /// A class that represents a logical change to a JSON document.
 ///
 /// It is used by row-based replication to send information about changes in JSON documents
@@ -64,7 +70,150 @@ pub struct JsonDiff<'a> {
     value: Option<jsonb::Value<'a>>,
 }
 
+/// An error that might be returned by [`JsonDiff::apply`].
+#[derive(Debug, thiserror::Error)]
+pub enum JsonDiffApplyError {
+    #[error("Invalid JsonDiff path: {}", _0)]
+    InvalidPath(String),
+    #[error("JsonDiff path {} doesn't exist in the document", _0)]
+    PathNotFound(String),
+    #[error("JsonDiff value is missing")]
+    MissingValue,
+    #[error("JsonDiff value is invalid: {}", _0)]
+    InvalidValue(#[from] JsonbToJsonError),
+}
+
 impl<'a> JsonDiff<'a> {
+    /// Creates a new JsonDiff.
+    ///
+    /// `value` is ignored for `JsonDiffOperation::REMOVE`.
+    pub fn new(
+        path: impl Into<Cow<'a, [u8]>>,
+        operation: JsonDiffOperation,
+        value: Option<jsonb::Value<'a>>,
+    ) -> Self {
+        Self {
+            path: RawBytes::new(path),
+            operation: Const::new(operation),
+            value: value.filter(|_| operation != JsonDiffOperation::REMOVE),
+        }
+    }
+
+    /// Applies this diff to the given JSON document.
+    ///
+    /// Paths are navigated leg by leg (e.g. `$.a[2].b`). Missing intermediate containers
+    /// are created for `INSERT` (an object for a key leg and an array for an index leg),
+    /// while `REPLACE` and `REMOVE` require the whole path to exist.
+    ///
+    /// Removing the last element of an array or the last key of an object
+    /// leaves an empty container.
+    pub fn apply(&self, doc: &mut serde_json::Value) -> Result<(), JsonDiffApplyError> {
+        let path = self.path.as_str();
+        let legs = jsonb::parse_json_path(&path)
+            .ok_or_else(|| JsonDiffApplyError::InvalidPath(path.to_string()))?;
+        let not_found = || JsonDiffApplyError::PathNotFound(path.to_string());
+
+        let value = match self.operation() {
+            JsonDiffOperation::REMOVE => serde_json::Value::Null,
+            _ => {
+                let value = self.value.clone().ok_or(JsonDiffApplyError::MissingValue)?;
+                serde_json::Value::try_from(value)?
+            }
+        };
+
+        if self.operation() == JsonDiffOperation::REPLACE {
+            *resolve_json_path(doc, &legs).ok_or_else(not_found)? = value;
+            return Ok(());
+        }
+
+        let (last_leg, parent_legs) = legs.split_last().ok_or_else(not_found)?;
+        let parent = match self.operation() {
+            JsonDiffOperation::INSERT => resolve_or_create_json_path(doc, &legs),
+            _ => resolve_json_path(doc, parent_legs),
+        }
+        .ok_or_else(not_found)?;
+
+        match (self.operation(), parent, last_leg) {
+            (JsonDiffOperation::INSERT, serde_json::Value::Object(map), JsonPathLeg::Key(key)) => {
+                map.insert(key.clone(), value);
+            }
+            (JsonDiffOperation::INSERT, serde_json::Value::Array(arr), JsonPathLeg::Index(i)) => {
+                let index = i.resolve(arr.len()).ok_or_else(not_found)?;
+                arr.insert(std::cmp::min(index, arr.len()), value);
+            }
+            (JsonDiffOperation::REMOVE, serde_json::Value::Object(map), JsonPathLeg::Key(key)) => {
+                map.remove(key).ok_or_else(not_found)?;
+            }
+            (JsonDiffOperation::REMOVE, serde_json::Value::Array(arr), JsonPathLeg::Index(i)) => {
+                let index = i
+                    .resolve(arr.len())
+                    .filter(|x| *x < arr.len())
+                    .ok_or_else(not_found)?;
+                arr.remove(index);
+            }
+            _ => return Err(not_found()),
+        }
+
+        Ok(())
+    }
+
+    /// Converts diffs to an equivalent JSON Patch (RFC 6902) document.
+    ///
+    /// `REPLACE`, `INSERT` and `REMOVE` are mapped to `replace`, `add` and `remove`
+    /// operations respectively, and paths are converted to JSON Pointers
+    /// (i.e. `$.a[1]` becomes `/a/1`).
+    ///
+    /// Array indexes relative to the end of an array (i.e. `$[last]`) couldn't be expressed
+    /// without the document, so [`JsonDiffApplyError::InvalidPath`] is returned for them.
+    pub fn to_json_patch(diffs: &[JsonDiff<'_>]) -> Result<serde_json::Value, JsonDiffApplyError> {
+        let mut patch = Vec::with_capacity(diffs.len());
+        for diff in diffs {
+            let path = diff.path.as_str();
+            let invalid_path = || JsonDiffApplyError::InvalidPath(path.to_string());
+            let legs = jsonb::parse_json_path(&path).ok_or_else(invalid_path)?;
+
+            let mut pointer = String::new();
+            for leg in legs {
+                pointer.push('/');
+                match leg {
+                    JsonPathLeg::Key(key) => {
+                        pointer.push_str(&key.replace('~', "~0").replace('/', "~1"))
+                    }
+                    JsonPathLeg::Index(JsonArrayIndex::FromStart(i)) => {
+                        pointer.push_str(&i.to_string())
+                    }
+                    JsonPathLeg::Index(JsonArrayIndex::FromEnd(_)) => return Err(invalid_path()),
+                }
+            }
+
+            let op = match diff.operation() {
+                JsonDiffOperation::REPLACE => "replace",
+                JsonDiffOperation::INSERT => "add",
+                JsonDiffOperation::REMOVE => "remove",
+            };
+
+            let mut operation = serde_json::Map::new();
+            operation.insert("op".into(), op.into());
+            operation.insert("path".into(), pointer.into());
+            if diff.operation() != JsonDiffOperation::REMOVE {
+                let value = diff.value.clone().ok_or(JsonDiffApplyError::MissingValue)?;
+                operation.insert("value".into(), serde_json::Value::try_from(value)?);
+            }
+            patch.push(serde_json::Value::Object(operation));
+        }
+        Ok(serde_json::Value::Array(patch))
+    }
+
+    /// Returns `true` if this diff replaces the whole document
+    /// (i.e. it is a `REPLACE` at the `$` path, as MySQL logs `JSON_SET(doc, '$', ...)`).
+    ///
+    /// There is no dedicated operation for this, so [`JsonDiff::value`] of such a diff
+    /// is the new document.
+    pub fn is_document_replacement(&self) -> bool {
+        self.operation() == JsonDiffOperation::REPLACE
+            && matches!(jsonb::parse_json_path(&self.path.as_str()), Some(legs) if legs.is_empty())
+    }
+
     /// Returns the raw JsonDiff path.
     pub fn path(&'a self) -> &'a [u8] {
         self.path.as_bytes()
@@ -95,6 +244,271 @@ impl<'a> JsonDiff<'a> {
     }
 }
 
+/// Returns the number of `INSERT`, `REPLACE` and `REMOVE` operations (in this order)
+/// among the given diffs.
+pub fn count_json_ops(diffs: &[JsonDiff<'_>]) -> (usize, usize, usize) {
+    diffs
+        .iter()
+        .fold((0, 0, 0), |(insert, replace, remove), diff| {
+            match diff.operation() {
+                JsonDiffOperation::INSERT => (insert + 1, replace, remove),
+                JsonDiffOperation::REPLACE => (insert, replace + 1, remove),
+                JsonDiffOperation::REMOVE => (insert, replace, remove + 1),
+            }
+        })
+}
+
+/// Applies diffs to the base document in order (see [`JsonDiff::apply`]) and returns
+/// the updated document.
+///
+/// Unlike [`JsonDiff::apply`], a `REMOVE` of a nonexistent path is a no-op (MySQL doesn't
+/// treat it as an error). An `INSERT` at an array index past the end appends to the array.
+///
+/// Diffs are applied to the JSONB tree itself, so values that have no JSON counterpart
+/// (i.e. opaque values) are kept as is. Arrays and objects along the path of a diff
+/// are rebuilt, while other values are copied without decoding.
+pub fn apply_diffs(
+    base: jsonb::Value<'_>,
+    diffs: &[JsonDiff<'_>],
+) -> Result<jsonb::Value<'static>, JsonDiffApplyError> {
+    let mut doc = base.into_owned();
+    for diff in diffs {
+        let path = diff.path.as_str();
+        let legs = jsonb::parse_json_path(&path)
+            .ok_or_else(|| JsonDiffApplyError::InvalidPath(path.to_string()))?;
+        let value = match diff.operation() {
+            JsonDiffOperation::REMOVE => None,
+            _ => Some(
+                diff.value
+                    .as_ref()
+                    .ok_or(JsonDiffApplyError::MissingValue)?,
+            ),
+        };
+
+        if legs.is_empty() && diff.operation() != JsonDiffOperation::REPLACE {
+            return Err(JsonDiffApplyError::PathNotFound(path.to_string()));
+        }
+
+        match apply_jsonb(&doc, &legs, diff.operation(), value) {
+            Ok(Some(updated)) => doc = updated,
+            Ok(None) if diff.operation() == JsonDiffOperation::REMOVE => {}
+            Ok(None) => return Err(JsonDiffApplyError::PathNotFound(path.to_string())),
+            Err(err) => return Err(JsonbToJsonError::InvalidJsonb(err).into()),
+        }
+    }
+    Ok(doc)
+}
+
+/// Elements of a JSONB array or object.
+enum JsonbEntries {
+    Array(Vec<jsonb::Value<'static>>),
+    Object(Vec<(Vec<u8>, jsonb::Value<'static>)>),
+}
+
+impl JsonbEntries {
+    /// Returns elements of the given value or `None` if it isn't an array or object.
+    fn new(value: &jsonb::Value<'_>) -> io::Result<Option<Self>> {
+        fn values<'a>(
+            iter: impl Iterator<Item = io::Result<jsonb::Value<'a>>>,
+        ) -> io::Result<Option<JsonbEntries>> {
+            iter.map(|x| x.map(jsonb::Value::into_owned))
+                .collect::<io::Result<_>>()
+                .map(|x| Some(JsonbEntries::Array(x)))
+        }
+
+        fn entries<'a>(
+            iter: impl Iterator<Item = io::Result<(jsonb::ObjectKey<'a>, jsonb::Value<'a>)>>,
+        ) -> io::Result<Option<JsonbEntries>> {
+            iter.map(|x| x.map(|(k, v)| (k.value_raw().to_vec(), v.into_owned())))
+                .collect::<io::Result<_>>()
+                .map(|x| Some(JsonbEntries::Object(x)))
+        }
+
+        match value {
+            jsonb::Value::SmallArray(x) => values(x.iter()),
+            jsonb::Value::LargeArray(x) => values(x.iter()),
+            jsonb::Value::SmallObject(x) => entries(x.iter()),
+            jsonb::Value::LargeObject(x) => entries(x.iter()),
+            _ => Ok(None),
+        }
+    }
+
+    /// Returns an empty container suitable for the given path leg.
+    fn for_leg(leg: &JsonPathLeg) -> Self {
+        match leg {
+            JsonPathLeg::Key(_) => JsonbEntries::Object(Vec::new()),
+            JsonPathLeg::Index(_) => JsonbEntries::Array(Vec::new()),
+        }
+    }
+
+    /// Builds a JSONB array or object using the smallest suitable storage format.
+    fn build(self) -> io::Result<jsonb::Value<'static>> {
+        let value = match self {
+            JsonbEntries::Array(values) => jsonb::ComplexValue::from_entries(&[], &values)
+                .map(jsonb::Value::SmallArray)
+                .or_else(|| {
+                    jsonb::ComplexValue::from_entries(&[], &values).map(jsonb::Value::LargeArray)
+                }),
+            JsonbEntries::Object(entries) => {
+                let keys = entries.iter().map(|(k, _)| &k[..]).collect::<Vec<_>>();
+                let values = entries.iter().map(|(_, v)| v.clone()).collect::<Vec<_>>();
+                jsonb::ComplexValue::from_entries(&keys, &values)
+                    .map(jsonb::Value::SmallObject)
+                    .or_else(|| {
+                        jsonb::ComplexValue::from_entries(&keys, &values)
+                            .map(jsonb::Value::LargeObject)
+                    })
+            }
+        };
+        value.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "JSONB value is too large"))
+    }
+}
+
+/// Returns the position of the given key among keys sorted the way MySql sorts them
+/// (by length and then bytewise).
+fn find_key(entries: &[(Vec<u8>, jsonb::Value<'static>)], key: &str) -> Result<usize, usize> {
+    entries.binary_search_by(|(k, _)| (k.len(), &k[..]).cmp(&(key.len(), key.as_bytes())))
+}
+
+/// Applies an operation at the given path of a JSONB document with the semantics
+/// of [`JsonDiff::apply`] and returns the updated document.
+///
+/// `value` is `None` for `REMOVE`. Returns `None` if the path doesn't exist.
+fn apply_jsonb(
+    doc: &jsonb::Value<'_>,
+    legs: &[JsonPathLeg],
+    operation: JsonDiffOperation,
+    value: Option<&jsonb::Value<'_>>,
+) -> io::Result<Option<jsonb::Value<'static>>> {
+    let (leg, rest) = match legs.split_first() {
+        Some(x) => x,
+        None => return Ok(value.map(|x| x.clone().into_owned())),
+    };
+
+    let mut entries = match JsonbEntries::new(doc)? {
+        Some(entries) => entries,
+        None => return Ok(None),
+    };
+
+    // the last leg of INSERT or REMOVE modifies the container itself
+    if rest.is_empty() && operation != JsonDiffOperation::REPLACE {
+        match (&mut entries, leg, value) {
+            (JsonbEntries::Object(entries), JsonPathLeg::Key(key), Some(value)) => {
+                let value = value.clone().into_owned();
+                match find_key(entries, key) {
+                    Ok(i) => entries[i].1 = value,
+                    Err(i) => entries.insert(i, (key.as_bytes().to_vec(), value)),
+                }
+            }
+            (JsonbEntries::Array(values), JsonPathLeg::Index(i), Some(value)) => {
+                let index = match i.resolve(values.len()) {
+                    Some(index) => std::cmp::min(index, values.len()),
+                    None => return Ok(None),
+                };
+                values.insert(index, value.clone().into_owned());
+            }
+            (JsonbEntries::Object(entries), JsonPathLeg::Key(key), None) => {
+                match find_key(entries, key) {
+                    Ok(i) => entries.remove(i),
+                    Err(_) => return Ok(None),
+                };
+            }
+            (JsonbEntries::Array(values), JsonPathLeg::Index(i), None) => {
+                match i.resolve(values.len()).filter(|x| *x < values.len()) {
+                    Some(index) => values.remove(index),
+                    None => return Ok(None),
+                };
+            }
+            _ => return Ok(None),
+        }
+        return entries.build().map(Some);
+    }
+
+    // missing intermediate containers are created for INSERT
+    let create = || match (operation, rest.first()) {
+        (JsonDiffOperation::INSERT, Some(next)) => JsonbEntries::for_leg(next).build().map(Some),
+        _ => Ok(None),
+    };
+
+    let child = match (&mut entries, leg) {
+        (JsonbEntries::Object(entries), JsonPathLeg::Key(key)) => match find_key(entries, key) {
+            Ok(i) => &mut entries[i].1,
+            Err(i) => match create()? {
+                Some(child) => {
+                    entries.insert(i, (key.as_bytes().to_vec(), child));
+                    &mut entries[i].1
+                }
+                None => return Ok(None),
+            },
+        },
+        (JsonbEntries::Array(values), JsonPathLeg::Index(i)) => match i.resolve(values.len()) {
+            Some(index) if index < values.len() => &mut values[index],
+            Some(_) => match create()? {
+                Some(child) => {
+                    values.push(child);
+                    values.last_mut().expect("just pushed")
+                }
+                None => return Ok(None),
+            },
+            None => return Ok(None),
+        },
+        _ => return Ok(None),
+    };
+
+    match apply_jsonb(child, rest, operation, value)? {
+        Some(updated) => *child = updated,
+        None => return Ok(None),
+    }
+    entries.build().map(Some)
+}
+
+/// Returns the value at the given path (if any).
+fn resolve_json_path<'a>(
+    mut doc: &'a mut serde_json::Value,
+    legs: &[JsonPathLeg],
+) -> Option<&'a mut serde_json::Value> {
+    for leg in legs {
+        doc = match (doc, leg) {
+            (serde_json::Value::Object(map), JsonPathLeg::Key(key)) => map.get_mut(key)?,
+            (serde_json::Value::Array(arr), JsonPathLeg::Index(i)) => {
+                let index = i.resolve(arr.len())?;
+                arr.get_mut(index)?
+            }
+            _ => return None,
+        };
+    }
+    Some(doc)
+}
+
+/// Returns the parent of the value at the given path creating missing containers
+/// along the way (the type of a created container is defined by the following leg).
+fn resolve_or_create_json_path<'a>(
+    mut doc: &'a mut serde_json::Value,
+    legs: &[JsonPathLeg],
+) -> Option<&'a mut serde_json::Value> {
+    for (leg, next) in legs.iter().zip(legs.iter().skip(1)) {
+        let container = || match next {
+            JsonPathLeg::Key(_) => serde_json::Value::Object(Default::default()),
+            JsonPathLeg::Index(_) => serde_json::Value::Array(Default::default()),
+        };
+        doc = match (doc, leg) {
+            (serde_json::Value::Object(map), JsonPathLeg::Key(key)) => {
+                map.entry(key.clone()).or_insert_with(container)
+            }
+            (serde_json::Value::Array(arr), JsonPathLeg::Index(i)) => {
+                let index = i.resolve(arr.len())?;
+                if index >= arr.len() {
+                    arr.push(container());
+                }
+                let len = arr.len();
+                &mut arr[std::cmp::min(index, len - 1)]
+            }
+            _ => return None,
+        };
+    }
+    Some(doc)
+}
+
 impl<'de> MyDeserialize<'de> for JsonDiff<'de> {
     const SIZE: Option<usize> = None;
     type Ctx = ();
@@ -117,3 +531,290 @@ impl<'de> MyDeserialize<'de> for JsonDiff<'de> {
         })
     }
 }
+
+impl MySerialize for JsonDiff<'_> {
+    fn serialize(&self, buf: &mut Vec<u8>) {
+        self.operation.serialize(&mut *buf);
+        self.path.serialize(&mut *buf);
+        if let Some(ref value) = self.value {
+            let mut value_buf = Vec::new();
+            value.serialize(&mut value_buf);
+            RawInt::<LenEnc>::new(value_buf.len() as u64).serialize(&mut *buf);
+            buf.extend_from_slice(&value_buf);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn apply(
+        doc: serde_json::Value,
+        path: &str,
+        operation: JsonDiffOperation,
+        value: Option<serde_json::Value>,
+    ) -> Result<serde_json::Value, JsonDiffApplyError> {
+        let mut doc = doc;
+        let diff = JsonDiff::new(path.as_bytes(), operation, value.map(Into::into));
+        diff.apply(&mut doc)?;
+        Ok(doc)
+    }
+
+    #[test]
+    fn should_convert_to_json_patch() -> Result<(), JsonDiffApplyError> {
+        use JsonDiffOperation::*;
+
+        let diffs = [
+            JsonDiff::new(&b"$.a.b"[..], REPLACE, Some(json!([1, "x"]).into())),
+            JsonDiff::new(&b"$.c[2]"[..], REMOVE, None),
+            JsonDiff::new(&b"$.\"d/e~\""[..], INSERT, Some(json!(null).into())),
+        ];
+        assert_eq!(
+            JsonDiff::to_json_patch(&diffs)?,
+            json!([
+                { "op": "replace", "path": "/a/b", "value": [1, "x"] },
+                { "op": "remove", "path": "/c/2" },
+                { "op": "add", "path": "/d~1e~0", "value": null },
+            ])
+        );
+
+        let diffs = [JsonDiff::new(&b"$"[..], REPLACE, Some(json!({}).into()))];
+        assert_eq!(
+            JsonDiff::to_json_patch(&diffs)?,
+            json!([{ "op": "replace", "path": "", "value": {} }])
+        );
+
+        let diffs = [JsonDiff::new(&b"$[last]"[..], REMOVE, None)];
+        assert!(matches!(
+            JsonDiff::to_json_patch(&diffs),
+            Err(JsonDiffApplyError::InvalidPath(_))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_count_json_ops() {
+        use JsonDiffOperation::*;
+
+        let diffs = [
+            JsonDiff::new(&b"$.a"[..], REPLACE, Some(json!(1).into())),
+            JsonDiff::new(&b"$.b"[..], INSERT, Some(json!(2).into())),
+            JsonDiff::new(&b"$.c"[..], REMOVE, None),
+            JsonDiff::new(&b"$.d"[..], REPLACE, Some(json!(3).into())),
+            JsonDiff::new(&b"$.e"[..], REMOVE, None),
+            JsonDiff::new(&b"$.f"[..], REMOVE, None),
+        ];
+        assert_eq!(count_json_ops(&diffs), (1, 2, 3));
+        assert_eq!(count_json_ops(&[]), (0, 0, 0));
+    }
+
+    #[test]
+    fn should_apply_remove_leaving_empty_container() -> Result<(), JsonDiffApplyError> {
+        use JsonDiffOperation::*;
+
+        let doc = apply(json!({"a": [1]}), "$.a[0]", REMOVE, None)?;
+        assert_eq!(doc, json!({"a": []}));
+
+        let doc = apply(json!([{"a": 1}]), "$[0].a", REMOVE, None)?;
+        assert_eq!(doc, json!([{}]));
+
+        let doc = apply(json!({"a": [1, 2]}), "$.a[last]", REMOVE, None)?;
+        assert_eq!(doc, json!({"a": [1]}));
+
+        assert!(matches!(
+            apply(json!({"a": []}), "$.a[0]", REMOVE, None),
+            Err(JsonDiffApplyError::PathNotFound(_))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_apply_replace_and_insert() -> Result<(), JsonDiffApplyError> {
+        use JsonDiffOperation::*;
+
+        let doc = apply(json!({"a": 1}), "$.a", REPLACE, Some(json!("x")))?;
+        assert_eq!(doc, json!({"a": "x"}));
+
+        let doc = apply(json!({"a": 1}), "$", REPLACE, Some(json!([])))?;
+        assert_eq!(doc, json!([]));
+
+        let doc = apply(json!({"a": [1, 3]}), "$.a[1]", INSERT, Some(json!(2)))?;
+        assert_eq!(doc, json!({"a": [1, 2, 3]}));
+
+        let doc = apply(json!({"a": [1]}), "$.a[10]", INSERT, Some(json!(2)))?;
+        assert_eq!(doc, json!({"a": [1, 2]}));
+
+        let doc = apply(json!({}), r#"$."b c""#, INSERT, Some(json!(null)))?;
+        assert_eq!(doc, json!({"b c": null}));
+
+        assert!(matches!(
+            apply(json!({}), "$.a", REPLACE, Some(json!(1))),
+            Err(JsonDiffApplyError::PathNotFound(_))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_apply_diffs() -> Result<(), JsonDiffApplyError> {
+        use JsonDiffOperation::*;
+
+        let base = jsonb::Value::from(json!({"a": {"b": [1, 2]}, "c": [{"d": 1}]}));
+        let diffs = [
+            JsonDiff::new(&b"$.a.b[1]"[..], REPLACE, Some(json!("x").into())),
+            JsonDiff::new(&b"$.a.b[0]"[..], INSERT, Some(json!(0).into())),
+            // past the end of the array, so it is appended
+            JsonDiff::new(&b"$.a.b[10]"[..], INSERT, Some(json!(3).into())),
+            JsonDiff::new(&b"$.c[0].e"[..], INSERT, Some(json!([]).into())),
+            JsonDiff::new(&b"$.c[0].d"[..], REMOVE, None),
+            // nonexistent key and index, so these are no-op
+            JsonDiff::new(&b"$.a.x"[..], REMOVE, None),
+            JsonDiff::new(&b"$.c[5]"[..], REMOVE, None),
+        ];
+        let doc = apply_diffs(base.clone(), &diffs)?;
+        assert_eq!(
+            serde_json::Value::try_from(doc)?,
+            json!({"a": {"b": [0, 1, "x", 3]}, "c": [{"e": []}]})
+        );
+
+        assert_eq!(apply_diffs(base.clone(), &[])?, base);
+
+        let diffs = [JsonDiff::new(&b"$.x"[..], REPLACE, Some(json!(1).into()))];
+        assert!(matches!(
+            apply_diffs(base.clone(), &diffs),
+            Err(JsonDiffApplyError::PathNotFound(_))
+        ));
+
+        // missing containers are created for INSERT
+        let diffs = [JsonDiff::new(
+            &b"$.x[0].y"[..],
+            INSERT,
+            Some(json!(1).into()),
+        )];
+        let doc = apply_diffs(base.clone(), &diffs)?;
+        assert_eq!(
+            serde_json::Value::try_from(doc)?,
+            json!({"a": {"b": [1, 2]}, "c": [{"d": 1}], "x": [{"y": 1}]})
+        );
+
+        // the whole document is replaced
+        let diffs = [JsonDiff::new(&b"$"[..], REPLACE, Some(json!([1]).into()))];
+        assert_eq!(apply_diffs(base, &diffs)?, jsonb::Value::from(json!([1])));
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_keep_opaque_values_when_applying_diffs() -> Result<(), JsonDiffApplyError> {
+        use crate::constants::ColumnType;
+        use JsonDiffOperation::*;
+
+        let date = jsonb::Value::Opaque(jsonb::OpaqueValue::new(
+            ColumnType::MYSQL_TYPE_DATE,
+            &[0, 0, 0, 0, 0x80, 0xcc, 0x99, 0x19][..],
+        ));
+        let base =
+            jsonb::ComplexValue::from_entries(&[b"a", b"b"], &[date.clone(), json!(1).into()])
+                .map(jsonb::Value::SmallObject)
+                .unwrap();
+        let diffs = [JsonDiff::new(&b"$.b"[..], REPLACE, Some(date.clone()))];
+
+        let doc = apply_diffs(base, &diffs)?;
+        assert_eq!(
+            doc.get_path(&jsonb::parse_json_path("$.a").unwrap())
+                .unwrap(),
+            Some(date.clone())
+        );
+        assert_eq!(
+            doc.get_path(&jsonb::parse_json_path("$.b").unwrap())
+                .unwrap(),
+            Some(date)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_decode_document_replacement() -> io::Result<()> {
+        use crate::{
+            binlog::value::{BinlogValue, BinlogValueCtx},
+            constants::ColumnType,
+        };
+
+        // partial JSON value logged for `JSON_SET(doc, '$', JSON_OBJECT('a', 1))`
+        let data = [
+            17, 0, 0, 0, // length
+            0, // REPLACE
+            1, b'$', // path
+            13,   // value length
+            0x00, 1, 0, 12, 0, // small object of a single member, 12 bytes long
+            11, 0, 1, 0, // key entry
+            0x05, 1, 0,    // value entry (inlined INT16)
+            b'a', // key
+        ];
+        let ctx = BinlogValueCtx::new(ColumnType::MYSQL_TYPE_JSON, &[4][..]).with_partial(true);
+        let mut buf = ParseBuf(&data);
+        let value: BinlogValue = buf.parse(ctx)?;
+        assert!(buf.is_empty());
+
+        let diffs = match value {
+            BinlogValue::JsonDiff(diffs) => diffs,
+            other => panic!("unexpected value {:?}", other),
+        };
+        assert_eq!(diffs.len(), 1);
+        assert!(diffs[0].is_document_replacement());
+
+        let mut doc = json!([1, 2]);
+        diffs[0].apply(&mut doc).unwrap();
+        assert_eq!(doc, json!({"a": 1}));
+
+        let diff = JsonDiff::new(
+            &b"$.a"[..],
+            JsonDiffOperation::REPLACE,
+            Some(json!(1).into()),
+        );
+        assert!(!diff.is_document_replacement());
+        let diff = JsonDiff::new(&b"$"[..], JsonDiffOperation::INSERT, Some(json!(1).into()));
+        assert!(!diff.is_document_replacement());
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_apply_multi_leg_path() -> Result<(), JsonDiffApplyError> {
+        use JsonDiffOperation::*;
+
+        let doc = json!({"a": [{"c": 2}]});
+        let doc = apply(doc, "$.a[0].b", INSERT, Some(json!(1)))?;
+        assert_eq!(doc, json!({"a": [{"c": 2, "b": 1}]}));
+
+        let doc = apply(doc, "$.a[0].b", REPLACE, Some(json!("x")))?;
+        assert_eq!(doc, json!({"a": [{"c": 2, "b": "x"}]}));
+
+        let doc = apply(doc, "$.a[0].c", REMOVE, None)?;
+        assert_eq!(doc, json!({"a": [{"b": "x"}]}));
+
+        // missing containers are only created for INSERT
+        let doc = apply(json!({}), "$.a[0].b", INSERT, Some(json!(1)))?;
+        assert_eq!(doc, json!({"a": [{"b": 1}]}));
+        for (operation, value) in [(REPLACE, Some(json!(1))), (REMOVE, None)] {
+            assert!(matches!(
+                apply(json!({}), "$.a[0].b", operation, value),
+                Err(JsonDiffApplyError::PathNotFound(_))
+            ));
+        }
+
+        // existing values aren't replaced with containers
+        assert!(matches!(
+            apply(json!({"a": 1}), "$.a[0].b", INSERT, Some(json!(1))),
+            Err(JsonDiffApplyError::PathNotFound(_))
+        ));
+
+        Ok(())
+    }
+}
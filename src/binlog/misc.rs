@@ -6,7 +6,7 @@
 // option. All files in the project carrying such notice may not be copied,
 // modified, or distributed except according to those terms.
 
-use byteorder::{BigEndian as BE, ReadBytesExt};
+use byteorder::{BigEndian as BE, ReadBytesExt, WriteBytesExt};
 use saturating::Saturating as S;
 
 use std::{
@@ -32,7 +32,7 @@ pub fn my_time_packed_from_binary<T: io::Read>(mut input: T, dec: u32) -> io::Re
     match dec {
         1 | 2 => {
             let mut intpart = input.read_u24::<BE>()? as i64 - TIMEF_INT_OFS;
-            let mut frac = input.read_u8()? as u32;
+            let mut frac = input.read_u8()? as i32;
             if intpart < 0 && frac > 0 {
                 intpart += 1;
                 frac -= 0x100;
@@ -64,7 +64,8 @@ pub fn my_packed_time_get_frac_part(i: i64) -> i64 {
     i % (1 << 24)
 }
 
-pub fn time_from_packed(mut tmp: i64) -> Value {
+/// Returns `(is_negative, hours, minutes, seconds, micro seconds)` of the packed time value.
+pub fn time_parts_from_packed(mut tmp: i64) -> (bool, u32, u8, u8, u32) {
     let neg = if tmp < 0 {
         tmp = -tmp;
         true
@@ -76,7 +77,12 @@ pub fn time_from_packed(mut tmp: i64) -> Value {
     let m = ((hms >> 6) as u32) % (1 << 6);
     let s = ((hms) as u32) % (1 << 6);
     let u = my_packed_time_get_frac_part(tmp);
-    Value::Time(neg, 0, h as u8, m as u8, s as u8, u as u32)
+    (neg, h, m as u8, s as u8, u as u32)
+}
+
+pub fn time_from_packed(tmp: i64) -> Value {
+    let (neg, h, m, s, u) = time_parts_from_packed(tmp);
+    Value::Time(neg, 0, h as u8, m, s, u)
 }
 
 pub fn my_datetime_packed_from_binary<T: io::Read>(mut input: T, dec: u32) -> io::Result<i64> {
@@ -131,6 +137,140 @@ pub fn my_timestamp_from_binary<T: io::Read>(mut input: T, dec: u8) -> io::Resul
     Ok((sec, usec))
 }
 
+/// Packs the given time parts (see [`time_parts_from_packed`]).
+pub fn time_packed_from_parts(neg: bool, h: u32, m: u8, s: u8, us: u32) -> i64 {
+    let hms = ((h as i64) << 12) | ((m as i64) << 6) | s as i64;
+    let packed = my_packed_time_make(hms, us as i64);
+    if neg {
+        -packed
+    } else {
+        packed
+    }
+}
+
+/// Writes the packed time value (see [`my_time_packed_from_binary`]).
+pub fn my_time_packed_to_binary<T: Write>(mut output: T, packed: i64, dec: u32) -> io::Result<()> {
+    let intpart = my_packed_time_get_int_part(packed);
+    let frac = my_packed_time_get_frac_part(packed);
+    match dec {
+        1 | 2 => {
+            output.write_u24::<BE>((intpart + TIMEF_INT_OFS) as u32)?;
+            output.write_i8((frac / 10_000) as i8)
+        }
+        3 | 4 => {
+            output.write_u24::<BE>((intpart + TIMEF_INT_OFS) as u32)?;
+            output.write_i16::<BE>((frac / 100) as i16)
+        }
+        5 | 6 => output.write_u48::<BE>((packed + TIMEF_OFS) as u64),
+        _ => output.write_u24::<BE>((intpart + TIMEF_INT_OFS) as u32),
+    }
+}
+
+/// Packs the given date and time parts (see [`datetime_from_packed`]).
+pub fn datetime_packed_from_parts(y: u16, m: u8, d: u8, h: u8, i: u8, s: u8, us: u32) -> i64 {
+    let ymd = (((y as i64) * 13 + m as i64) << 5) | d as i64;
+    let hms = ((h as i64) << 12) | ((i as i64) << 6) | s as i64;
+    my_packed_time_make((ymd << 17) | hms, us as i64)
+}
+
+/// Writes the packed datetime value (see [`my_datetime_packed_from_binary`]).
+pub fn my_datetime_packed_to_binary<T: Write>(
+    mut output: T,
+    packed: i64,
+    dec: u32,
+) -> io::Result<()> {
+    let intpart = my_packed_time_get_int_part(packed);
+    let frac = my_packed_time_get_frac_part(packed);
+    output.write_uint::<BE>((intpart + DATETIMEF_INT_OFS) as u64, 5)?;
+    match dec {
+        1 | 2 => output.write_i8((frac / 10_000) as i8),
+        3 | 4 => output.write_i16::<BE>((frac / 100) as i16),
+        5 | 6 => output.write_i24::<BE>(frac as i32),
+        _ => Ok(()),
+    }
+}
+
+/// Writes the timestamp value (see [`my_timestamp_from_binary`]).
+pub fn my_timestamp_to_binary<T: Write>(
+    mut output: T,
+    sec: u32,
+    usec: u32,
+    dec: u8,
+) -> io::Result<()> {
+    output.write_u32::<BE>(sec)?;
+    match dec {
+        1 | 2 => output.write_u8((usec / 10_000) as u8),
+        3 | 4 => output.write_u16::<BE>((usec / 100) as u16),
+        5 | 6 => output.write_u24::<BE>(usec),
+        _ => Ok(()),
+    }
+}
+
+/// Parses members of an ENUM or SET column definition.
+///
+/// Accepts either a full definition (i.e. `enum('a','b')` as reported by
+/// `information_schema.COLUMNS.COLUMN_TYPE`) or just the list of quoted members (`'a','b'`).
+/// Doubled quotes (`''`) and backslash escapes within a member are unescaped,
+/// empty members are preserved.
+///
+/// Anything after the closing paren of a full definition is ignored, so that
+/// `enum('a','b') CHARACTER SET utf8mb4 COLLATE utf8mb4_bin` gives `["a", "b"]`.
+pub fn parse_enum_def(def: &str) -> io::Result<Vec<String>> {
+    let malformed = || io::Error::new(io::ErrorKind::InvalidData, "Malformed ENUM/SET definition");
+
+    let mut def = def.trim();
+    let mut terminator = None;
+    if let Some(open) = def.find('(') {
+        let name = &def[..open];
+        if name.eq_ignore_ascii_case("enum") || name.eq_ignore_ascii_case("set") {
+            def = &def[(open + 1)..];
+            terminator = Some(')');
+        }
+    }
+
+    let mut members = Vec::new();
+    let mut chars = def.trim_start().chars().peekable();
+
+    if chars.peek().copied() == terminator {
+        return Ok(members);
+    }
+
+    loop {
+        if chars.next() != Some('\'') {
+            return Err(malformed());
+        }
+
+        let mut member = String::new();
+        loop {
+            match chars.next().ok_or_else(malformed)? {
+                '\'' if chars.peek() == Some(&'\'') => {
+                    chars.next();
+                    member.push('\'');
+                }
+                '\'' => break,
+                '\\' => member.push(chars.next().ok_or_else(malformed)?),
+                c => member.push(c),
+            }
+        }
+        members.push(member);
+
+        while chars.peek().map(|c| c.is_whitespace()).unwrap_or(false) {
+            chars.next();
+        }
+        match chars.next() {
+            Some(',') => {
+                while chars.peek().map(|c| c.is_whitespace()).unwrap_or(false) {
+                    chars.next();
+                }
+            }
+            c if c == terminator => break,
+            Some(_) | None => return Err(malformed()),
+        }
+    }
+
+    Ok(members)
+}
+
 pub(crate) struct LimitedWrite<T> {
     limit: S<usize>,
     write: T,
@@ -162,3 +302,102 @@ pub(crate) trait LimitWrite: Write + Sized {
 }
 
 impl<T: Write> LimitWrite for T {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn my_timestamp_from_binary() -> io::Result<()> {
+        // the Unix epoch
+        for dec in 0..=6 {
+            let data = [0_u8; 7];
+            let len = 4 + (dec as usize + 1) / 2;
+            let mut input = &data[..len];
+            assert_eq!(super::my_timestamp_from_binary(&mut input, dec)?, (0, 0));
+            assert!(input.is_empty());
+        }
+
+        // maximum fractional part of each width
+        let cases: [(u8, &[u8], i32); 3] = [
+            (2, &[0, 0, 0, 1, 99], 990_000),
+            (4, &[0, 0, 0, 1, 0x27, 0x0F], 999_900),
+            (6, &[0, 0, 0, 1, 0x0F, 0x42, 0x3F], 999_999),
+        ];
+        for (dec, data, usec) in cases {
+            assert_eq!(super::my_timestamp_from_binary(data, dec)?, (1, usec));
+        }
+
+        // the maximum TIMESTAMP value 2038-01-19 03:14:07
+        let data = 0x7FFF_FFFF_u32.to_be_bytes();
+        assert_eq!(
+            super::my_timestamp_from_binary(&data[..], 0)?,
+            (i32::MAX, 0)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn my_datetime_packed_from_binary_without_fraction() -> io::Result<()> {
+        let packed = datetime_packed_from_parts(2021, 12, 31, 23, 59, 59, 0);
+        let mut data = Vec::new();
+        my_datetime_packed_to_binary(&mut data, packed, 0)?;
+        assert_eq!(data.len(), 5);
+
+        // DATETIME(0) has no fractional part, so the next value must stay untouched
+        data.push(0xFF);
+        let mut input = &data[..];
+        let parsed = super::my_datetime_packed_from_binary(&mut input, 0)?;
+        assert_eq!(input, &[0xFF]);
+        assert_eq!(my_packed_time_get_frac_part(parsed), 0);
+        assert_eq!(
+            datetime_from_packed(parsed),
+            Value::Date(2021, 12, 31, 23, 59, 59, 0)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn parse_enum_def() -> io::Result<()> {
+        assert_eq!(super::parse_enum_def("'','x','é'")?, vec!["", "x", "é"]);
+        assert_eq!(
+            super::parse_enum_def("enum('','x','é')")?,
+            vec!["", "x", "é"]
+        );
+        assert_eq!(
+            super::parse_enum_def("SET('it''s', 'a\\\\b', '日本語')")?,
+            vec!["it's", "a\\b", "日本語"]
+        );
+        assert_eq!(super::parse_enum_def("enum()")?, Vec::<String>::new());
+        assert!(super::parse_enum_def("enum('a")
+            .unwrap_err()
+            .to_string()
+            .contains("Malformed"));
+        assert!(super::parse_enum_def("'a' 'b'").is_err());
+        assert_eq!(
+            super::parse_enum_def("enum('a','b') CHARACTER SET utf8mb4")?,
+            vec!["a", "b"]
+        );
+        assert_eq!(
+            super::parse_enum_def("set('a)',  'b' ) COLLATE utf8mb4_bin")?,
+            vec!["a)", "b"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn parse_enum_def_with_quotes_and_commas() -> io::Result<()> {
+        assert_eq!(
+            super::parse_enum_def("enum('a,b','it''s')")?,
+            vec!["a,b", "it's"]
+        );
+        assert_eq!(
+            super::parse_enum_def("set(''',''', ',', '''')")?,
+            vec!["','", ",", "'"]
+        );
+        assert!(super::parse_enum_def("enum('it's')").is_err());
+        Ok(())
+    }
+}
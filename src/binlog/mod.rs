@@ -35,6 +35,8 @@ use self::{
     events::{Event, FormatDescriptionEvent, TableMapEvent},
 };
 
+#[cfg(feature = "apache-avro")]
+pub mod avro;
 pub mod consts;
 pub mod decimal;
 pub mod events;
@@ -42,6 +44,8 @@ pub mod jsonb;
 pub mod jsondiff;
 pub mod misc;
 pub mod row;
+pub mod schema;
+pub mod transaction;
 pub mod value;
 
 pub struct BinlogCtx<'a> {
@@ -198,7 +202,11 @@ impl ColumnType {
     /// as well as the total number of occupied bytes.
     ///
     /// `is_array` must be true if `self` is from `MYSQL_TYPE_TYPED_ARRAY` metadata.
-    fn get_metadata<'a>(&self, ptr: &'a [u8], is_array: bool) -> Option<(&'a [u8], usize)> {
+    pub(crate) fn get_metadata<'a>(
+        &self,
+        ptr: &'a [u8],
+        is_array: bool,
+    ) -> Option<(&'a [u8], usize)> {
         match self {
             Self::MYSQL_TYPE_TINY_BLOB
             | Self::MYSQL_TYPE_BLOB
@@ -223,10 +231,13 @@ impl ColumnType {
             | Self::MYSQL_TYPE_ENUM
             | Self::MYSQL_TYPE_STRING
             | Self::MYSQL_TYPE_BIT => ptr.get(..2).map(|x| (x, 2)),
-            Self::MYSQL_TYPE_TYPED_ARRAY => Self::try_from(*ptr.get(0)?)
-                .ok()?
-                .get_metadata(&ptr.get(1..)?, true)
-                .map(|(x, n)| (x, n + 1)),
+            Self::MYSQL_TYPE_TYPED_ARRAY => {
+                // element type followed by element metadata
+                let (_, n) = Self::try_from(*ptr.get(0)?)
+                    .ok()?
+                    .get_metadata(ptr.get(1..)?, true)?;
+                ptr.get(..(n + 1)).map(|x| (x, n + 1))
+            }
             _ => Some((&[], 0)),
         }
     }
@@ -17,16 +17,19 @@ use bitvec::{prelude::BitVec, slice::BitSlice};
 use crate::{
     constants::{ColumnFlags, ColumnType},
     io::ParseBuf,
-    misc::raw::int::*,
+    misc::{raw::int::*, unexpected_buf_eof_at},
     packets::Column,
-    proto::MyDeserialize,
+    proto::{MyDeserialize, MySerialize},
     row::{new_row_raw, Row},
     value::Value,
 };
 
 use super::{
     events::{OptionalMetadataField, TableMapEvent},
-    value::{BinlogValue, BinlogValueToValueError},
+    value::{
+        BinlogValue, BinlogValueCtx, BinlogValueError, BinlogValueOptions, BinlogValueToValueError,
+        BINARY_CHARSET,
+    },
 };
 
 /// Bonlog rows event row value options.
@@ -61,6 +64,11 @@ impl TryFrom<u64> for BinlogRowValueOptions {
 }
 
 /// Representation of a binlog row.
+///
+/// If the `TableMapEvent` gives names of ENUM and SET members (i.e. `binlog_row_metadata=FULL`),
+/// then decoding a row fails with an `InvalidData` error if an ENUM index or a SET bit
+/// refers to a non-existent member (see [`TableMapEvent::enum_or_set_member_counts`]).
+/// [`BinlogRow::deserialize_lenient`] replaces such values with [`BinlogValue::Error`].
 #[derive(Clone, PartialEq)]
 pub struct BinlogRow {
     values: Vec<Option<BinlogValue<'static>>>,
@@ -118,6 +126,31 @@ impl BinlogRow {
             .collect()
     }
 
+    /// Returns a MINIMAL image of this row, i.e. a row that contains only
+    /// the given primary key columns.
+    ///
+    /// Columns of the image are in the table order (i.e. in the order of bits of the columns
+    /// present bitmap) regardless of the order of `primary_key`. Duplicates are ignored.
+    ///
+    /// `self` is expected to be a full row image, so that an offset of a column
+    /// within this row is equal to its offset within the table.
+    ///
+    /// Returns `None` if some of the primary key columns is out of range
+    /// or was taken by `take` method.
+    pub fn to_minimal_image(&self, primary_key: &[usize]) -> Option<BinlogRow> {
+        let mut primary_key = primary_key.to_vec();
+        primary_key.sort_unstable();
+        primary_key.dedup();
+
+        let mut values = Vec::with_capacity(primary_key.len());
+        let mut columns = Vec::with_capacity(primary_key.len());
+        for index in primary_key {
+            values.push(Some(self.values.get(index)?.as_ref()?.clone()));
+            columns.push(self.columns.get(index)?.clone());
+        }
+        Some(BinlogRow::new(values, columns.into_boxed_slice().into()))
+    }
+
     #[doc(hidden)]
     pub fn place(&mut self, index: usize, value: BinlogValue<'static>) {
         self.values[index] = Some(value);
@@ -135,13 +168,74 @@ impl<'de> MyDeserialize<'de> for BinlogRow {
     /// * corresponding table map event
     type Ctx = (u64, &'de BitSlice<u8>, bool, &'de TableMapEvent<'de>);
 
-    fn deserialize(
-        (num_columns, cols, have_shared_image, table_info): Self::Ctx,
+    fn deserialize(ctx: Self::Ctx, buf: &mut ParseBuf<'de>) -> io::Result<Self> {
+        let columns_meta = ColumnsMeta::new(ctx.3);
+        BinlogRow::deserialize_row(ctx, &columns_meta, None, false, buf)
+    }
+}
+
+/// Per-column metadata of a `TableMapEvent` needed to decode its rows.
+///
+/// It is computed once per table map (see [`crate::binlog::events::RowsEventRows`])
+/// rather than for every row. Malformed optional metadata doesn't fail the decoding:
+/// columns get the unknown charset and ENUM/SET values aren't bounds checked.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct ColumnsMeta {
+    charsets: Vec<Option<u16>>,
+    member_counts: Vec<Option<u64>>,
+}
+
+impl ColumnsMeta {
+    pub(crate) fn new(table_info: &TableMapEvent<'_>) -> Self {
+        Self {
+            charsets: table_info.column_charsets().unwrap_or_default(),
+            member_counts: table_info.enum_or_set_member_counts().unwrap_or_default(),
+        }
+    }
+}
+
+impl BinlogRow {
+    /// Same as `deserialize` but a malformed value of a fixed-length column
+    /// doesn't abort the whole row.
+    ///
+    /// Such values are replaced by [`BinlogValue::Error`] placeholders and also returned
+    /// along with their column offsets in the table (see [`BinlogValue::deserialize_lenient`]).
+    pub fn deserialize_lenient<'de>(
+        ctx: <Self as MyDeserialize<'de>>::Ctx,
+        buf: &mut ParseBuf<'de>,
+    ) -> io::Result<(Self, Vec<(usize, BinlogValueError)>)> {
+        let mut errors = vec![];
+        let columns_meta = ColumnsMeta::new(ctx.3);
+        let row = BinlogRow::deserialize_row(ctx, &columns_meta, Some(&mut errors), false, buf)?;
+        Ok((row, errors))
+    }
+
+    /// Parses a row. Parsing is lenient if `errors` is given.
+    ///
+    /// An unexpected end of `buf` while reading a value is reported along with the offset
+    /// of the value within the row image and the number of remaining bytes.
+    ///
+    /// Values of invisible columns (see [`TableMapEvent::column_visibility`]) are parsed
+    /// but not included into the row if `skip_invisible` is `true`.
+    pub(crate) fn deserialize_row<'de>(
+        (num_columns, cols, have_shared_image, table_info): <Self as MyDeserialize<'de>>::Ctx,
+        columns_meta: &ColumnsMeta,
+        mut errors: Option<&mut Vec<(usize, BinlogValueError)>>,
+        skip_invisible: bool,
         buf: &mut ParseBuf<'de>,
     ) -> io::Result<Self> {
         let mut values: Vec<Option<BinlogValue<'static>>> = vec![];
         let mut columns = vec![];
 
+        let origin = buf.0;
+        let eof_at = |err: io::Error, rest: &[u8]| {
+            if err.kind() == io::ErrorKind::UnexpectedEof {
+                unexpected_buf_eof_at(origin.len() - rest.len(), rest.len())
+            } else {
+                err
+            }
+        };
+
         // read a shared image if needed (see WL#2955)
         let mut partial_cols = if have_shared_image {
             let value_options = *buf.parse::<RawInt<LenEnc>>(())?;
@@ -174,6 +268,12 @@ impl<'de> MyDeserialize<'de> for BinlogRow {
             .unwrap_or(None)
         });
 
+        let ColumnsMeta {
+            charsets,
+            member_counts,
+        } = columns_meta;
+        let visibility = table_info.column_visibility();
+
         let mut numeric_index = 0;
         for i in 0..(num_columns as usize) {
             // check if column is in columns list
@@ -211,29 +311,84 @@ impl<'de> MyDeserialize<'de> for BinlogRow {
                 if is_unsigned {
                     column_flags |= ColumnFlags::UNSIGNED_FLAG;
                 }
+                let charset = charsets.get(i).copied().flatten().unwrap_or_default();
+                if charset == BINARY_CHARSET {
+                    column_flags |= ColumnFlags::BINARY_FLAG;
+                }
                 let column = Column::new(column_type)
                     // column name – `@<i>` where i is a column offset in a table
                     .with_name(format!("@{}", i).as_bytes())
                     .with_flags(column_flags)
+                    .with_character_set(charset)
                     .with_schema(table_info.database_name_raw())
                     .with_org_table(table_info.table_name_raw())
                     .with_table(table_info.table_name_raw());
-                columns.push(column);
 
                 // check if column is null
-                if null_bitmap
+                let value = if null_bitmap
                     .get(image_idx)
                     .as_deref()
                     .copied()
                     .unwrap_or(true)
                 {
-                    values.push(Some(BinlogValue::Value(Value::NULL)));
+                    BinlogValue::Value(Value::NULL)
                 } else {
-                    let ctx = (column_type, column_meta, is_unsigned, is_partial);
-                    values.push(Some(buf.parse::<BinlogValue>(ctx)?.into_owned()));
+                    let ctx = BinlogValueCtx::new(column_type, column_meta)
+                        .with_unsigned(is_unsigned)
+                        .with_partial(is_partial);
+                    let rest = buf.0;
+                    let value = match errors {
+                        Some(ref mut errors) => {
+                            let value = BinlogValue::deserialize_lenient(
+                                ctx,
+                                BinlogValueOptions::default(),
+                                buf,
+                            )
+                            .map_err(|err| eof_at(err, rest))?;
+                            let value = match member_counts.get(i).copied().flatten() {
+                                Some(count) => {
+                                    match check_member_index(column_type, &value, count) {
+                                        Ok(()) => value,
+                                        Err(err) => BinlogValue::Error(BinlogValueError::new(
+                                            column_type,
+                                            err,
+                                        )),
+                                    }
+                                }
+                                None => value,
+                            };
+                            if let BinlogValue::Error(ref err) = value {
+                                errors.push((i, err.clone()));
+                            }
+                            value
+                        }
+                        None => {
+                            let value = buf
+                                .parse::<BinlogValue>(ctx)
+                                .map_err(|err| eof_at(err, rest))?;
+                            if let Some(count) = member_counts.get(i).copied().flatten() {
+                                check_member_index(column_type, &value, count)?;
+                            }
+                            value
+                        }
+                    };
+                    value.into_owned()
+                };
+
+                let is_visible = visibility
+                    .and_then(|bits| bits.get(i).as_deref().copied())
+                    .unwrap_or(true);
+                if is_visible || !skip_invisible {
+                    columns.push(column);
+                    values.push(Some(value));
                 }
 
                 image_idx += 1;
+            } else if matches!(table_info.get_column_type(i), Ok(Some(ty)) if ty.is_numeric_type())
+            {
+                // signedness is given for every numeric column of a table,
+                // not only for columns of this row image
+                numeric_index += 1;
             }
         }
 
@@ -241,6 +396,39 @@ impl<'de> MyDeserialize<'de> for BinlogRow {
     }
 }
 
+/// Checks that a decoded ENUM index or SET bitmask refers to one of `member_count` members
+/// given by the optional metadata (see [`TableMapEvent::enum_or_set_member_counts`]).
+fn check_member_index(
+    column_type: ColumnType,
+    value: &BinlogValue<'_>,
+    member_count: u64,
+) -> io::Result<()> {
+    let is_valid = match (column_type, value) {
+        // `0` stands for the empty string stored for invalid values
+        (ColumnType::MYSQL_TYPE_ENUM, BinlogValue::Value(Value::Int(x))) => {
+            *x >= 0 && *x as u64 <= member_count
+        }
+        (ColumnType::MYSQL_TYPE_SET, BinlogValue::Value(Value::Bytes(x))) => {
+            x.iter().enumerate().all(|(i, byte)| {
+                (0..8).all(|bit| byte & (1 << bit) == 0 || ((i * 8 + bit) as u64) < member_count)
+            })
+        }
+        _ => true,
+    };
+
+    if is_valid {
+        Ok(())
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "{:?} value refers to a non-existent member (the column has {} members)",
+                column_type, member_count
+            ),
+        ))
+    }
+}
+
 impl fmt::Debug for BinlogRow {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let mut debug = f.debug_struct("BinlogRow");
@@ -258,6 +446,124 @@ impl fmt::Debug for BinlogRow {
     }
 }
 
+/// Renders an `INSERT INTO table (columns) VALUES (row)` statement.
+///
+/// Identifiers are quoted with backticks, values are rendered using
+/// [`BinlogValue::to_sql_literal`]. Note, that `table` is quoted as a single identifier,
+/// so it shouldn't be qualified with a database name.
+pub fn row_to_insert_sql(table: &str, columns: &[&str], row: &[BinlogValue<'_>]) -> String {
+    fn quote_ident(ident: &str) -> String {
+        format!("`{}`", ident.replace('`', "``"))
+    }
+
+    let columns = columns
+        .iter()
+        .map(|x| quote_ident(x))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let values = row
+        .iter()
+        .map(|x| x.to_sql_literal())
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    format!(
+        "INSERT INTO {} ({}) VALUES ({})",
+        quote_ident(table),
+        columns,
+        values
+    )
+}
+
+/// Returns `true` if both rows have the same number of values and values are equal
+/// at every column offset except those listed in `ignore` (e.g. to deduplicate rows
+/// that only differ in an `updated_at` column).
+///
+/// Values are compared using `PartialEq`, so a NULL equals to a NULL.
+pub fn rows_equal_ignoring(a: &[BinlogValue<'_>], b: &[BinlogValue<'_>], ignore: &[usize]) -> bool {
+    a.len() == b.len()
+        && a.iter()
+            .zip(b)
+            .enumerate()
+            .all(|(i, (a, b))| ignore.contains(&i) || a == b)
+}
+
+/// Returns a fingerprint of row values (e.g. to use as an idempotency key of a CDC sink).
+///
+/// The fingerprint is the 64-bit FNV-1a hash of a tagged binary encoding of values, so,
+/// unlike hashes based on `RandomState`, it's stable across process runs:
+///
+/// *   plain values are encoded using the binary protocol;
+/// *   decimals are encoded as the scale followed by the MySql binary representation
+///     (see [`crate::binlog::decimal::Decimal::write_bin`]);
+/// *   JSONB documents and JSON diffs are encoded using the binlog binary format;
+/// *   errors are encoded as the column type followed by the error message.
+pub fn row_fingerprint(row: &[BinlogValue<'_>]) -> u64 {
+    fn encode(value: &BinlogValue<'_>, out: &mut Vec<u8>) {
+        match value {
+            BinlogValue::Value(x) => {
+                out.push(match x {
+                    Value::NULL => 0,
+                    Value::Bytes(_) => 1,
+                    Value::Int(_) => 2,
+                    Value::UInt(_) => 3,
+                    Value::Float(_) => 4,
+                    Value::Double(_) => 5,
+                    Value::Date(..) => 6,
+                    Value::Time(..) => 7,
+                });
+                x.serialize(out);
+            }
+            BinlogValue::TypedArray(elements) => {
+                out.push(8);
+                out.extend_from_slice(&(elements.len() as u64).to_le_bytes());
+                elements.iter().for_each(|x| encode(x, out));
+            }
+            BinlogValue::Decimal(x) => {
+                out.push(9);
+                out.extend_from_slice(&(x.scale() as u64).to_le_bytes());
+                let mut bin = Vec::with_capacity(x.bin_size());
+                x.write_bin(&mut bin)
+                    .expect("writing into a Vec can't fail");
+                put_bytes(&bin, out);
+            }
+            BinlogValue::Jsonb(x) => {
+                out.push(10);
+                let mut bin = Vec::new();
+                x.serialize(&mut bin);
+                put_bytes(&bin, out);
+            }
+            BinlogValue::JsonDiff(diffs) => {
+                out.push(11);
+                out.extend_from_slice(&(diffs.len() as u64).to_le_bytes());
+                for diff in diffs {
+                    let mut bin = Vec::new();
+                    diff.serialize(&mut bin);
+                    put_bytes(&bin, out);
+                }
+            }
+            BinlogValue::Error(x) => {
+                out.push(12);
+                out.push(x.column_type as u8);
+                put_bytes(x.message.as_bytes(), out);
+            }
+        }
+    }
+
+    fn put_bytes(bytes: &[u8], out: &mut Vec<u8>) {
+        out.extend_from_slice(&(bytes.len() as u64).to_le_bytes());
+        out.extend_from_slice(bytes);
+    }
+
+    let mut out = Vec::new();
+    out.extend_from_slice(&(row.len() as u64).to_le_bytes());
+    row.iter().for_each(|x| encode(x, &mut out));
+
+    out.iter().fold(0xcbf2_9ce4_8422_2325, |hash, x| {
+        (hash ^ *x as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
 #[derive(Debug, thiserror::Error)]
 #[error(
     "Can't convert BinlogRow to Row at column offset {}: {}",
@@ -290,3 +596,273 @@ impl TryFrom<BinlogRow> for Row {
         Ok(new_row_raw(values, binlog_row.columns))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_render_insert_sql() {
+        let row = [
+            BinlogValue::Value(Value::Int(1)),
+            BinlogValue::Value(Value::Bytes(b"it's \"quoted\"".to_vec())),
+            BinlogValue::Value(Value::NULL),
+        ];
+        assert_eq!(
+            row_to_insert_sql("t`1", &["id", "name", "note"], &row),
+            r#"INSERT INTO `t``1` (`id`, `name`, `note`) VALUES (1, 'it\'s \"quoted\"', NULL)"#
+        );
+    }
+
+    #[test]
+    fn should_compare_rows_ignoring_columns() {
+        let a = [
+            BinlogValue::Value(Value::Int(1)),
+            BinlogValue::Value(Value::Bytes(b"foo".to_vec())),
+            BinlogValue::Value(Value::Int(1_640_995_199)),
+        ];
+        let b = [
+            BinlogValue::Value(Value::Int(1)),
+            BinlogValue::Value(Value::Bytes(b"foo".to_vec())),
+            BinlogValue::Value(Value::Int(1_640_995_200)),
+        ];
+        assert!(rows_equal_ignoring(&a, &b, &[2]));
+        assert!(rows_equal_ignoring(&a, &a, &[]));
+        assert!(!rows_equal_ignoring(&a, &b, &[]));
+        assert!(!rows_equal_ignoring(&a, &b, &[1]));
+        assert!(!rows_equal_ignoring(&a, &b[..2], &[2]));
+    }
+
+    #[test]
+    fn should_compute_stable_row_fingerprint() {
+        let row = || {
+            vec![
+                BinlogValue::Value(Value::Int(1)),
+                BinlogValue::Value(Value::Bytes(b"foo".to_vec())),
+                BinlogValue::Value(Value::NULL),
+                BinlogValue::json(serde_json::json!({ "a": [1, 2] })),
+                BinlogValue::Decimal("-12.50".parse().unwrap()),
+            ]
+        };
+        let fingerprint = row_fingerprint(&row());
+        assert_eq!(row_fingerprint(&row()), fingerprint);
+        // fingerprints are persisted by sinks, so they must not change between runs
+        assert_eq!(fingerprint, 1_472_549_087_552_561_742);
+
+        let mut other = row();
+        other[2] = BinlogValue::Value(Value::Bytes(vec![]));
+        assert_ne!(row_fingerprint(&other), fingerprint);
+        assert_ne!(row_fingerprint(&row()[..4]), fingerprint);
+
+        // decimals are hashed by value and scale
+        let mut other = row();
+        other[4] = BinlogValue::Decimal("-12.5".parse().unwrap());
+        assert_ne!(row_fingerprint(&other), fingerprint);
+        assert_ne!(
+            row_fingerprint(&[BinlogValue::bytes("ab"), BinlogValue::bytes("c")]),
+            row_fingerprint(&[BinlogValue::bytes("a"), BinlogValue::bytes("bc")])
+        );
+        assert_ne!(
+            row_fingerprint(&[BinlogValue::Value(Value::Int(1))]),
+            row_fingerprint(&[BinlogValue::Value(Value::UInt(1))])
+        );
+    }
+
+    #[test]
+    fn to_minimal_image() {
+        let columns: Arc<[Column]> = vec![
+            Column::new(ColumnType::MYSQL_TYPE_LONG).with_name(b"@0"),
+            Column::new(ColumnType::MYSQL_TYPE_VARCHAR).with_name(b"@1"),
+            Column::new(ColumnType::MYSQL_TYPE_LONG).with_name(b"@2"),
+        ]
+        .into_boxed_slice()
+        .into();
+        let row = BinlogRow::new(
+            vec![
+                Some(BinlogValue::Value(Value::Int(1))),
+                Some(BinlogValue::Value(Value::Bytes(b"foo".to_vec()))),
+                Some(BinlogValue::Value(Value::NULL)),
+            ],
+            columns,
+        );
+
+        let minimal = row.to_minimal_image(&[0]).unwrap();
+        assert_eq!(minimal.len(), 1);
+        assert_eq!(minimal.columns_ref()[0].name_str(), "@0");
+        assert_eq!(minimal.unwrap(), vec![BinlogValue::Value(Value::Int(1))]);
+
+        // composite key given out of the table order
+        let minimal = row.to_minimal_image(&[2, 0, 2]).unwrap();
+        assert_eq!(minimal.len(), 2);
+        assert_eq!(minimal.columns_ref()[0].name_str(), "@0");
+        assert_eq!(minimal.columns_ref()[1].name_str(), "@2");
+        assert_eq!(
+            minimal.unwrap(),
+            vec![
+                BinlogValue::Value(Value::Int(1)),
+                BinlogValue::Value(Value::NULL)
+            ]
+        );
+
+        assert!(row.to_minimal_image(&[3]).is_none());
+    }
+
+    #[test]
+    fn should_distinguish_varbinary_from_varchar() -> io::Result<()> {
+        use crate::binlog::{events::FormatDescriptionEvent, BinlogCtx, BinlogVersion};
+
+        const TABLE_MAP: &[u8] = &[
+            1, 0, 0, 0, 0, 0, // table id
+            0, 0, // flags
+            2, b'd', b'b', 0, // database name
+            1, b't', 0, // table name
+            2, // columns count
+            15, 15, // MYSQL_TYPE_VARCHAR, MYSQL_TYPE_VARCHAR
+            4, 32, 0, 32, 0, // columns metadata
+            0, // null bitmask
+            2, 3, 33, 0, 63, // DEFAULT_CHARSET: utf8 by default, binary for the column 0
+        ];
+
+        let fde = FormatDescriptionEvent::new(BinlogVersion::Version4);
+        let table_map: TableMapEvent =
+            ParseBuf(TABLE_MAP).parse(BinlogCtx::new(TABLE_MAP.len(), &fde))?;
+        assert_eq!(table_map.column_charsets()?, vec![Some(63), Some(33)]);
+
+        let cols = BitSlice::<u8>::from_slice(&[0b11]);
+        let row: BinlogRow = ParseBuf(&[0, 3, b'f', b'o', b'o', 3, b'b', b'a', b'r'])
+            .parse((2, cols, false, &table_map))?;
+
+        let columns = row.columns();
+        assert!(columns[0].flags().contains(ColumnFlags::BINARY_FLAG));
+        assert!(!columns[1].flags().contains(ColumnFlags::BINARY_FLAG));
+        assert!(row.as_ref(0).unwrap().is_binary(&columns[0]));
+        assert!(!row.as_ref(1).unwrap().is_binary(&columns[1]));
+
+        // a non-default charset of a non-existent column falls back to the unknown charset
+        let mut malformed = TABLE_MAP.to_vec();
+        let len = malformed.len();
+        malformed[len - 2] = 5;
+        let table_map: TableMapEvent =
+            ParseBuf(&malformed).parse(BinlogCtx::new(malformed.len(), &fde))?;
+        assert!(table_map.column_charsets().is_err());
+
+        let row: BinlogRow = ParseBuf(&[0, 3, b'f', b'o', b'o', 3, b'b', b'a', b'r'])
+            .parse((2, cols, false, &table_map))?;
+        for column in row.columns_ref() {
+            assert_eq!(column.character_set(), 0);
+            assert!(!column.flags().contains(ColumnFlags::BINARY_FLAG));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_parse_row_leniently() -> io::Result<()> {
+        use crate::binlog::{events::FormatDescriptionEvent, BinlogCtx, BinlogVersion};
+
+        const TABLE_MAP: &[u8] = &[
+            1, 0, 0, 0, 0, 0, // table id
+            0, 0, // flags
+            2, b'd', b'b', 0, // database name
+            1, b't', 0, // table name
+            3, // columns count
+            3, 19, 3, // MYSQL_TYPE_LONG, MYSQL_TYPE_TIME2, MYSQL_TYPE_LONG
+            1, 0, // columns metadata
+            0, // null bitmask
+        ];
+
+        let fde = FormatDescriptionEvent::new(BinlogVersion::Version4);
+        let table_map: TableMapEvent =
+            ParseBuf(TABLE_MAP).parse(BinlogCtx::new(TABLE_MAP.len(), &fde))?;
+        let cols = BitSlice::<u8>::from_slice(&[0b111]);
+        let ctx = (3, cols, false, &table_map);
+
+        // TIME2 value of 1000 hours is out of range
+        let data = [0, 42, 0, 0, 0, 0xBE, 0x80, 0x00, 43, 0, 0, 0];
+
+        let err = ParseBuf(&data).parse::<BinlogRow>(ctx).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        let mut buf = ParseBuf(&data);
+        let (row, errors) = BinlogRow::deserialize_lenient(ctx, &mut buf)?;
+        assert!(buf.is_empty());
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].0, 1);
+        assert_eq!(errors[0].1.column_type, ColumnType::MYSQL_TYPE_TIME2);
+        assert_eq!(errors[0].1.kind, io::ErrorKind::InvalidData);
+        assert_eq!(
+            row.unwrap(),
+            vec![
+                BinlogValue::Value(Value::Int(42)),
+                BinlogValue::Error(errors[0].1.clone()),
+                BinlogValue::Value(Value::Int(43)),
+            ]
+        );
+
+        // truncated values are reported along with their offsets
+        let data = [0, 42, 0, 0, 0, 0x80, 0x00, 0x00, 43, 0, 0, 0];
+        for (len, offset) in [(10, 8), (7, 5)] {
+            let err = ParseBuf(&data[..len]).parse::<BinlogRow>(ctx).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+            assert_eq!(
+                err.to_string(),
+                format!(
+                    "unexpected EOF at offset {}, {} bytes remaining",
+                    offset,
+                    len - offset
+                )
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_honor_signedness_of_tiny() -> io::Result<()> {
+        use crate::binlog::{events::FormatDescriptionEvent, BinlogCtx, BinlogVersion};
+
+        const TABLE_MAP: &[u8] = &[
+            1, 0, 0, 0, 0, 0, // table id
+            0, 0, // flags
+            2, b'd', b'b', 0, // database name
+            1, b't', 0, // table name
+            3, // columns count
+            1, 1, 1, // MYSQL_TYPE_TINY, MYSQL_TYPE_TINY, MYSQL_TYPE_TINY
+            0, // columns metadata
+            0, // null bitmask
+            1, 1, 0xA0, // SIGNEDNESS: 0b1010_0000, i.e. unsigned, signed, unsigned
+        ];
+
+        let fde = FormatDescriptionEvent::new(BinlogVersion::Version4);
+        let table_map: TableMapEvent =
+            ParseBuf(TABLE_MAP).parse(BinlogCtx::new(TABLE_MAP.len(), &fde))?;
+
+        let cols = BitSlice::<u8>::from_slice(&[0b111]);
+        let row: BinlogRow =
+            ParseBuf(&[0, 0xFF, 0xFF, 0xFF]).parse((3, cols, false, &table_map))?;
+        assert_eq!(
+            row.unwrap(),
+            vec![
+                BinlogValue::Value(Value::Int(255)),
+                BinlogValue::Value(Value::Int(-1)),
+                BinlogValue::Value(Value::Int(255)),
+            ]
+        );
+
+        // the first column isn't in the image
+        let cols = BitSlice::<u8>::from_slice(&[0b110]);
+        let row: BinlogRow = ParseBuf(&[0, 0xFF, 0xFF]).parse((3, cols, false, &table_map))?;
+        assert!(row.columns_ref()[1]
+            .flags()
+            .contains(ColumnFlags::UNSIGNED_FLAG));
+        assert_eq!(
+            row.unwrap(),
+            vec![
+                BinlogValue::Value(Value::Int(-1)),
+                BinlogValue::Value(Value::Int(255)),
+            ]
+        );
+
+        Ok(())
+    }
+}
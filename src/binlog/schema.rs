@@ -0,0 +1,298 @@
+// Copyright (c) 2021 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Row image parsing guided by a table schema given as JSON
+//! (i.e. without a corresponding `TableMapEvent`).
+//!
+//! Schema is an array of column definitions, e.g.:
+//!
+//! ```json
+//! [
+//!     { "name": "id", "type": "int", "unsigned": true },
+//!     { "name": "title", "type": "varchar", "length": 1020 },
+//!     { "name": "price", "type": "decimal", "precision": 10, "scale": 2 },
+//!     { "name": "created", "type": "datetime", "fsp": 6 }
+//! ]
+//! ```
+//!
+//! where:
+//!
+//! *   `type` – SQL type name (only scalar types are supported, i.e. integer, floating point,
+//!     decimal, temporal, BIT, string and BLOB types);
+//! *   `name` – optional column name (defaults to `@<i>` where `i` is the column offset);
+//! *   `unsigned` – optional signedness of a numeric column (defaults to `false`);
+//! *   `length` – maximum length of a CHAR, BINARY, VARCHAR or VARBINARY value in bytes;
+//! *   `precision` and `scale` – precision and scale of a DECIMAL column;
+//! *   `fsp` – optional fractional seconds precision of a temporal column (defaults to `0`);
+//! *   `width` – width of a BIT column.
+
+use std::{io, sync::Arc};
+
+use bitvec::prelude::BitVec;
+
+use crate::{
+    constants::{ColumnFlags, ColumnType},
+    io::ParseBuf,
+    packets::Column,
+};
+
+use super::{
+    row::BinlogRow,
+    value::{BinlogValue, BinlogValueCtx},
+};
+
+fn malformed_schema(msg: impl std::fmt::Display) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("Malformed schema: {}", msg),
+    )
+}
+
+/// Column definition of a [`RowSchema`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ColumnSchema {
+    name: Option<String>,
+    column_type: ColumnType,
+    column_meta: Vec<u8>,
+    is_unsigned: bool,
+}
+
+impl ColumnSchema {
+    /// Creates a column definition out of its JSON description (see the module docs).
+    pub fn from_json(json: &serde_json::Value) -> io::Result<Self> {
+        use ColumnType::*;
+
+        let field = |name: &str| json.get(name);
+        let int_field = |name: &str| -> io::Result<Option<u64>> {
+            match field(name) {
+                Some(x) => x
+                    .as_u64()
+                    .map(Some)
+                    .ok_or_else(|| malformed_schema(format!("`{}` must be an integer", name))),
+                None => Ok(None),
+            }
+        };
+        let required_int_field = |name: &str, max: u64| -> io::Result<u64> {
+            match int_field(name)? {
+                Some(x) if x <= max => Ok(x),
+                Some(_) => Err(malformed_schema(format!("`{}` is out of range", name))),
+                None => Err(malformed_schema(format!("`{}` is required", name))),
+            }
+        };
+        let fsp = || -> io::Result<Vec<u8>> {
+            match int_field("fsp")?.unwrap_or(0) {
+                x @ 0..=6 => Ok(vec![x as u8]),
+                _ => Err(malformed_schema("`fsp` is out of range")),
+            }
+        };
+
+        let name = match field("name") {
+            Some(x) => Some(
+                x.as_str()
+                    .ok_or_else(|| malformed_schema("`name` must be a string"))?
+                    .to_owned(),
+            ),
+            None => None,
+        };
+        let is_unsigned = match field("unsigned") {
+            Some(x) => x
+                .as_bool()
+                .ok_or_else(|| malformed_schema("`unsigned` must be a boolean"))?,
+            None => false,
+        };
+        let type_name = field("type")
+            .and_then(|x| x.as_str())
+            .ok_or_else(|| malformed_schema("`type` must be a string"))?
+            .to_ascii_lowercase();
+
+        let (column_type, column_meta) = match &*type_name {
+            "tinyint" => (MYSQL_TYPE_TINY, vec![]),
+            "smallint" => (MYSQL_TYPE_SHORT, vec![]),
+            "mediumint" => (MYSQL_TYPE_INT24, vec![]),
+            "int" | "integer" => (MYSQL_TYPE_LONG, vec![]),
+            "bigint" => (MYSQL_TYPE_LONGLONG, vec![]),
+            "float" => (MYSQL_TYPE_FLOAT, vec![4]),
+            "double" => (MYSQL_TYPE_DOUBLE, vec![8]),
+            "decimal" => {
+                let precision = required_int_field("precision", 65)?;
+                let scale = required_int_field("scale", precision.min(30))?;
+                (MYSQL_TYPE_NEWDECIMAL, vec![precision as u8, scale as u8])
+            }
+            "year" => (MYSQL_TYPE_YEAR, vec![]),
+            "date" => (MYSQL_TYPE_NEWDATE, vec![]),
+            "time" => (MYSQL_TYPE_TIME2, fsp()?),
+            "datetime" => (MYSQL_TYPE_DATETIME2, fsp()?),
+            "timestamp" => (MYSQL_TYPE_TIMESTAMP2, fsp()?),
+            "bit" => {
+                let width = required_int_field("width", 64)?;
+                (MYSQL_TYPE_BIT, vec![(width % 8) as u8, (width / 8) as u8])
+            }
+            "char" | "binary" => {
+                let length = required_int_field("length", 1020)?;
+                // real type and length are mixed up here (see `MYSQL_TYPE_STRING` metadata)
+                let byte0 = (ColumnType::MYSQL_TYPE_STRING as u8 & !0x30)
+                    | ((((length >> 8) << 4) as u8 & 0x30) ^ 0x30);
+                (MYSQL_TYPE_STRING, vec![byte0, length as u8])
+            }
+            "varchar" | "varbinary" => {
+                let length = required_int_field("length", u16::MAX as u64)?;
+                (MYSQL_TYPE_VARCHAR, (length as u16).to_le_bytes().to_vec())
+            }
+            "tinyblob" | "tinytext" => (MYSQL_TYPE_BLOB, vec![1]),
+            "blob" | "text" => (MYSQL_TYPE_BLOB, vec![2]),
+            "mediumblob" | "mediumtext" => (MYSQL_TYPE_BLOB, vec![3]),
+            "longblob" | "longtext" => (MYSQL_TYPE_BLOB, vec![4]),
+            other => return Err(malformed_schema(format!("unsupported type `{}`", other))),
+        };
+
+        Ok(Self {
+            name,
+            column_type,
+            column_meta,
+            is_unsigned,
+        })
+    }
+
+    /// Returns the column name (if given).
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Returns the column type (as it would be given by a `TableMapEvent`).
+    pub fn column_type(&self) -> ColumnType {
+        self.column_type
+    }
+
+    /// Returns the column metadata (as it would be given by a `TableMapEvent`).
+    pub fn column_meta(&self) -> &[u8] {
+        &self.column_meta
+    }
+
+    /// Returns `true` if the column is an unsigned numeric column.
+    pub fn is_unsigned(&self) -> bool {
+        self.is_unsigned
+    }
+
+    /// Returns a context for [`BinlogValue`] deserialization.
+    pub fn value_ctx(&self) -> BinlogValueCtx<'_> {
+        BinlogValueCtx::new(self.column_type, &self.column_meta).with_unsigned(self.is_unsigned)
+    }
+}
+
+/// Table schema for parsing row images without a `TableMapEvent`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RowSchema {
+    columns: Vec<ColumnSchema>,
+}
+
+impl RowSchema {
+    /// Creates a schema out of its JSON description (see the module docs).
+    pub fn from_json(json: &serde_json::Value) -> io::Result<Self> {
+        let columns = json
+            .as_array()
+            .ok_or_else(|| malformed_schema("array of columns expected"))?
+            .iter()
+            .map(ColumnSchema::from_json)
+            .collect::<io::Result<Vec<_>>>()?;
+        Ok(Self { columns })
+    }
+
+    /// Returns column definitions.
+    pub fn columns(&self) -> &[ColumnSchema] {
+        &self.columns
+    }
+
+    /// Parses a full row image (the null bitmap followed by values of non-null columns).
+    pub fn parse_row<'a>(&'a self, buf: &mut ParseBuf<'a>) -> io::Result<BinlogRow> {
+        let bitmap_len = (self.columns.len() + 7) / 8;
+        let bitmap_buf: &[u8] = buf.parse(bitmap_len)?;
+        let null_bitmap = BitVec::<u8>::from_slice(bitmap_buf);
+
+        let mut values = Vec::with_capacity(self.columns.len());
+        let mut columns = Vec::with_capacity(self.columns.len());
+        for (i, column) in self.columns.iter().enumerate() {
+            let value = if null_bitmap[i] {
+                BinlogValue::Value(crate::value::Value::NULL)
+            } else {
+                buf.parse::<BinlogValue>(column.value_ctx())?.into_owned()
+            };
+            values.push(Some(value));
+
+            let mut column_flags = ColumnFlags::empty();
+            if column.is_unsigned {
+                column_flags |= ColumnFlags::UNSIGNED_FLAG;
+            }
+            let name = match column.name {
+                Some(ref name) => name.clone(),
+                None => format!("@{}", i),
+            };
+            columns.push(
+                Column::new(column.column_type)
+                    .with_name(name.as_bytes())
+                    .with_flags(column_flags),
+            );
+        }
+
+        Ok(BinlogRow::new(
+            values,
+            Arc::from(columns.into_boxed_slice()),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+    use crate::{binlog::decimal::Decimal, value::Value};
+
+    #[test]
+    fn should_parse_row_with_schema() -> io::Result<()> {
+        let schema = RowSchema::from_json(&serde_json::json!([
+            { "name": "id", "type": "int", "unsigned": true },
+            { "name": "title", "type": "varchar", "length": 32 },
+            { "name": "price", "type": "decimal", "precision": 10, "scale": 2 },
+            { "name": "created", "type": "DATE" },
+            { "name": "code", "type": "char", "length": 300 },
+            { "type": "bigint" },
+        ]))?;
+        assert_eq!(schema.columns()[4].column_meta(), &[0xEE, 44]);
+
+        let mut data = vec![0b0010_0000];
+        data.extend_from_slice(&[0xFF, 0xFF, 0xFF, 0xFF]);
+        data.extend_from_slice(b"\x03foo");
+        Decimal::from_str("00000012.50")
+            .unwrap()
+            .write_bin(&mut data)?;
+        data.extend_from_slice(&[0x9F, 0xCB, 0x0F]);
+        data.extend_from_slice(b"\x02\x00ab");
+
+        let mut buf = ParseBuf(&data);
+        let row = schema.parse_row(&mut buf)?;
+        assert!(buf.is_empty());
+        assert_eq!(row.columns_ref()[0].name_str(), "id");
+        assert_eq!(row.columns_ref()[5].name_str(), "@5");
+        assert_eq!(
+            row.unwrap(),
+            vec![
+                BinlogValue::Value(Value::Int(u32::MAX as i64)),
+                BinlogValue::Value(Value::Bytes(b"foo".to_vec())),
+                BinlogValue::Decimal(Decimal::from_str("00000012.50").unwrap()),
+                BinlogValue::Value(Value::Date(2021, 12, 31, 0, 0, 0, 0)),
+                BinlogValue::Value(Value::Bytes(b"ab".to_vec())),
+                BinlogValue::Value(Value::NULL),
+            ]
+        );
+
+        let err = RowSchema::from_json(&serde_json::json!([{ "type": "json" }])).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,420 @@
+// Copyright (c) 2021 Anatoly Ikorsky
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Decoding of whole transactions out of a sequence of binlog events.
+
+use std::{collections::HashMap, io};
+
+use crate::io::ParseBuf;
+
+use super::{
+    consts::{BinlogChecksumAlg, BinlogVersion, EventType},
+    events::{
+        BinlogEventFooter, BinlogEventHeader, Event, EventData, FormatDescriptionEvent, GtidEvent,
+        TableMapEvent,
+    },
+    row::BinlogRow,
+    value::BinlogValue,
+};
+
+/// Before and after images of a changed row (see [`super::events::RowsEventRows`]).
+pub type RowChange = (Option<BinlogRow>, Option<BinlogRow>);
+
+/// Rows of a single table changed by a transaction.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableChanges {
+    table_id: u64,
+    database_name: String,
+    table_name: String,
+    rows: Vec<RowChange>,
+}
+
+impl TableChanges {
+    /// Returns the table identifier (see [`TableMapEvent::table_id`]).
+    pub fn table_id(&self) -> u64 {
+        self.table_id
+    }
+
+    /// Returns the database name.
+    pub fn database_name(&self) -> &str {
+        &self.database_name
+    }
+
+    /// Returns the table name.
+    pub fn table_name(&self) -> &str {
+        &self.table_name
+    }
+
+    /// Returns changed rows in the binlog order.
+    pub fn rows(&self) -> &[RowChange] {
+        &self.rows
+    }
+}
+
+/// Transaction decoded out of a sequence of binlog events.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transaction {
+    gtid: Option<GtidEvent>,
+    xid: u64,
+    tables: Vec<TableChanges>,
+}
+
+impl Transaction {
+    /// Decodes a single transaction out of `events`, i.e. out of a GTID event followed by
+    /// table map and rows events, and terminated by a XID event.
+    ///
+    /// The GTID event is optional (e.g. if GTIDs are disabled), other events
+    /// (such as the `BEGIN` query event) are skipped. Rows are grouped by table in order
+    /// of the first change of a table.
+    ///
+    /// Returns an error if a rows event refers to an unknown table, if there is no XID event,
+    /// or if there are any events after the XID event.
+    pub fn from_events(events: &[Event]) -> io::Result<Self> {
+        let mut gtid = None;
+        let mut table_maps: HashMap<u64, TableMapEvent<'_>> = HashMap::new();
+        let mut tables: Vec<TableChanges> = Vec::new();
+
+        for (i, event) in events.iter().enumerate() {
+            match event.read_data()? {
+                Some(EventData::GtidEvent(x)) => gtid = Some(x),
+                Some(EventData::TableMapEvent(x)) => {
+                    table_maps.insert(x.table_id(), x);
+                }
+                Some(EventData::RowsEvent(rows_event)) => {
+                    let table_id = rows_event.table_id();
+                    let tme = table_maps.get(&table_id).ok_or_else(|| {
+                        io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            format!("unknown table id {}", table_id),
+                        )
+                    })?;
+
+                    let pos = match tables.iter().position(|x| x.table_id == table_id) {
+                        Some(pos) => pos,
+                        None => {
+                            tables.push(TableChanges {
+                                table_id,
+                                database_name: tme.database_name().into_owned(),
+                                table_name: tme.table_name().into_owned(),
+                                rows: Vec::new(),
+                            });
+                            tables.len() - 1
+                        }
+                    };
+                    for row in rows_event.rows(tme) {
+                        tables[pos].rows.push(row?);
+                    }
+                }
+                Some(EventData::XidEvent(x)) => {
+                    if i + 1 != events.len() {
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            "events after the end of the transaction",
+                        ));
+                    }
+                    return Ok(Self {
+                        gtid,
+                        xid: x.xid,
+                        tables,
+                    });
+                }
+                _ => (),
+            }
+        }
+
+        Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "transaction isn't terminated by a XID event",
+        ))
+    }
+
+    /// Returns the GTID event of this transaction (if any).
+    pub fn gtid(&self) -> Option<&GtidEvent> {
+        self.gtid.as_ref()
+    }
+
+    /// Returns the XID of this transaction.
+    pub fn xid(&self) -> u64 {
+        self.xid
+    }
+
+    /// Returns changes grouped by table.
+    pub fn tables(&self) -> &[TableChanges] {
+        &self.tables
+    }
+}
+
+/// Extracts events out of a `BINLOG` statement (see [`decode_base64_rows`]).
+fn decode_binlog_statement(stmt: &str) -> io::Result<Vec<u8>> {
+    let malformed = || io::Error::new(io::ErrorKind::InvalidData, "Malformed BINLOG statement");
+
+    let mut body = stmt.trim();
+    if body.len() >= 6 && body[..6].eq_ignore_ascii_case("BINLOG") {
+        let start = body.find('\'').ok_or_else(malformed)? + 1;
+        let len = body[start..].find('\'').ok_or_else(malformed)?;
+        body = &body[start..(start + len)];
+    }
+
+    let body = body
+        .bytes()
+        .filter(|x| !x.is_ascii_whitespace())
+        .collect::<Vec<_>>();
+
+    // events are encoded one by one, so a padding could be found in the middle of the body
+    let mut bytes = Vec::new();
+    let mut chunk_start = 0;
+    for (i, quantum) in body.chunks(4).enumerate() {
+        let chunk_end = i * 4 + quantum.len();
+        if quantum.contains(&b'=') || chunk_end == body.len() {
+            base64::decode_config_buf(&body[chunk_start..chunk_end], base64::STANDARD, &mut bytes)
+                .map_err(|_| malformed())?;
+            chunk_start = chunk_end;
+        }
+    }
+
+    Ok(bytes)
+}
+
+/// Returns `true` if the event ends with a valid CRC32 checksum.
+fn has_crc32_checksum(event: &[u8]) -> bool {
+    if event.len() < BinlogEventHeader::LEN + BinlogEventFooter::BINLOG_CHECKSUM_LEN {
+        return false;
+    }
+    let (data, checksum) = event.split_at(event.len() - BinlogEventFooter::BINLOG_CHECKSUM_LEN);
+    crc32fast::hash(data).to_le_bytes() == checksum
+}
+
+/// Decodes rows of a `BINLOG` statement printed by `mysqlbinlog --base64-output`.
+///
+/// `stmt` is either the whole statement (i.e. `BINLOG '...'`) or just its base64 body.
+/// Rows events must refer to the given table, while other events (e.g. the table map event
+/// itself) are skipped. Row images are returned in the binlog order, so the before image
+/// of an updated row precedes its after image.
+///
+/// Events are assumed to be checksummed if the statement contains a format description
+/// event that says so, or if an event ends with a valid CRC32 checksum.
+pub fn decode_base64_rows(
+    stmt: &str,
+    table_map: &TableMapEvent<'_>,
+) -> io::Result<Vec<Vec<BinlogValue<'static>>>> {
+    let bytes = decode_binlog_statement(stmt)?;
+
+    let mut fde: Option<FormatDescriptionEvent<'static>> = None;
+    let no_checksum_fde = FormatDescriptionEvent::new(BinlogVersion::Version4);
+    let crc32_fde = FormatDescriptionEvent::new(BinlogVersion::Version4).with_footer(
+        BinlogEventFooter::new(BinlogChecksumAlg::BINLOG_CHECKSUM_ALG_CRC32),
+    );
+
+    let mut rows = Vec::new();
+    let mut buf = &bytes[..];
+    while !buf.is_empty() {
+        let header: BinlogEventHeader = ParseBuf(buf).parse(())?;
+        let event_size = header.event_size() as usize;
+        if event_size < BinlogEventHeader::LEN || event_size > buf.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "truncated event in BINLOG statement",
+            ));
+        }
+        let (event_bytes, rest) = buf.split_at(event_size);
+        buf = rest;
+
+        let is_fde = header.event_type_raw() == EventType::FORMAT_DESCRIPTION_EVENT as u8;
+        let event_fde = match fde {
+            Some(ref fde) if !is_fde => fde,
+            _ if has_crc32_checksum(event_bytes) => &crc32_fde,
+            _ => &no_checksum_fde,
+        };
+        let event = Event::read(event_fde, event_bytes)?;
+
+        match event.read_data()? {
+            Some(EventData::FormatDescriptionEvent(_)) => fde = Some(event.fde().clone()),
+            Some(EventData::RowsEvent(rows_event)) => {
+                if rows_event.table_id() != table_map.table_id() {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("unknown table id {}", rows_event.table_id()),
+                    ));
+                }
+                for row in rows_event.rows(table_map) {
+                    let (before, after) = row?;
+                    rows.extend(before.into_iter().chain(after).map(BinlogRow::unwrap));
+                }
+            }
+            _ => (),
+        }
+    }
+
+    Ok(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        binlog::{
+            consts::{BinlogVersion, EventFlags, EventType},
+            events::{BinlogEventHeader, FormatDescriptionEvent, XidEvent},
+            value::BinlogValue,
+        },
+        proto::MySerialize,
+        value::Value,
+    };
+
+    fn event(fde: &FormatDescriptionEvent<'_>, event_type: EventType, data: &[u8]) -> Event {
+        let header = BinlogEventHeader::new(
+            0,
+            event_type,
+            1,
+            (BinlogEventHeader::LEN + data.len()) as u32,
+            0,
+            EventFlags::empty(),
+        );
+        let mut bytes = Vec::new();
+        header.serialize(&mut bytes);
+        bytes.extend_from_slice(data);
+        Event::read(fde, &bytes[..]).unwrap()
+    }
+
+    #[test]
+    fn should_decode_transaction() -> io::Result<()> {
+        let fde = FormatDescriptionEvent::new(BinlogVersion::Version4);
+
+        let mut gtid = Vec::new();
+        GtidEvent::new([0xAB; 16], 42).serialize(&mut gtid);
+        let table_map = [
+            1, 0, 0, 0, 0, 0, // table id
+            0, 0, // flags
+            2, b'd', b'b', 0, // database name
+            1, b't', 0, // table name
+            1, // columns count
+            3, // MYSQL_TYPE_LONG
+            0, // columns metadata
+            0, // null bitmask
+        ];
+        // two rows in a single `LONG` column
+        let rows = [
+            1, 0, 0, 0, 0, 0, 0, 0, 2, 0, 1, 0b1, 0, 1, 0, 0, 0, 0, 2, 0, 0, 0,
+        ];
+        let mut xid = Vec::new();
+        XidEvent { xid: 7 }.serialize(&mut xid);
+
+        let events = vec![
+            event(&fde, EventType::GTID_EVENT, &gtid),
+            event(&fde, EventType::TABLE_MAP_EVENT, &table_map),
+            event(&fde, EventType::WRITE_ROWS_EVENT, &rows),
+            event(&fde, EventType::XID_EVENT, &xid),
+        ];
+
+        let transaction = Transaction::from_events(&events)?;
+        let gtid = transaction.gtid().unwrap();
+        assert_eq!((gtid.sid(), gtid.gno()), ([0xAB; 16], 42));
+        assert_eq!(transaction.xid(), 7);
+        assert_eq!(transaction.tables().len(), 1);
+
+        let table = &transaction.tables()[0];
+        assert_eq!((table.database_name(), table.table_name()), ("db", "t"));
+        let values = table
+            .rows()
+            .iter()
+            .map(|(before, after)| {
+                assert!(before.is_none());
+                after.clone().unwrap().unwrap()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            values,
+            vec![
+                vec![BinlogValue::Value(Value::Int(1))],
+                vec![BinlogValue::Value(Value::Int(2))],
+            ]
+        );
+
+        let err = Transaction::from_events(&events[..3]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+        let err = Transaction::from_events(&events[2..]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        Ok(())
+    }
+
+    #[test]
+    fn should_decode_base64_rows() -> io::Result<()> {
+        let fde = FormatDescriptionEvent::new(BinlogVersion::Version4);
+        let raw_event = |event_type: EventType, data: &[u8], with_checksum: bool| {
+            let checksum_len = if with_checksum { 4 } else { 0 };
+            let header = BinlogEventHeader::new(
+                0,
+                event_type,
+                1,
+                (BinlogEventHeader::LEN + data.len() + checksum_len) as u32,
+                0,
+                EventFlags::empty(),
+            );
+            let mut bytes = Vec::new();
+            header.serialize(&mut bytes);
+            bytes.extend_from_slice(data);
+            if with_checksum {
+                let checksum = crc32fast::hash(&bytes);
+                bytes.extend_from_slice(&checksum.to_le_bytes());
+            }
+            bytes
+        };
+
+        let table_map = [
+            1, 0, 0, 0, 0, 0, // table id
+            0, 0, // flags
+            2, b'd', b'b', 0, // database name
+            1, b't', 0, // table name
+            2, // columns count
+            3, 15, // MYSQL_TYPE_LONG, MYSQL_TYPE_VARCHAR
+            2, 10, 0,    // columns metadata
+            0b10, // null bitmask
+        ];
+        // INSERT INTO db.t VALUES (1, 'foo'), (2, NULL)
+        let rows = [
+            1, 0, 0, 0, 0, 0, 0, 0, 2, 0, 2, 0b11, // rows event header
+            0b00, 1, 0, 0, 0, 3, b'f', b'o', b'o', // first row
+            0b10, 2, 0, 0, 0, // second row
+        ];
+        let tme = event(&fde, EventType::TABLE_MAP_EVENT, &table_map);
+        let tme = tme.read_event::<TableMapEvent>()?;
+        let expected = vec![
+            vec![BinlogValue::int(1), BinlogValue::bytes("foo")],
+            vec![BinlogValue::int(2), BinlogValue::NULL],
+        ];
+
+        for with_checksum in [false, true] {
+            // print events the way `mysqlbinlog` does
+            let mut stmt = String::from("BINLOG '\n");
+            for event in [
+                raw_event(EventType::TABLE_MAP_EVENT, &table_map, with_checksum),
+                raw_event(EventType::WRITE_ROWS_EVENT, &rows, with_checksum),
+            ] {
+                let encoded = base64::encode(&event);
+                for line in encoded.as_bytes().chunks(76) {
+                    stmt.push_str(std::str::from_utf8(line).unwrap());
+                    stmt.push('\n');
+                }
+            }
+            stmt.push_str("'/*!*/;");
+
+            assert_eq!(decode_base64_rows(&stmt, &tme)?, expected);
+        }
+
+        let body = base64::encode(raw_event(EventType::WRITE_ROWS_EVENT, &rows, false));
+        assert_eq!(decode_base64_rows(&body, &tme)?, expected);
+
+        let err = decode_base64_rows(&body[..(body.len() - 8)], &tme).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+        let err = decode_base64_rows("BINLOG 'foo", &tme).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        Ok(())
+    }
+}
@@ -6,105 +6,2045 @@
 // option. All files in the project carrying such notice may not be copied,
 // modified, or distributed except according to those terms.
 
-use std::{convert::TryFrom, io};
+use std::{
+    borrow::Cow,
+    convert::{TryFrom, TryInto},
+    fmt, io,
+};
+
+use bitvec::slice::BitSlice;
+use bytes::BufMut;
 
 use crate::{
     binlog::{decimal, jsonb, jsondiff::JsonDiff, misc::*},
     constants::{ColumnFlags, ColumnType},
-    io::ParseBuf,
-    misc::raw::int::*,
-    proto::MyDeserialize,
+    io::{BufMutExt, ParseBuf},
+    misc::{raw::int::*, unexpected_buf_eof, unexpected_buf_eof_at},
+    packets::Column,
+    proto::{MyDeserialize, MySerialize},
     value::Value::{self, *},
 };
 
 use super::jsonb::JsonbToJsonError;
 
+/// Charset+collation id of the `binary` charset (used by BINARY, VARBINARY and BLOB columns).
+pub const BINARY_CHARSET: u16 = 63;
+
+/// Character set of a text column (see [`BinlogValue::into_value_with_charset`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Charset {
+    /// `binary` – bytes are never transcoded.
+    Binary,
+    /// `utf8mb3` or `utf8mb4`.
+    Utf8,
+    /// MySql `latin1` (i.e. `cp1252` with five undefined code points mapped to C1 controls).
+    Latin1,
+    /// `ascii`.
+    Ascii,
+}
+
+/// Unicode code points of `cp1252` bytes in the `0x80..=0x9F` range (as defined by MySql).
+const CP1252_HIGH: [char; 32] = [
+    '\u{20AC}', '\u{0081}', '\u{201A}', '\u{0192}', '\u{201E}', '\u{2026}', '\u{2020}', '\u{2021}',
+    '\u{02C6}', '\u{2030}', '\u{0160}', '\u{2039}', '\u{0152}', '\u{008D}', '\u{017D}', '\u{008F}',
+    '\u{0090}', '\u{2018}', '\u{2019}', '\u{201C}', '\u{201D}', '\u{2022}', '\u{2013}', '\u{2014}',
+    '\u{02DC}', '\u{2122}', '\u{0161}', '\u{203A}', '\u{0153}', '\u{009D}', '\u{017E}', '\u{0178}',
+];
+
+impl Charset {
+    /// Returns a charset of the given charset+collation id (`None` if it's not supported).
+    pub fn from_collation_id(id: u16) -> Option<Self> {
+        match id {
+            BINARY_CHARSET => Some(Charset::Binary),
+            5 | 8 | 15 | 31 | 47 | 48 | 49 | 94 => Some(Charset::Latin1),
+            11 | 65 => Some(Charset::Ascii),
+            33 | 45 | 46 | 76 | 83 | 192..=215 | 223..=247 | 255..=323 => Some(Charset::Utf8),
+            _ => None,
+        }
+    }
+
+    /// Transcodes `bytes` in this charset to UTF-8.
+    ///
+    /// Invalid sequences are replaced with `U+FFFD REPLACEMENT CHARACTER`.
+    /// `binary` bytes are returned as is.
+    pub fn decode_to_utf8(self, bytes: Vec<u8>) -> Vec<u8> {
+        match self {
+            Charset::Binary => bytes,
+            Charset::Utf8 => match String::from_utf8(bytes) {
+                Ok(string) => string.into_bytes(),
+                Err(err) => String::from_utf8_lossy(err.as_bytes())
+                    .into_owned()
+                    .into_bytes(),
+            },
+            Charset::Latin1 if bytes.is_ascii() => bytes,
+            Charset::Latin1 => bytes
+                .iter()
+                .map(|&x| match x {
+                    0x80..=0x9F => CP1252_HIGH[(x - 0x80) as usize],
+                    x => x as char,
+                })
+                .collect::<String>()
+                .into_bytes(),
+            Charset::Ascii if bytes.is_ascii() => bytes,
+            Charset::Ascii => bytes
+                .iter()
+                .map(|&x| if x.is_ascii() { x as char } else { '\u{FFFD}' })
+                .collect::<String>()
+                .into_bytes(),
+        }
+    }
+}
+
+/// The maximum absolute value of the hours part of a MySql TIME value.
+pub const TIME_MAX_HOUR: u32 = 838;
+
+/// Treatment of TIME values outside of the `[-838:59:59, 838:59:59]` range
+/// (see [`BinlogValueOptions::with_time_out_of_range`]).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TimeOutOfRange {
+    /// Emit an `InvalidData` error (the default).
+    #[default]
+    Error,
+    /// Clamp the value to the nearest boundary of the valid range.
+    Clamp,
+}
+
+/// Treatment of unsigned BIGINT values above `i64::MAX`
+/// (see [`BinlogValueOptions::with_uint_overflow`]).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UIntOverflow {
+    /// Decode the value as `Value::UInt` (the default).
+    #[default]
+    Keep,
+    /// Emit an `InvalidData` error.
+    Error,
+    /// Saturate the value to `i64::MAX`.
+    Saturate,
+}
+
+/// Options for [`BinlogValue::deserialize_with_options`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BinlogValueOptions {
+    time_out_of_range: TimeOutOfRange,
+    split_time_days: bool,
+    uint_overflow: UIntOverflow,
+}
+
+impl BinlogValueOptions {
+    /// Creates default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Defines the treatment of out-of-range TIME values (defaults to [`TimeOutOfRange::Error`]).
+    ///
+    /// Such values are only possible in corrupted data since MySql clamps TIME values
+    /// to the `[-838:59:59, 838:59:59]` range.
+    pub fn with_time_out_of_range(mut self, time_out_of_range: TimeOutOfRange) -> Self {
+        self.time_out_of_range = time_out_of_range;
+        self
+    }
+
+    /// Returns the treatment of out-of-range TIME values.
+    pub fn time_out_of_range(&self) -> TimeOutOfRange {
+        self.time_out_of_range
+    }
+
+    /// Defines whether hours ≥ 24 of a `MYSQL_TYPE_TIME` or `MYSQL_TYPE_TIME2` value
+    /// should be split into the days part of `Value::Time` (defaults to `false`).
+    ///
+    /// I.e. `50:00:00` will be decoded as `Time(false, 2, 2, 0, 0, 0)`
+    /// instead of `Time(false, 0, 50, 0, 0, 0)`.
+    pub fn with_split_time_days(mut self, split_time_days: bool) -> Self {
+        self.split_time_days = split_time_days;
+        self
+    }
+
+    /// Returns `true` if hours ≥ 24 of a TIME value are split into the days part.
+    pub fn split_time_days(&self) -> bool {
+        self.split_time_days
+    }
+
+    /// Defines the treatment of unsigned BIGINT values above `i64::MAX`
+    /// (defaults to [`UIntOverflow::Keep`]).
+    ///
+    /// Values of any other integer column (TINYINT, SMALLINT, MEDIUMINT, INT and BIGINT,
+    /// either signed or unsigned) are always decoded as `Value::Int`, so use anything but
+    /// [`UIntOverflow::Keep`] to get `Value::Int` for every integer value.
+    pub fn with_uint_overflow(mut self, uint_overflow: UIntOverflow) -> Self {
+        self.uint_overflow = uint_overflow;
+        self
+    }
+
+    /// Returns the treatment of unsigned BIGINT values above `i64::MAX`.
+    pub fn uint_overflow(&self) -> UIntOverflow {
+        self.uint_overflow
+    }
+}
+
+/// Deserialization context of a [`BinlogValue`].
+///
+/// Could be converted from the `(col_type, col_meta, is_unsigned, is_partial)` tuple.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BinlogValueCtx<'a> {
+    col_type: ColumnType,
+    col_meta: &'a [u8],
+    is_unsigned: bool,
+    is_partial: bool,
+}
+
+impl<'a> BinlogValueCtx<'a> {
+    /// Creates a context for a signed non-partial value of the given column.
+    pub const fn new(col_type: ColumnType, col_meta: &'a [u8]) -> Self {
+        Self {
+            col_type,
+            col_meta,
+            is_unsigned: false,
+            is_partial: false,
+        }
+    }
+
+    /// Defines whether the column is an unsigned numeric column (defaults to `false`).
+    pub fn with_unsigned(mut self, is_unsigned: bool) -> Self {
+        self.is_unsigned = is_unsigned;
+        self
+    }
+
+    /// Defines whether the value is a partial JSON update (defaults to `false`).
+    pub fn with_partial(mut self, is_partial: bool) -> Self {
+        self.is_partial = is_partial;
+        self
+    }
+
+    /// Returns the column type.
+    pub fn col_type(&self) -> ColumnType {
+        self.col_type
+    }
+
+    /// Returns the column metadata.
+    pub fn col_meta(&self) -> &'a [u8] {
+        self.col_meta
+    }
+
+    /// Returns `true` if the column is an unsigned numeric column.
+    pub fn is_unsigned(&self) -> bool {
+        self.is_unsigned
+    }
+
+    /// Returns `true` if the value is a partial JSON update.
+    pub fn is_partial(&self) -> bool {
+        self.is_partial
+    }
+}
+
+impl<'a> From<(ColumnType, &'a [u8], bool, bool)> for BinlogValueCtx<'a> {
+    fn from(
+        (col_type, col_meta, is_unsigned, is_partial): (ColumnType, &'a [u8], bool, bool),
+    ) -> Self {
+        Self {
+            col_type,
+            col_meta,
+            is_unsigned,
+            is_partial,
+        }
+    }
+}
+
+/// Returns every column type that `BinlogValue::deserialize` can handle.
+pub fn supported_binlog_column_types() -> &'static [ColumnType] {
+    use ColumnType::*;
+
+    &[
+        MYSQL_TYPE_TINY,
+        MYSQL_TYPE_SHORT,
+        MYSQL_TYPE_LONG,
+        MYSQL_TYPE_FLOAT,
+        MYSQL_TYPE_DOUBLE,
+        MYSQL_TYPE_TIMESTAMP,
+        MYSQL_TYPE_LONGLONG,
+        MYSQL_TYPE_INT24,
+        MYSQL_TYPE_TIME,
+        MYSQL_TYPE_DATETIME,
+        MYSQL_TYPE_YEAR,
+        MYSQL_TYPE_NEWDATE,
+        MYSQL_TYPE_VARCHAR,
+        MYSQL_TYPE_BIT,
+        MYSQL_TYPE_TIMESTAMP2,
+        MYSQL_TYPE_DATETIME2,
+        MYSQL_TYPE_TIME2,
+        MYSQL_TYPE_TYPED_ARRAY,
+        MYSQL_TYPE_JSON,
+        MYSQL_TYPE_NEWDECIMAL,
+        MYSQL_TYPE_ENUM,
+        MYSQL_TYPE_SET,
+        MYSQL_TYPE_TINY_BLOB,
+        MYSQL_TYPE_MEDIUM_BLOB,
+        MYSQL_TYPE_LONG_BLOB,
+        MYSQL_TYPE_BLOB,
+        MYSQL_TYPE_VAR_STRING,
+        MYSQL_TYPE_STRING,
+        MYSQL_TYPE_GEOMETRY,
+    ]
+}
+
+/// Splits values of a row image into per-column raw byte slices without interpreting them.
+///
+/// *   `body` – values of the row image (i.e. a row image without the null bitmap);
+/// *   `col_types` and `metas` – types and metadata of the row image columns;
+/// *   `null_bitmap` – bit is set if the corresponding column is NULL.
+///
+/// Returns `None` for NULL columns. Bytes after the last value are ignored,
+/// so that the sum of the slice lengths is the length of the row image values.
+pub fn split_row_columns<'a>(
+    body: &'a [u8],
+    col_types: &[ColumnType],
+    metas: &[&[u8]],
+    null_bitmap: &BitSlice<u8>,
+) -> io::Result<Vec<Option<&'a [u8]>>> {
+    if col_types.len() != metas.len() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "number of column types and metadata differs",
+        ));
+    }
+
+    let mut offset = 0;
+    let mut columns = Vec::with_capacity(col_types.len());
+    for (i, (col_type, col_meta)) in col_types.iter().zip(metas).enumerate() {
+        if null_bitmap.get(i).as_deref().copied().unwrap_or(false) {
+            columns.push(None);
+            continue;
+        }
+        let rest = &body[offset..];
+        let len = value_len(*col_type, col_meta, rest)?;
+        let value = rest
+            .get(..len)
+            .ok_or_else(|| unexpected_buf_eof_at(offset, rest.len()))?;
+        columns.push(Some(value));
+        offset += len;
+    }
+
+    Ok(columns)
+}
+
+/// Returns the on-wire length of a value (including its length prefix) without consuming it.
+///
+/// `buf` must start with the value – length prefixes of BLOB, VARCHAR, CHAR and JSON values
+/// are peeked from it, while lengths of numeric and temporal values are computed from
+/// the metadata (so `buf` isn't required to hold the whole value).
+///
+/// Signedness never affects the width of a value, so the `is_unsigned` argument
+/// is ignored (it is accepted for symmetry with the [`BinlogValue`] deserialization context).
+pub fn binlog_value_len(
+    col_type: ColumnType,
+    col_meta: &[u8],
+    _is_unsigned: bool,
+    buf: &ParseBuf<'_>,
+) -> io::Result<usize> {
+    value_len(col_type, col_meta, buf.0)
+}
+
+/// Estimates the length of row image values given column types and metadata.
+///
+/// The result is the total length of fixed-length values plus a lower bound
+/// for variable-length values (i.e. the size of their length prefixes).
+/// The null bitmap isn't included.
+///
+/// Returns `None` if some column type is unsupported, some metadata is malformed,
+/// or if the number of types and metadata differs.
+pub fn estimate_row_bytes(col_types: &[ColumnType], metas: &[&[u8]]) -> Option<usize> {
+    use ColumnType::*;
+
+    if col_types.len() != metas.len() {
+        return None;
+    }
+
+    let mut total = 0;
+    for (col_type, col_meta) in col_types.iter().zip(metas) {
+        total += match col_type {
+            MYSQL_TYPE_JSON | MYSQL_TYPE_TYPED_ARRAY => 4,
+            MYSQL_TYPE_TINY_BLOB
+            | MYSQL_TYPE_MEDIUM_BLOB
+            | MYSQL_TYPE_LONG_BLOB
+            | MYSQL_TYPE_BLOB
+            | MYSQL_TYPE_GEOMETRY => match col_meta.first()? {
+                x @ 1..=4 => *x as usize,
+                _ => return None,
+            },
+            MYSQL_TYPE_VARCHAR => {
+                let type_len = *col_meta.first()? as usize | (*col_meta.get(1)? as usize) << 8;
+                if type_len < 256 {
+                    1
+                } else {
+                    2
+                }
+            }
+            MYSQL_TYPE_VAR_STRING => {
+                if var_string_max_len(col_meta) < 256 {
+                    1
+                } else {
+                    2
+                }
+            }
+            MYSQL_TYPE_STRING if col_meta.len() >= 2 => {
+                if string_max_len(col_meta) < 256 {
+                    1
+                } else {
+                    2
+                }
+            }
+            _ => fixed_value_len(*col_type, col_meta)?,
+        };
+    }
+
+    Some(total)
+}
+
+/// `(column_index, value, (start, len))` triple yielded by [`RowImage::next_with_span`].
+pub type SpannedValue<'a> = (usize, BinlogValue<'a>, (usize, usize));
+
+/// Lazy iterator over values of a row image.
+///
+/// Yields `(column_index, value)` pairs (NULL columns are yielded as `Value::NULL`).
+/// Use [`RowImage::skip_column`] to step over a column without decoding it.
+/// Use [`RowImage::next_with_span`] to also get byte spans of values.
+/// Iteration stops after the first error.
+#[derive(Debug, Clone)]
+pub struct RowImage<'a> {
+    body: &'a [u8],
+    buf: ParseBuf<'a>,
+    col_types: &'a [ColumnType],
+    metas: &'a [&'a [u8]],
+    null_bitmap: &'a BitSlice<u8>,
+    unsigned: Option<&'a BitSlice<u8>>,
+    index: usize,
+}
+
+impl<'a> RowImage<'a> {
+    /// Creates a new iterator (see [`split_row_columns`] for the meaning of arguments).
+    ///
+    /// Numeric columns are decoded as signed (see [`RowImage::with_unsigned`]).
+    pub fn new(
+        body: &'a [u8],
+        col_types: &'a [ColumnType],
+        metas: &'a [&'a [u8]],
+        null_bitmap: &'a BitSlice<u8>,
+    ) -> io::Result<Self> {
+        if col_types.len() != metas.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "number of column types and metadata differs",
+            ));
+        }
+
+        Ok(Self {
+            body,
+            buf: ParseBuf(body),
+            col_types,
+            metas,
+            null_bitmap,
+            unsigned: None,
+            index: 0,
+        })
+    }
+
+    /// Defines signedness of numeric columns (bit is set if the corresponding column
+    /// is unsigned).
+    pub fn with_unsigned(mut self, unsigned: &'a BitSlice<u8>) -> Self {
+        self.unsigned = Some(unsigned);
+        self
+    }
+
+    /// Returns the index of the next column.
+    pub fn column_index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns bytes of the row image that aren't consumed yet.
+    pub fn remaining(&self) -> &'a [u8] {
+        self.buf.0
+    }
+
+    /// Skips the next column consuming exactly its on-wire length.
+    ///
+    /// Returns the index of the skipped column or `None` if there are no more columns.
+    pub fn skip_column(&mut self) -> io::Result<Option<usize>> {
+        let index = self.index;
+        let col_type = match self.col_types.get(index) {
+            Some(col_type) => *col_type,
+            None => return Ok(None),
+        };
+        if !self.is_null(index) {
+            let len = value_len(col_type, self.metas[index], self.buf.0)?;
+            self.buf
+                .checked_eat(len)
+                .ok_or_else(|| self.unexpected_eof())?;
+        }
+        self.index += 1;
+        Ok(Some(index))
+    }
+
+    /// Same as `next`, but also yields the `(start, len)` byte span of the value
+    /// within the row image body (spans of NULL columns are empty).
+    ///
+    /// Spans allow to overwrite a single value in place without re-serializing the whole row.
+    pub fn next_with_span(&mut self) -> Option<io::Result<SpannedValue<'a>>> {
+        let start = self.offset();
+        let item = self.next()?;
+        Some(item.map(|(index, value)| (index, value, (start, self.offset() - start))))
+    }
+
+    /// Returns the offset of the next value within the row image body.
+    fn offset(&self) -> usize {
+        self.body.len() - self.buf.remaining()
+    }
+
+    /// Returns an `UnexpectedEof` error reporting the current offset within the row image.
+    fn unexpected_eof(&self) -> io::Error {
+        unexpected_buf_eof_at(self.offset(), self.buf.remaining())
+    }
+
+    fn is_null(&self, index: usize) -> bool {
+        self.null_bitmap
+            .get(index)
+            .as_deref()
+            .copied()
+            .unwrap_or(false)
+    }
+
+    fn read_next(&mut self) -> io::Result<BinlogValue<'a>> {
+        let index = self.index;
+        if self.is_null(index) {
+            return Ok(BinlogValue::Value(NULL));
+        }
+        let is_unsigned = self
+            .unsigned
+            .and_then(|x| x.get(index).as_deref().copied())
+            .unwrap_or(false);
+        let mut buf = self.buf;
+        let ctx = BinlogValueCtx::new(self.col_types[index], self.metas[index]);
+        match buf.parse(ctx.with_unsigned(is_unsigned)) {
+            Ok(value) => {
+                self.buf = buf;
+                Ok(value)
+            }
+            Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => Err(self.unexpected_eof()),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+impl<'a> Iterator for RowImage<'a> {
+    type Item = io::Result<(usize, BinlogValue<'a>)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let index = self.index;
+        if index >= self.col_types.len() {
+            return None;
+        }
+        match self.read_next() {
+            Ok(value) => {
+                self.index += 1;
+                Some(Ok((index, value)))
+            }
+            Err(err) => {
+                self.index = self.col_types.len();
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+/// Raw binlog value, that isn't interpreted yet (see [`BinlogValue::scan`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RawBinlogValue<'a> {
+    col_type: ColumnType,
+    col_meta: &'a [u8],
+    is_unsigned: bool,
+    is_partial: bool,
+    data: &'a [u8],
+}
+
+impl<'a> RawBinlogValue<'a> {
+    /// Returns the column type.
+    pub fn column_type(&self) -> ColumnType {
+        self.col_type
+    }
+
+    /// Returns the column metadata.
+    pub fn column_meta(&self) -> &'a [u8] {
+        self.col_meta
+    }
+
+    /// Returns the raw value bytes (including length prefixes).
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// Interprets the value.
+    pub fn to_value(&self) -> io::Result<BinlogValue<'a>> {
+        self.to_value_with_options(BinlogValueOptions::default())
+    }
+
+    /// Interprets the value using the given options.
+    pub fn to_value_with_options(
+        &self,
+        options: BinlogValueOptions,
+    ) -> io::Result<BinlogValue<'a>> {
+        let ctx = BinlogValueCtx::new(self.col_type, self.col_meta)
+            .with_unsigned(self.is_unsigned)
+            .with_partial(self.is_partial);
+        BinlogValue::deserialize_with_options(ctx, options, &mut ParseBuf(self.data))
+    }
+
+    /// Returns bits of a `MYSQL_TYPE_BIT` value (see [`BinlogValue::as_bit_vec`]).
+    ///
+    /// Returns `None` if this isn't a BIT value.
+    pub fn as_bit_vec(&self) -> Option<Vec<bool>> {
+        if self.col_type != ColumnType::MYSQL_TYPE_BIT {
+            return None;
+        }
+        bit_vec(self.data, self.col_meta)
+    }
+
+    /// Returns the packed integer representation of a TIME2, DATETIME2 or TIMESTAMP2 value
+    /// along with the column type and the fractional seconds precision (`dec`).
+    ///
+    /// For TIME2 and DATETIME2 it's the MySql's packed representation
+    /// (see [`my_time_packed_from_binary`] and [`my_datetime_packed_from_binary`]),
+    /// for TIMESTAMP2 it's seconds and microseconds packed into a single integer
+    /// (see [`my_packed_time_make`]). Such value could be written back byte-exact
+    /// using [`my_time_packed_to_binary`], [`my_datetime_packed_to_binary`]
+    /// or [`my_timestamp_to_binary`].
+    ///
+    /// Note that this accessor is not available on a decoded [`BinlogValue`], because
+    /// it retains neither the packed integer nor the column type and precision
+    /// (e.g. TIMESTAMP2 is decoded as a string). Use [`BinlogValue::scan`] to get
+    /// the raw value of a column instead.
+    ///
+    /// Returns `None` for other column types or if the value is malformed.
+    pub fn packed_temporal_raw(&self) -> Option<(ColumnType, i64, u8)> {
+        use ColumnType::*;
+
+        let dec = *self.col_meta.first()?;
+        let packed = match self.col_type {
+            MYSQL_TYPE_TIME2 => my_time_packed_from_binary(self.data, dec as u32).ok()?,
+            MYSQL_TYPE_DATETIME2 => my_datetime_packed_from_binary(self.data, dec as u32).ok()?,
+            MYSQL_TYPE_TIMESTAMP2 => {
+                let (sec, usec) = my_timestamp_from_binary(self.data, dec).ok()?;
+                my_packed_time_make(sec as u32 as i64, usec as i64)
+            }
+            _ => return None,
+        };
+        Some((self.col_type, packed, dec))
+    }
+}
+
+/// Returns exactly `nbits` bits of a BIT value, the most significant bit first.
+fn bit_vec(bytes: &[u8], col_meta: &[u8]) -> Option<Vec<bool>> {
+    let nbits = *col_meta.get(1)? as usize * 8 + *col_meta.first()? as usize;
+    if bytes.len() != (nbits + 7) / 8 {
+        return None;
+    }
+    let padding = bytes.len() * 8 - nbits;
+    let bits = (padding..(bytes.len() * 8))
+        .map(|i| bytes[i / 8] & (0x80 >> (i % 8)) != 0)
+        .collect();
+    Some(bits)
+}
+
+/// Kind of a [`BinlogValue`] (see [`BinlogValue::kind`]).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ValueKind {
+    /// `BinlogValue::Value(Value::NULL)`.
+    Null,
+    /// `BinlogValue::Value(Value::Bytes(..))`.
+    Bytes,
+    /// `BinlogValue::Value(Value::Int(..))`.
+    Int,
+    /// `BinlogValue::Value(Value::UInt(..))`.
+    UInt,
+    /// `BinlogValue::Value(Value::Float(..))`.
+    Float,
+    /// `BinlogValue::Value(Value::Double(..))`.
+    Double,
+    /// `BinlogValue::Value(Value::Date(..))`.
+    Date,
+    /// `BinlogValue::Value(Value::Time(..))`.
+    Time,
+    /// `BinlogValue::Decimal(..)`.
+    Decimal,
+    /// `BinlogValue::Jsonb(..)`.
+    Jsonb,
+    /// `BinlogValue::JsonDiff(..)`.
+    JsonDiff,
+    /// `BinlogValue::TypedArray(..)`.
+    TypedArray,
+    /// `BinlogValue::Error(..)`.
+    Error,
+}
+
+/// Temporal value converted to `chrono` (see [`BinlogValue::to_chrono`]).
+#[cfg(feature = "chrono")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ChronoTemporal {
+    /// DATE value.
+    Date(chrono::NaiveDate),
+    /// TIME value (might be negative and might exceed 24 hours).
+    Time(chrono::Duration),
+    /// DATETIME value, or TIMESTAMP value as a UTC date-time.
+    DateTime(chrono::NaiveDateTime),
+}
+
 /// Value of a binlog event.
 #[derive(Debug, Clone, PartialEq)]
 pub enum BinlogValue<'a> {
     /// MySql value.
     Value(Value),
+    /// DECIMAL value (see `MYSQL_TYPE_NEWDECIMAL`).
+    Decimal(decimal::Decimal),
     /// JSONB value.
     Jsonb(jsonb::Value<'a>),
     /// Value of a partial JSON modification event.
     JsonDiff(Vec<JsonDiff<'a>>),
+    /// Elements of a typed array (see `MYSQL_TYPE_TYPED_ARRAY`).
+    TypedArray(Vec<BinlogValue<'a>>),
+    /// Placeholder for a value that failed to parse (see [`BinlogValue::deserialize_lenient`]).
+    Error(BinlogValueError),
+}
+
+impl BinlogValue<'static> {
+    /// NULL value (i.e. `BinlogValue::Value(Value::NULL)`).
+    pub const NULL: Self = BinlogValue::Value(NULL);
+
+    /// Creates an integer value.
+    ///
+    /// ```
+    /// # use mysql_common::{binlog::value::BinlogValue, value::Value};
+    /// assert_eq!(BinlogValue::int(-42), BinlogValue::Value(Value::Int(-42)));
+    /// ```
+    pub fn int(x: i64) -> Self {
+        BinlogValue::Value(Int(x))
+    }
+
+    /// Creates a bytes value.
+    ///
+    /// ```
+    /// # use mysql_common::{binlog::value::BinlogValue, value::Value};
+    /// assert_eq!(
+    ///     BinlogValue::bytes("foo"),
+    ///     BinlogValue::Value(Value::Bytes(b"foo".to_vec())),
+    /// );
+    /// ```
+    pub fn bytes(x: impl Into<Vec<u8>>) -> Self {
+        BinlogValue::Value(Bytes(x.into()))
+    }
+
+    /// Creates a date/datetime value.
+    ///
+    /// ```
+    /// # use mysql_common::{binlog::value::BinlogValue, value::Value};
+    /// assert_eq!(
+    ///     BinlogValue::datetime(2021, 12, 31, 23, 59, 59, 500_000),
+    ///     BinlogValue::Value(Value::Date(2021, 12, 31, 23, 59, 59, 500_000)),
+    /// );
+    /// ```
+    pub fn datetime(
+        year: u16,
+        month: u8,
+        day: u8,
+        hour: u8,
+        minute: u8,
+        second: u8,
+        micros: u32,
+    ) -> Self {
+        BinlogValue::Value(Date(year, month, day, hour, minute, second, micros))
+    }
+
+    /// Creates a JSONB value from the given JSON (see `jsonb::Value::from`).
+    ///
+    /// ```
+    /// # use std::convert::TryFrom;
+    /// # use mysql_common::binlog::value::BinlogValue;
+    /// let value = BinlogValue::json(serde_json::json!({"foo": [1, 2]}));
+    /// assert_eq!(value.json_len(), Some(1));
+    /// match value {
+    ///     BinlogValue::Jsonb(x) => assert_eq!(
+    ///         serde_json::Value::try_from(x).unwrap(),
+    ///         serde_json::json!({"foo": [1, 2]}),
+    ///     ),
+    ///     _ => unreachable!(),
+    /// }
+    /// ```
+    pub fn json(x: serde_json::Value) -> Self {
+        BinlogValue::Jsonb(x.into())
+    }
 }
 
 impl<'a> BinlogValue<'a> {
+    /// Returns the kind of this value.
+    pub fn kind(&self) -> ValueKind {
+        match self {
+            BinlogValue::Value(NULL) => ValueKind::Null,
+            BinlogValue::Value(Bytes(_)) => ValueKind::Bytes,
+            BinlogValue::Value(Int(_)) => ValueKind::Int,
+            BinlogValue::Value(UInt(_)) => ValueKind::UInt,
+            BinlogValue::Value(Float(_)) => ValueKind::Float,
+            BinlogValue::Value(Double(_)) => ValueKind::Double,
+            BinlogValue::Value(Date(..)) => ValueKind::Date,
+            BinlogValue::Value(Time(..)) => ValueKind::Time,
+            BinlogValue::Decimal(_) => ValueKind::Decimal,
+            BinlogValue::Jsonb(_) => ValueKind::Jsonb,
+            BinlogValue::JsonDiff(_) => ValueKind::JsonDiff,
+            BinlogValue::TypedArray(_) => ValueKind::TypedArray,
+            BinlogValue::Error(_) => ValueKind::Error,
+        }
+    }
+
+    /// Returns `true` if this value is NULL.
+    pub fn is_null(&self) -> bool {
+        matches!(self, BinlogValue::Value(NULL))
+    }
+
     /// Returns a `'static` version of `self`.
     pub fn into_owned(self) -> BinlogValue<'static> {
         match self {
             BinlogValue::Value(x) => BinlogValue::Value(x),
+            BinlogValue::Decimal(x) => BinlogValue::Decimal(x),
             BinlogValue::Jsonb(x) => BinlogValue::Jsonb(x.into_owned()),
             BinlogValue::JsonDiff(x) => {
                 BinlogValue::JsonDiff(x.into_iter().map(|x| x.into_owned()).collect())
             }
+            BinlogValue::TypedArray(x) => {
+                BinlogValue::TypedArray(x.into_iter().map(|x| x.into_owned()).collect())
+            }
+            BinlogValue::Error(x) => BinlogValue::Error(x),
         }
     }
-}
 
-impl<'de> MyDeserialize<'de> for BinlogValue<'de> {
-    const SIZE: Option<usize> = None;
-    /// <col_type, col_meta, is_unsigned, is_partial>
-    type Ctx = (ColumnType, &'de [u8], bool, bool);
+    /// Returns true if `self` is a string value of a `column` having the binary charset
+    /// (e.g. VARBINARY or BLOB as opposed to VARCHAR or TEXT).
+    ///
+    /// Columns of a [`super::row::BinlogRow`] will only carry the charset if the table map event
+    /// contains the charset optional metadata (see `binlog_row_metadata=FULL`).
+    pub fn is_binary(&self, column: &Column) -> bool {
+        matches!(self, BinlogValue::Value(Bytes(_)))
+            && column.column_type().is_character_type()
+            && column.character_set() == BINARY_CHARSET
+    }
 
-    fn deserialize(
-        (mut col_type, col_meta, is_unsigned, is_partial): Self::Ctx,
-        buf: &mut ParseBuf<'de>,
-    ) -> io::Result<Self> {
-        use ColumnType::*;
+    /// Returns an integer value as `i64`.
+    ///
+    /// Integer columns as well as TIMESTAMP values (seconds since the epoch) are decoded
+    /// as integers. Returns `None` for non-integer values and for unsigned values
+    /// above `i64::MAX`.
+    pub fn as_i64(&self) -> Option<i64> {
+        match *self {
+            BinlogValue::Value(Int(x)) => Some(x),
+            BinlogValue::Value(UInt(x)) => i64::try_from(x).ok(),
+            _ => None,
+        }
+    }
 
-        let mut length = 0_usize;
+    /// Returns an integer value as `u64`.
+    ///
+    /// Returns `None` for non-integer values and for negative values.
+    pub fn as_u64(&self) -> Option<u64> {
+        match *self {
+            BinlogValue::Value(Int(x)) => u64::try_from(x).ok(),
+            BinlogValue::Value(UInt(x)) => Some(x),
+            _ => None,
+        }
+    }
 
-        if col_type == MYSQL_TYPE_TYPED_ARRAY {
-            let type_byte = col_meta[0];
-            col_type = ColumnType::try_from(type_byte).unwrap_or(col_type);
+    /// Returns a numeric value as `f64`.
+    ///
+    /// Integer and decimal values are converted (possibly losing precision,
+    /// see [`BinlogValue::decimal_as_f64`]). Returns `None` for non-numeric values.
+    pub fn as_f64(&self) -> Option<f64> {
+        match *self {
+            BinlogValue::Value(Int(x)) => Some(x as f64),
+            BinlogValue::Value(UInt(x)) => Some(x as f64),
+            BinlogValue::Value(Float(x)) => Some(x as f64),
+            BinlogValue::Value(Double(x)) => Some(x),
+            BinlogValue::Decimal(_) => self.decimal_as_f64(),
+            _ => None,
         }
+    }
 
-        if col_type == MYSQL_TYPE_STRING {
-            if col_meta[0] >= 1 {
-                let byte0 = col_meta[0] as usize;
-                let byte1 = col_meta[1] as usize;
+    /// Returns bytes of a bytes value.
+    ///
+    /// String, BLOB, BIT, SET, YEAR and TIMESTAMP2 values are decoded as bytes.
+    /// Returns `None` for any other value.
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            BinlogValue::Value(Bytes(x)) => Some(x),
+            _ => None,
+        }
+    }
 
-                if (byte0 & 0x30) != 0x30 {
-                    // a long CHAR() field: see #37426
-                    length = byte1 | (((byte0 & 0x30) ^ 0x30) << 4);
-                } else {
-                    length = byte1;
-                }
-            } else {
-                length = (ParseBuf(col_meta)).eat_u16_le() as usize;
+    /// Returns a bytes value or a decimal value as a string.
+    ///
+    /// Bytes values (see [`BinlogValue::as_bytes`]) are borrowed, while decimal values
+    /// are formatted the way MySql does it (see [`BinlogValue::as_decimal_string`]).
+    ///
+    /// Returns `None` for other values or if bytes aren't a valid UTF-8.
+    pub fn as_str(&self) -> Option<Cow<'_, str>> {
+        match self {
+            BinlogValue::Value(Bytes(x)) => std::str::from_utf8(x).ok().map(Cow::Borrowed),
+            BinlogValue::Decimal(x) => Some(Cow::Owned(x.to_string())),
+            _ => None,
+        }
+    }
+
+    /// Returns up to `n` first bytes of a bytes value without copying it
+    /// (e.g. to preview a large BLOB in a log line).
+    ///
+    /// Returns the whole value if it is shorter than `n`, and `None` if `self`
+    /// isn't a bytes value.
+    pub fn blob_prefix(&self, n: usize) -> Option<&[u8]> {
+        match self {
+            BinlogValue::Value(Bytes(x)) => Some(&x[..n.min(x.len())]),
+            _ => None,
+        }
+    }
+
+    /// Returns true if `self` is a JSON value holding the JSON `null` literal.
+    ///
+    /// Note that this is distinct from the SQL `NULL` (i.e. `BinlogValue::Value(Value::NULL)`).
+    pub fn is_json_null(&self) -> bool {
+        matches!(self, BinlogValue::Jsonb(x) if x.is_null())
+    }
+
+    /// Returns the value of a JSON value holding the JSON `true` or `false` literal.
+    ///
+    /// Returns `None` for any other value.
+    pub fn as_json_bool(&self) -> Option<bool> {
+        match self {
+            BinlogValue::Jsonb(jsonb::Value::Bool(x)) => Some(*x),
+            _ => None,
+        }
+    }
+
+    /// Returns the number of elements if `self` is a JSONB array or object.
+    ///
+    /// Returns `None` for scalar JSONB values and for non-JSONB values.
+    pub fn json_len(&self) -> Option<usize> {
+        match self {
+            BinlogValue::Jsonb(x) => x.len(),
+            _ => None,
+        }
+    }
+
+    /// Returns bits of a BIT value of the declared width, the most significant bit first
+    /// (i.e. `b'0000000101'` of a `BIT(10)` column gives exactly 10 booleans).
+    ///
+    /// The width is recomputed from the column metadata `col_meta`.
+    /// Returns `None` if `self` isn't a bytes value of the corresponding length.
+    pub fn as_bit_vec(&self, col_meta: &[u8]) -> Option<Vec<bool>> {
+        match self {
+            BinlogValue::Value(Bytes(bytes)) => bit_vec(bytes, col_meta),
+            _ => None,
+        }
+    }
+
+    /// Resolves a decoded ENUM value (a 1-based ordinal) to its label given the ordered
+    /// list of members (see [`crate::binlog::events::EnumStrValues`]).
+    ///
+    /// The `0` ordinal stands for the empty string stored for invalid values (as in MySQL),
+    /// so it is resolved to an empty bytes value.
+    ///
+    /// Returns `None` if `self` isn't an integer or if the ordinal is out of bounds.
+    pub fn resolve_enum(&self, labels: &[&str]) -> Option<BinlogValue<'static>> {
+        match *self {
+            BinlogValue::Value(Int(0)) => Some(BinlogValue::bytes("")),
+            BinlogValue::Value(Int(x)) if x > 0 => labels
+                .get(x as usize - 1)
+                .map(|label| BinlogValue::bytes(*label)),
+            _ => None,
+        }
+    }
+
+    /// Returns the 1-based definition index of a decoded ENUM value, where `0` stands for
+    /// the empty string stored for invalid values.
+    ///
+    /// Note that [`BinlogValue::resolve_enum`] doesn't modify `self`, so the index stays
+    /// available after the name is resolved. Returns `None` if `self` isn't an ENUM index
+    /// (ENUM has at most 65535 members).
+    pub fn enum_index(&self) -> Option<u16> {
+        match *self {
+            BinlogValue::Value(Int(x)) => u16::try_from(x).ok(),
+            _ => None,
+        }
+    }
+
+    /// Decodes a SET value (a little-endian bitmask) into the list of its members given
+    /// the ordered list of members (see [`crate::binlog::events::SetStrValues`]).
+    ///
+    /// Returns `None` if `self` isn't a bytes value or if it has a bit set
+    /// for a non-existent member.
+    pub fn decode_set<'b>(&self, members: &[&'b str]) -> Option<Vec<&'b str>> {
+        match self {
+            BinlogValue::Value(Bytes(bytes)) => decode_set(bytes, members),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` if both `self` and `other` are structurally equal JSONB values
+    /// (see [`jsonb::Value::structural_eq`]).
+    pub fn json_eq(&self, other: &BinlogValue<'_>) -> bool {
+        match (self, other) {
+            (BinlogValue::Jsonb(x), BinlogValue::Jsonb(y)) => x.structural_eq(y),
+            _ => false,
+        }
+    }
+
+    /// Returns the unscaled integer representation of a decimal value and its scale,
+    /// i.e. `123.45` will be returned as `(12345, 2)`.
+    ///
+    /// Returns `None` if `self` isn't a decimal value (see `MYSQL_TYPE_NEWDECIMAL`)
+    /// or if the unscaled value doesn't fit into `i128`.
+    pub fn decimal_as_scaled_i128(&self) -> Option<(i128, usize)> {
+        let dec = self.as_decimal()?;
+        Some((dec.to_scaled_i128()?, dec.scale()))
+    }
+
+    /// Returns the decimal held by `self`.
+    ///
+    /// Decimal strings (i.e. decimals decoded by previous versions) are also accepted.
+    fn as_decimal(&self) -> Option<Cow<'_, decimal::Decimal>> {
+        match self {
+            BinlogValue::Decimal(dec) => Some(Cow::Borrowed(dec)),
+            BinlogValue::Value(Bytes(bytes)) => {
+                decimal::Decimal::parse_bytes(bytes).ok().map(Cow::Owned)
             }
+            _ => None,
         }
+    }
 
-        match col_type {
-            MYSQL_TYPE_TINY | MYSQL_TYPE_SHORT | MYSQL_TYPE_LONG | MYSQL_TYPE_LONGLONG
-            | MYSQL_TYPE_FLOAT | MYSQL_TYPE_DOUBLE => {
-                let mut flags = ColumnFlags::empty();
-                flags.set(ColumnFlags::UNSIGNED_FLAG, is_unsigned);
-                Value::deserialize_bin((col_type, flags), &mut *buf).map(BinlogValue::Value)
+    /// Formats a decimal value (see `MYSQL_TYPE_NEWDECIMAL`) the way MySql does it,
+    /// i.e. it returns the value that was decoded as `Bytes` by previous versions.
+    ///
+    /// Returns `None` if `self` isn't a decimal value.
+    pub fn as_decimal_string(&self) -> Option<String> {
+        self.as_decimal().map(|dec| dec.to_string())
+    }
+
+    /// Converts `self` to a `Value` transcoding string values from the given `charset` to UTF-8,
+    /// so that the resulting bytes are safe to stringify.
+    ///
+    /// Non-string values are converted as is (see `TryFrom<BinlogValue> for Value`).
+    pub fn into_value_with_charset(
+        self,
+        charset: Charset,
+    ) -> Result<Value, BinlogValueToValueError> {
+        match self {
+            BinlogValue::Value(Bytes(bytes)) => Ok(Bytes(charset.decode_to_utf8(bytes))),
+            other => Value::try_from(other),
+        }
+    }
+
+    /// Renders any value as a string, converting bytes lossy and never erroring.
+    ///
+    /// Numbers and temporal values are formatted the way MySql does it
+    /// (i.e. `2021-12-31 23:59:59.500000`), SQL NULL is rendered as `NULL`,
+    /// JSONB values are rendered as JSON.
+    pub fn to_string_lossy(&self) -> Cow<'_, str> {
+        match self {
+            BinlogValue::Value(Bytes(bytes)) => String::from_utf8_lossy(bytes),
+            BinlogValue::Value(x @ Date(..)) | BinlogValue::Value(x @ Time(..)) => {
+                // strip quotes
+                let sql = x.as_sql(false);
+                Cow::Owned(sql[1..(sql.len() - 1)].into())
             }
-            MYSQL_TYPE_TIMESTAMP => {
-                let val: RawInt<LeU32> = buf.parse(())?;
-                Ok(BinlogValue::Value(Int(*val as i64)))
+            BinlogValue::Value(x) => Cow::Owned(x.as_sql(false)),
+            BinlogValue::Decimal(x) => Cow::Owned(x.to_string()),
+            BinlogValue::Jsonb(x) => match serde_json::Value::try_from(x.clone()) {
+                Ok(json) => Cow::Owned(json.to_string()),
+                Err(_) => Cow::Owned(format!("{:?}", x)),
+            },
+            BinlogValue::JsonDiff(x) => Cow::Owned(format!("{:?}", x)),
+            BinlogValue::TypedArray(x) => {
+                let elements = x.iter().map(|x| x.to_string_lossy()).collect::<Vec<_>>();
+                Cow::Owned(format!("[{}]", elements.join(", ")))
             }
-            MYSQL_TYPE_INT24 => {
-                if is_unsigned {
-                    let val: RawInt<LeU24> = buf.parse(())?;
+            BinlogValue::Error(x) => Cow::Owned(x.to_string()),
+        }
+    }
+
+    /// Renders a typed array (i.e. a multi-valued index column) as a JSON array
+    /// of its element values.
+    ///
+    /// Integers and floating point numbers are rendered as JSON numbers (non-finite floats
+    /// become `null`), SQL NULL becomes `null` and other elements are rendered as strings
+    /// (see [`BinlogValue::to_string_lossy`]).
+    ///
+    /// Returns `None` if `self` isn't a typed array.
+    pub fn typed_array_to_json(&self) -> Option<serde_json::Value> {
+        fn element_to_json(element: &BinlogValue<'_>) -> serde_json::Value {
+            match *element {
+                BinlogValue::Value(NULL) => serde_json::Value::Null,
+                BinlogValue::Value(Int(x)) => x.into(),
+                BinlogValue::Value(UInt(x)) => x.into(),
+                BinlogValue::Value(Float(x)) => serde_json::Number::from_f64(x as f64)
+                    .map(serde_json::Value::Number)
+                    .unwrap_or(serde_json::Value::Null),
+                BinlogValue::Value(Double(x)) => serde_json::Number::from_f64(x)
+                    .map(serde_json::Value::Number)
+                    .unwrap_or(serde_json::Value::Null),
+                ref other => serde_json::Value::String(other.to_string_lossy().into_owned()),
+            }
+        }
+
+        match self {
+            BinlogValue::TypedArray(elements) => Some(serde_json::Value::Array(
+                elements.iter().map(element_to_json).collect(),
+            )),
+            _ => None,
+        }
+    }
+
+    /// Converts `self` to a JSON value.
+    ///
+    /// *   SQL NULL becomes `null`, integers and floating point numbers become JSON numbers
+    ///     (non-finite floats become `null`);
+    /// *   dates become ISO 8601 strings (i.e. `2021-12-31T23:59:59.500000`), zero dates and
+    ///     times are rendered the way MySql does it (see [`BinlogValue::to_string_lossy`]);
+    /// *   bytes become strings if `is_binary` is `false` and the bytes are valid UTF-8,
+    ///     otherwise they are encoded as base64 strings. Use `is_binary` for BLOB and BINARY
+    ///     columns (see [`BinlogValue::is_binary`]);
+    /// *   JSONB values are converted as is, typed arrays become arrays;
+    /// *   partial JSON updates become arrays of `{ "path", "operation", "value" }` objects
+    ///     (`value` is absent for removals).
+    pub fn to_json(&self, is_binary: bool) -> Result<serde_json::Value, BinlogValueToValueError> {
+        let float = |x: f64| {
+            serde_json::Number::from_f64(x)
+                .map(serde_json::Value::Number)
+                .unwrap_or(serde_json::Value::Null)
+        };
+
+        match self {
+            BinlogValue::Value(NULL) => Ok(serde_json::Value::Null),
+            BinlogValue::Value(Int(x)) => Ok((*x).into()),
+            BinlogValue::Value(UInt(x)) => Ok((*x).into()),
+            BinlogValue::Value(Float(x)) => Ok(float(*x as f64)),
+            BinlogValue::Value(Double(x)) => Ok(float(*x)),
+            BinlogValue::Value(Bytes(bytes)) => match std::str::from_utf8(bytes) {
+                Ok(string) if !is_binary => Ok(string.into()),
+                _ => Ok(base64::encode(bytes).into()),
+            },
+            // TIMESTAMP values aren't decoded as dates, so it's a DATE or a DATETIME value
+            BinlogValue::Value(Date(..)) | BinlogValue::Value(Time(..)) => Ok(self
+                .to_rfc3339(ColumnType::MYSQL_TYPE_DATETIME)
+                .unwrap_or_else(|| self.to_string_lossy().into_owned())
+                .into()),
+            BinlogValue::Decimal(x) => Ok(x.to_string().into()),
+            BinlogValue::Jsonb(x) => Ok(serde_json::Value::try_from(x.clone())?),
+            BinlogValue::JsonDiff(diffs) => {
+                let mut output = Vec::with_capacity(diffs.len());
+                for diff in diffs {
+                    let mut object = serde_json::Map::new();
+                    object.insert("path".into(), diff.path_str().into_owned().into());
+                    object.insert("operation".into(), format!("{:?}", diff.operation()).into());
+                    if let Some(value) = diff.value() {
+                        object.insert("value".into(), value.clone().try_into()?);
+                    }
+                    output.push(serde_json::Value::Object(object));
+                }
+                Ok(serde_json::Value::Array(output))
+            }
+            BinlogValue::TypedArray(elements) => elements
+                .iter()
+                .map(|x| x.to_json(is_binary))
+                .collect::<Result<_, _>>()
+                .map(serde_json::Value::Array),
+            BinlogValue::Error(x) => Err(BinlogValueToValueError::Error(x.clone())),
+        }
+    }
+
+    /// Renders `self` as an SQL literal (see [`Value::as_sql`]), i.e. `'it\'s'`, `42` or `NULL`.
+    ///
+    /// JSONB values and typed arrays are rendered as quoted JSON documents. Partial JSON
+    /// updates and values that failed to parse have no literal form and are rendered as `NULL`.
+    pub fn to_sql_literal(&self) -> String {
+        let json = match self {
+            BinlogValue::Value(x) => return x.as_sql(false),
+            BinlogValue::Decimal(x) => return x.to_string(),
+            BinlogValue::Jsonb(x) => serde_json::Value::try_from(x.clone()).ok(),
+            BinlogValue::TypedArray(_) => self.typed_array_to_json(),
+            BinlogValue::JsonDiff(_) | BinlogValue::Error(_) => None,
+        };
+        match json {
+            Some(json) => Bytes(json.to_string().into_bytes()).as_sql(false),
+            None => NULL.as_sql(false),
+        }
+    }
+
+    /// Returns `true` if `self` is an integer value equal to `v`.
+    pub fn eq_i64(&self, v: i64) -> bool {
+        match *self {
+            BinlogValue::Value(Int(x)) => x == v,
+            BinlogValue::Value(UInt(x)) => v >= 0 && x == v as u64,
+            _ => false,
+        }
+    }
+
+    /// Returns `true` if `self` is a bytes value equal to `v`.
+    pub fn eq_bytes(&self, v: &[u8]) -> bool {
+        matches!(self, BinlogValue::Value(Bytes(x)) if x == v)
+    }
+
+    /// Returns `true` if `self` is a FLOAT or DOUBLE value equal to `v`.
+    ///
+    /// FLOAT values are widened to `f64` before the comparison.
+    pub fn eq_f64(&self, v: f64) -> bool {
+        match *self {
+            BinlogValue::Value(Float(x)) => x as f64 == v,
+            BinlogValue::Value(Double(x)) => x == v,
+            _ => false,
+        }
+    }
+
+    /// Formats a temporal value of a column of the given type as an RFC 3339 date-time,
+    /// i.e. `YYYY-MM-DDTHH:MM:SS.ffffff`.
+    ///
+    /// DATE and DATETIME values are formatted as naive (i.e. zone-less) date-times,
+    /// such as `2021-12-31T23:59:59.500000`. TIMESTAMP values are stored in UTC,
+    /// so they are formatted with the `Z` suffix, such as `2021-12-31T23:59:59.500000Z`.
+    ///
+    /// Returns `None` for other column types, if `self` doesn't match the column type
+    /// and for zero dates (`0000-00-00`).
+    pub fn to_rfc3339(&self, col_type: ColumnType) -> Option<String> {
+        use ColumnType::*;
+
+        match (col_type, self) {
+            (
+                MYSQL_TYPE_DATE | MYSQL_TYPE_NEWDATE | MYSQL_TYPE_DATETIME | MYSQL_TYPE_DATETIME2,
+                BinlogValue::Value(Date(y, m, d, h, i, s, us)),
+            ) => {
+                if *m == 0 || *d == 0 {
+                    return None;
+                }
+                Some(format_rfc3339(*y as i64, *m, *d, *h, *i, *s, *us))
+            }
+            (MYSQL_TYPE_TIMESTAMP | MYSQL_TYPE_TIMESTAMP2, _) => {
+                let (secs, micros) = self.as_timestamp()?;
+                if secs <= 0 {
+                    return None;
+                }
+
+                let days = secs.div_euclid(86_400);
+                let time = secs.rem_euclid(86_400);
+                let (y, m, d) = civil_from_days(days);
+                let mut out = format_rfc3339(
+                    y,
+                    m,
+                    d,
+                    (time / 3600) as u8,
+                    (time % 3600 / 60) as u8,
+                    (time % 60) as u8,
+                    micros,
+                );
+                out.push('Z');
+                Some(out)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns seconds since the Unix epoch and microseconds of a decoded TIMESTAMP
+    /// or TIMESTAMP2 value.
+    ///
+    /// TIMESTAMP values are decoded as integers, and TIMESTAMP2 values are decoded as
+    /// `<seconds>[.<microseconds>]` strings, where microseconds are always zero-padded
+    /// to six digits regardless of the column's fractional seconds precision.
+    ///
+    /// Returns `None` if `self` isn't a number of seconds since the epoch. Note that
+    /// the zero timestamp (`0000-00-00 00:00:00`) is returned as `(0, 0)`.
+    pub fn as_timestamp(&self) -> Option<(i64, u32)> {
+        match self {
+            BinlogValue::Value(Int(secs)) if *secs >= 0 => Some((*secs, 0)),
+            BinlogValue::Value(Bytes(bytes)) => {
+                let string = std::str::from_utf8(bytes).ok()?;
+                let mut parts = string.splitn(2, '.');
+                let secs = parts.next()?;
+                if secs.is_empty() || !secs.bytes().all(|x| x.is_ascii_digit()) {
+                    return None;
+                }
+                let micros = match parts.next() {
+                    Some(x) if x.len() == 6 && x.bytes().all(|x| x.is_ascii_digit()) => {
+                        x.parse().ok()?
+                    }
+                    Some(_) => return None,
+                    None => 0,
+                };
+                Some((secs.parse().ok()?, micros))
+            }
+            _ => None,
+        }
+    }
+
+    /// Converts a decimal value (see `MYSQL_TYPE_NEWDECIMAL`) to `f64`.
+    ///
+    /// Note, that the conversion is lossy – `f64` holds about 15-17 significant digits,
+    /// while MySql decimals could have up to 65 digits.
+    ///
+    /// Returns `None` if `self` isn't a decimal value.
+    pub fn decimal_as_f64(&self) -> Option<f64> {
+        self.as_decimal()?.to_string().parse().ok()
+    }
+
+    /// Converts a numeric value to a JSON number.
+    ///
+    /// Integers are converted as is, FLOAT and DOUBLE values are converted unless
+    /// they are not finite. A decimal value (see `MYSQL_TYPE_NEWDECIMAL`) is parsed from
+    /// its string representation, so it'll keep all its digits if `serde_json` is built
+    /// with the `arbitrary_precision` feature (and will be rounded to `f64` otherwise).
+    ///
+    /// Returns `None` for non-numeric values.
+    pub fn as_json_number(&self) -> Option<serde_json::Number> {
+        match *self {
+            BinlogValue::Value(Int(x)) => Some(x.into()),
+            BinlogValue::Value(UInt(x)) => Some(x.into()),
+            BinlogValue::Value(Float(x)) => serde_json::Number::from_f64(x as f64),
+            BinlogValue::Value(Double(x)) => serde_json::Number::from_f64(x),
+            _ => self.as_decimal()?.to_string().parse().ok(),
+        }
+    }
+
+    /// Formats a decimal value (see `MYSQL_TYPE_NEWDECIMAL`) grouping digits
+    /// of its integer part by thousands, i.e. `1234567.89` becomes `1,234,567.89`
+    /// if `thousands_sep` is `,`.
+    ///
+    /// This is a presentation helper – use [`BinlogValue::as_decimal_string`] to get
+    /// the raw decimal string. Returns `None` if `self` isn't a decimal value.
+    pub fn decimal_formatted(&self, thousands_sep: char) -> Option<String> {
+        let string = self.as_decimal_string()?;
+
+        let (sign, unsigned) = match string.strip_prefix('-') {
+            Some(unsigned) => ("-", unsigned),
+            None => ("", &*string),
+        };
+        let (int_part, frac_part) = match unsigned.find('.') {
+            Some(pos) => unsigned.split_at(pos),
+            None => (unsigned, ""),
+        };
+
+        let mut output = String::with_capacity(string.len() + int_part.len() / 3);
+        output.push_str(sign);
+        for (i, digit) in int_part.chars().enumerate() {
+            if i > 0 && (int_part.len() - i) % 3 == 0 {
+                output.push(thousands_sep);
+            }
+            output.push(digit);
+        }
+        output.push_str(frac_part);
+        Some(output)
+    }
+
+    /// Converts a decimal value (see `MYSQL_TYPE_NEWDECIMAL`) to `rust_decimal::Decimal`
+    /// preserving its scale.
+    ///
+    /// Returns `None` if `self` isn't a decimal value or if it doesn't fit into
+    /// `rust_decimal::Decimal` (precision above 28 digits).
+    #[cfg(feature = "rust_decimal")]
+    pub fn as_rust_decimal_with_scale(&self) -> Option<rust_decimal::Decimal> {
+        let (value, scale) = self.decimal_as_scaled_i128()?;
+        rust_decimal::Decimal::try_from_i128_with_scale(value, scale as u32).ok()
+    }
+
+    /// Converts a decoded DATE, DATETIME or TIMESTAMP value (see [`Value::Date`])
+    /// to `chrono::NaiveDateTime`.
+    ///
+    /// Returns `None` if `self` isn't a date-time value or if it isn't a valid date-time,
+    /// e.g. for zero dates or for impossible dates coming from corrupt data.
+    #[cfg(feature = "chrono")]
+    pub fn as_naive_datetime(&self) -> Option<chrono::NaiveDateTime> {
+        match *self {
+            BinlogValue::Value(Date(y, m, d, h, i, s, us)) => {
+                let date = chrono::NaiveDate::from_ymd_opt(y.into(), m.into(), d.into())?;
+                let time = chrono::NaiveTime::from_hms_micro_opt(h.into(), i.into(), s.into(), us)?;
+                Some(chrono::NaiveDateTime::new(date, time))
+            }
+            _ => None,
+        }
+    }
+
+    /// Converts a decoded temporal value of a column of the given type to `chrono`.
+    ///
+    /// DATE and NEWDATE values become [`ChronoTemporal::Date`], TIME and TIME2 values become
+    /// [`ChronoTemporal::Time`], DATETIME and DATETIME2 values become
+    /// [`ChronoTemporal::DateTime`]. TIMESTAMP and TIMESTAMP2 values (see
+    /// [`BinlogValue::as_timestamp`]) become [`ChronoTemporal::DateTime`] in UTC.
+    ///
+    /// Returns `None` for other column types, for values that don't match the column type
+    /// and for values that aren't valid dates, e.g. zero dates and the zero timestamp.
+    #[cfg(feature = "chrono")]
+    pub fn to_chrono(&self, column_type: ColumnType) -> Option<ChronoTemporal> {
+        use ColumnType::*;
+
+        match (column_type, self) {
+            (MYSQL_TYPE_DATE | MYSQL_TYPE_NEWDATE, BinlogValue::Value(Date(y, m, d, ..))) => {
+                chrono::NaiveDate::from_ymd_opt((*y).into(), (*m).into(), (*d).into())
+                    .map(ChronoTemporal::Date)
+            }
+            (MYSQL_TYPE_DATETIME | MYSQL_TYPE_DATETIME2, _) => {
+                self.as_naive_datetime().map(ChronoTemporal::DateTime)
+            }
+            (MYSQL_TYPE_TIMESTAMP | MYSQL_TYPE_TIMESTAMP2, _) => match self.as_timestamp()? {
+                (0, 0) => None,
+                (secs, micros) => chrono::NaiveDate::from_ymd_opt(1970, 1, 1)?
+                    .and_hms_opt(0, 0, 0)?
+                    .checked_add_signed(chrono::Duration::microseconds(
+                        secs.checked_mul(1_000_000)? + micros as i64,
+                    ))
+                    .map(ChronoTemporal::DateTime),
+            },
+            (
+                MYSQL_TYPE_TIME | MYSQL_TYPE_TIME2,
+                BinlogValue::Value(Time(is_neg, days, h, i, s, us)),
+            ) => {
+                let secs = *days as i64 * 86_400 + *h as i64 * 3600 + *i as i64 * 60 + *s as i64;
+                let duration = chrono::Duration::microseconds(secs * 1_000_000 + *us as i64);
+                Some(ChronoTemporal::Time(if *is_neg {
+                    -duration
+                } else {
+                    duration
+                }))
+            }
+            _ => None,
+        }
+    }
+
+    /// Same as `deserialize`, but errors if the value isn't of the `expected` kind.
+    pub fn deserialize_expecting(
+        ctx: impl Into<BinlogValueCtx<'a>>,
+        buf: &mut ParseBuf<'a>,
+        expected: ValueKind,
+    ) -> io::Result<Self> {
+        let ctx = ctx.into();
+        let value: Self = buf.parse(ctx)?;
+        if value.kind() != expected {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "expected {:?} value for {:?} column, got {:?}",
+                    expected,
+                    ctx.col_type(),
+                    value.kind()
+                ),
+            ));
+        }
+        Ok(value)
+    }
+
+    /// Same as `deserialize`, but also returns the exact bytes consumed by the value.
+    ///
+    /// This is a debugging helper, that allows to compare the raw value
+    /// against the `mysqlbinlog --hexdump` output.
+    pub fn deserialize_with_trace(
+        ctx: impl Into<BinlogValueCtx<'a>>,
+        buf: &mut ParseBuf<'a>,
+    ) -> io::Result<(Self, Vec<u8>)> {
+        let start = buf.0;
+        let value = buf.parse(ctx.into())?;
+        let consumed = start.len() - buf.len();
+        Ok((value, start[..consumed].to_vec()))
+    }
+
+    /// Same as `deserialize`, but the value is read from `buf` at the given `offset`,
+    /// and the error (if any) will report this offset and the column type.
+    ///
+    /// `offset` will be advanced by the length of the value.
+    pub fn deserialize_at(
+        ctx: impl Into<BinlogValueCtx<'a>>,
+        buf: &'a [u8],
+        offset: &mut usize,
+    ) -> Result<Self, BinlogValueParseError> {
+        let ctx = ctx.into();
+        let start = *offset;
+        let err = |error| BinlogValueParseError {
+            offset: start,
+            column_type: ctx.col_type(),
+            error,
+        };
+        let mut value_buf = ParseBuf(buf.get(start..).ok_or_else(|| err(unexpected_buf_eof()))?);
+        let value = value_buf.parse(ctx).map_err(err)?;
+        *offset = buf.len() - value_buf.len();
+        Ok(value)
+    }
+
+    /// Identifies the value's byte range without interpreting it.
+    ///
+    /// The returned [`RawBinlogValue`] could be promoted to a `BinlogValue` on demand.
+    pub fn scan(
+        ctx: impl Into<BinlogValueCtx<'a>>,
+        buf: &mut ParseBuf<'a>,
+    ) -> io::Result<RawBinlogValue<'a>> {
+        let BinlogValueCtx {
+            col_type,
+            col_meta,
+            is_unsigned,
+            is_partial,
+        } = ctx.into();
+        let len = value_len(col_type, col_meta, buf.0)?;
+        let data = buf.checked_eat(len).ok_or_else(unexpected_buf_eof)?;
+        Ok(RawBinlogValue {
+            col_type,
+            col_meta,
+            is_unsigned,
+            is_partial,
+            data,
+        })
+    }
+
+    /// Same as `deserialize`, but `col_meta` is a combined metadata buffer for all columns
+    /// (as stored in `TableMapEvent`) and `meta_offset` is the offset of this column's metadata.
+    ///
+    /// `meta_offset` will be advanced by the length of this column's metadata.
+    pub fn deserialize_with_meta_offset(
+        ctx: impl Into<BinlogValueCtx<'a>>,
+        meta_offset: &mut usize,
+        buf: &mut ParseBuf<'a>,
+    ) -> io::Result<Self> {
+        let BinlogValueCtx {
+            col_type,
+            col_meta,
+            is_unsigned,
+            is_partial,
+        } = ctx.into();
+        let (column_meta, meta_len) = col_meta
+            .get(*meta_offset..)
+            .and_then(|ptr| col_type.get_metadata(ptr, false))
+            .ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidData, "Malformed column metadata")
+            })?;
+        let value = buf.parse(BinlogValueCtx {
+            col_type,
+            col_meta: column_meta,
+            is_unsigned,
+            is_partial,
+        })?;
+        *meta_offset += meta_len;
+        Ok(value)
+    }
+
+    /// Deserializes values of a row image (i.e. of a row image without the null bitmap).
+    ///
+    /// *   `col_types` and `metas` – types and metadata of all the table columns;
+    /// *   `cols` – columns present bitmap of the rows event (bit is set if the corresponding
+    ///     column is present in the row image);
+    /// *   `null_bitmap` – null bitmap of the row image.
+    ///
+    /// Note that the null bitmap is indexed over the present columns only, i.e. its `n`-th bit
+    /// corresponds to the `n`-th set bit of `cols`.
+    ///
+    /// Returns a value per table column – `None` for columns that aren't present in the image
+    /// and [`BinlogValue::NULL`] for NULL columns. Numeric columns are decoded as signed.
+    pub fn deserialize_row(
+        col_types: &[ColumnType],
+        metas: &[&'a [u8]],
+        cols: &BitSlice<u8>,
+        null_bitmap: &BitSlice<u8>,
+        buf: &mut ParseBuf<'a>,
+    ) -> io::Result<Vec<Option<Self>>> {
+        if col_types.len() != metas.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "number of column types and metadata differs",
+            ));
+        }
+
+        let mut image_idx = 0;
+        let mut values = Vec::with_capacity(col_types.len());
+        for (i, (col_type, col_meta)) in col_types.iter().zip(metas).enumerate() {
+            if !cols.get(i).as_deref().copied().unwrap_or(false) {
+                values.push(None);
+                continue;
+            }
+            let is_null = null_bitmap
+                .get(image_idx)
+                .as_deref()
+                .copied()
+                .unwrap_or(true);
+            image_idx += 1;
+            if is_null {
+                values.push(Some(BinlogValue::Value(NULL)));
+            } else {
+                values.push(Some(buf.parse(BinlogValueCtx::new(*col_type, col_meta))?));
+            }
+        }
+
+        Ok(values)
+    }
+}
+
+/// Formats a zone-less RFC 3339 date-time.
+fn format_rfc3339(y: i64, m: u8, d: u8, h: u8, i: u8, s: u8, us: u32) -> String {
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:06}",
+        y, m, d, h, i, s, us
+    )
+}
+
+/// Converts a number of days since the Unix epoch to a `(year, month, day)` triple
+/// of the proleptic Gregorian calendar.
+fn civil_from_days(days: i64) -> (i64, u8, u8) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = doy - (153 * mp + 2) / 5 + 1;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 };
+    let y = yoe + era * 400 + if m <= 2 { 1 } else { 0 };
+    (y, m as u8, d as u8)
+}
+
+/// Decodes an element of a typed array.
+///
+/// DECIMAL elements are decoded as [`BinlogValue::Decimal`], just like DECIMAL columns.
+fn typed_array_element(
+    elem_type: ColumnType,
+    elem_meta: &[u8],
+    value: &jsonb::Value<'_>,
+) -> io::Result<BinlogValue<'static>> {
+    use ColumnType::*;
+
+    let malformed = || io::Error::new(io::ErrorKind::InvalidData, "Malformed typed array element");
+
+    let value = match (elem_type, value) {
+        (_, jsonb::Value::Null) => NULL,
+        (
+            MYSQL_TYPE_TINY | MYSQL_TYPE_SHORT | MYSQL_TYPE_INT24 | MYSQL_TYPE_LONG
+            | MYSQL_TYPE_LONGLONG | MYSQL_TYPE_YEAR,
+            value,
+        ) => match *value {
+            jsonb::Value::I16(x) => Int(x as i64),
+            jsonb::Value::U16(x) => Int(x as i64),
+            jsonb::Value::I32(x) => Int(x as i64),
+            jsonb::Value::U32(x) => Int(x as i64),
+            jsonb::Value::I64(x) => Int(x),
+            jsonb::Value::U64(x) => i64::try_from(x).map(Int).unwrap_or(UInt(x)),
+            _ => return Err(malformed()),
+        },
+        (MYSQL_TYPE_FLOAT, jsonb::Value::F64(x)) => Float(*x as f32),
+        (MYSQL_TYPE_DOUBLE, jsonb::Value::F64(x)) => Double(*x),
+        (
+            MYSQL_TYPE_VARCHAR | MYSQL_TYPE_VAR_STRING | MYSQL_TYPE_STRING,
+            jsonb::Value::String(x),
+        ) => Bytes(x.str_raw().into()),
+        (MYSQL_TYPE_NEWDECIMAL, jsonb::Value::Opaque(x)) => {
+            // element metadata holds precision and scale
+            let (precision, scale) = match elem_meta {
+                [precision, scale, ..] => (*precision as usize, *scale as usize),
+                _ => return Err(malformed()),
+            };
+            // opaque data is prefixed with precision and scale
+            let data = x.data_raw().get(2..).ok_or_else(malformed)?;
+            let dec = decimal::Decimal::read_bin(data, precision, scale, false)?;
+            return Ok(BinlogValue::Decimal(dec));
+        }
+        (
+            MYSQL_TYPE_DATE | MYSQL_TYPE_NEWDATE | MYSQL_TYPE_DATETIME | MYSQL_TYPE_DATETIME2
+            | MYSQL_TYPE_TIME | MYSQL_TYPE_TIME2,
+            jsonb::Value::Opaque(x),
+        ) => {
+            // packed temporal value
+            let data = x.data_raw().get(..8).ok_or_else(malformed)?;
+            let packed = i64::from_le_bytes(data.try_into().map_err(|_| malformed())?);
+            match elem_type {
+                MYSQL_TYPE_TIME | MYSQL_TYPE_TIME2 => time_from_packed(packed),
+                _ => datetime_from_packed(packed),
+            }
+        }
+        _ => return Err(malformed()),
+    };
+
+    Ok(BinlogValue::Value(value))
+}
+
+/// Returns members of a SET given its little-endian bitmask (see [`BinlogValue::decode_set`]).
+pub fn decode_set<'a>(bytes: &[u8], members: &[&'a str]) -> Option<Vec<&'a str>> {
+    let mut output = Vec::new();
+    for (i, byte) in bytes.iter().enumerate() {
+        for bit in 0..8 {
+            if byte & (1 << bit) != 0 {
+                output.push(*members.get(i * 8 + bit)?);
+            }
+        }
+    }
+    Some(output)
+}
+
+/// Returns the pack length (in bytes) of a `MYSQL_TYPE_ENUM` or `MYSQL_TYPE_SET` value.
+///
+/// Two metadata layouts are supported:
+///
+/// *   `[real_type, pack_length]` – metadata of a column that was recovered from
+///     a `MYSQL_TYPE_STRING` column of a table map event, as well as of a standalone
+///     ENUM or SET column (the pack length is in `col_meta[1]`);
+/// *   `[pack_length]` – metadata with the real type byte already stripped
+///     (the pack length is in `col_meta[0]`).
+fn enum_or_set_pack_len(col_meta: &[u8]) -> io::Result<usize> {
+    match *col_meta {
+        [pack_length] | [_, pack_length] => Ok(pack_length as usize),
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Malformed column metadata",
+        )),
+    }
+}
+
+/// Returns the maximum length of a legacy `MYSQL_TYPE_VAR_STRING` value.
+///
+/// It is encoded just like `MYSQL_TYPE_VARCHAR`, but table maps carry no metadata
+/// for this type, so the server falls back to the zero maximum length
+/// (i.e. to the one byte length prefix).
+fn var_string_max_len(col_meta: &[u8]) -> usize {
+    match col_meta {
+        [byte0, byte1, ..] => *byte0 as usize | (*byte1 as usize) << 8,
+        _ => 0,
+    }
+}
+
+/// Returns the maximum length (in bytes) of a `MYSQL_TYPE_STRING` value.
+///
+/// The server writes `real_type ^ ((length & 0x300) >> 4)` and `length & 0xFF`
+/// (see `Field_string::save_field_metadata`). All real types that are encoded
+/// as `MYSQL_TYPE_STRING` have both 0x30 bits set, so the two high bits of the length
+/// are recovered by xoring them back. The max length of a CHAR column is 1020 bytes
+/// (`CHAR(255)` in utf8mb4), so these two bits are enough.
+fn string_max_len(col_meta: &[u8]) -> usize {
+    if col_meta[0] >= 1 {
+        let byte0 = col_meta[0] as usize;
+        let byte1 = col_meta[1] as usize;
+
+        if (byte0 & 0x30) != 0x30 {
+            // a long CHAR() field: see #37426
+            byte1 | (((byte0 & 0x30) ^ 0x30) << 4)
+        } else {
+            byte1
+        }
+    } else {
+        (ParseBuf(col_meta)).eat_u16_le() as usize
+    }
+}
+
+/// Returns the on-wire length of a value (including length prefixes) given its metadata.
+///
+/// `buf` must start with the value, so that length prefixes could be read.
+fn value_len(col_type: ColumnType, col_meta: &[u8], buf: &[u8]) -> io::Result<usize> {
+    use ColumnType::*;
+
+    let meta = |i: usize| -> io::Result<usize> {
+        col_meta
+            .get(i)
+            .map(|x| *x as usize)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Malformed column metadata"))
+    };
+
+    // peeks a little-endian length prefix
+    let buf = ParseBuf(buf);
+    let prefixed = |prefix_len: usize| -> io::Result<usize> {
+        let len = match prefix_len {
+            1 => buf.checked_peek_u8().map(u32::from),
+            2 => buf.checked_peek_u16_le().map(u32::from),
+            3 => buf.checked_peek_u24_le(),
+            _ => buf.checked_peek_u32_le(),
+        }
+        .ok_or_else(unexpected_buf_eof)?;
+        Ok(prefix_len + len as usize)
+    };
+
+    match col_type {
+        MYSQL_TYPE_TINY | MYSQL_TYPE_YEAR => Ok(1),
+        MYSQL_TYPE_SHORT => Ok(2),
+        MYSQL_TYPE_INT24 | MYSQL_TYPE_TIME | MYSQL_TYPE_NEWDATE => Ok(3),
+        MYSQL_TYPE_LONG | MYSQL_TYPE_FLOAT | MYSQL_TYPE_TIMESTAMP => Ok(4),
+        MYSQL_TYPE_LONGLONG | MYSQL_TYPE_DOUBLE | MYSQL_TYPE_DATETIME => Ok(8),
+        MYSQL_TYPE_BIT => Ok((meta(1)? * 8 + meta(0)? + 7) / 8),
+        MYSQL_TYPE_TIMESTAMP2 => Ok(4 + (meta(0)? + 1) / 2),
+        MYSQL_TYPE_DATETIME2 => Ok(5 + (meta(0)? + 1) / 2),
+        MYSQL_TYPE_TIME2 => Ok(3 + (meta(0)? + 1) / 2),
+        MYSQL_TYPE_JSON | MYSQL_TYPE_TYPED_ARRAY => prefixed(4),
+        MYSQL_TYPE_NEWDECIMAL => {
+            let (precision, scale) = (meta(0)?, meta(1)?);
+            if scale > precision {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "Malformed column metadata",
+                ));
+            }
+            Ok(decimal::decimal_bin_size(precision, scale))
+        }
+        MYSQL_TYPE_ENUM => match enum_or_set_pack_len(col_meta)? {
+            x @ 1 | x @ 2 => Ok(x),
+            _ => Err(io::Error::new(io::ErrorKind::InvalidData, "Unknown ENUM")),
+        },
+        MYSQL_TYPE_SET => enum_or_set_pack_len(col_meta),
+        MYSQL_TYPE_TINY_BLOB
+        | MYSQL_TYPE_MEDIUM_BLOB
+        | MYSQL_TYPE_LONG_BLOB
+        | MYSQL_TYPE_BLOB
+        | MYSQL_TYPE_GEOMETRY => match meta(0)? {
+            x @ 1..=4 => prefixed(x),
+            _ => Err(io::Error::new(io::ErrorKind::InvalidData, "Unknown BLOB")),
+        },
+        MYSQL_TYPE_VARCHAR => {
+            let type_len = meta(0)? | (meta(1)? << 8);
+            prefixed(if type_len < 256 { 1 } else { 2 })
+        }
+        MYSQL_TYPE_VAR_STRING => prefixed(if var_string_max_len(col_meta) < 256 {
+            1
+        } else {
+            2
+        }),
+        MYSQL_TYPE_STRING => {
+            meta(1)?;
+            prefixed(if string_max_len(col_meta) < 256 { 1 } else { 2 })
+        }
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Don't know how to handle column",
+        )),
+    }
+}
+
+/// Returns the on-wire length of a value if it doesn't depend on the value itself.
+fn fixed_value_len(col_type: ColumnType, col_meta: &[u8]) -> Option<usize> {
+    use ColumnType::*;
+
+    match col_type {
+        MYSQL_TYPE_JSON
+        | MYSQL_TYPE_TYPED_ARRAY
+        | MYSQL_TYPE_TINY_BLOB
+        | MYSQL_TYPE_MEDIUM_BLOB
+        | MYSQL_TYPE_LONG_BLOB
+        | MYSQL_TYPE_BLOB
+        | MYSQL_TYPE_GEOMETRY
+        | MYSQL_TYPE_VARCHAR
+        | MYSQL_TYPE_VAR_STRING
+        | MYSQL_TYPE_STRING => None,
+        _ => value_len(col_type, col_meta, &[]).ok(),
+    }
+}
+
+/// A value that failed to parse (see [`BinlogValue::deserialize_lenient`]).
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("failed to parse {:?} value: {}", column_type, message)]
+pub struct BinlogValueError {
+    /// Column type of the value.
+    pub column_type: ColumnType,
+    /// Kind of the underlying error.
+    pub kind: io::ErrorKind,
+    /// Message of the underlying error.
+    pub message: String,
+}
+
+impl BinlogValueError {
+    pub(crate) fn new(column_type: ColumnType, error: io::Error) -> Self {
+        Self {
+            column_type,
+            kind: error.kind(),
+            message: error.to_string(),
+        }
+    }
+}
+
+impl From<BinlogValueError> for io::Error {
+    fn from(err: BinlogValueError) -> Self {
+        io::Error::new(err.kind, err)
+    }
+}
+
+/// An error returned by [`BinlogValue::deserialize_at`].
+#[derive(Debug, thiserror::Error)]
+#[error("failed at byte {} parsing {:?}: {}", offset, column_type, error)]
+pub struct BinlogValueParseError {
+    /// Offset of the value within the buffer.
+    pub offset: usize,
+    /// Column type of the value.
+    pub column_type: ColumnType,
+    /// Underlying error.
+    #[source]
+    pub error: io::Error,
+}
+
+impl From<BinlogValueParseError> for io::Error {
+    fn from(err: BinlogValueParseError) -> Self {
+        io::Error::new(err.error.kind(), err)
+    }
+}
+
+/// Builds a TIME value applying the given options.
+fn time_value(
+    mut neg: bool,
+    mut h: u32,
+    mut m: u8,
+    mut s: u8,
+    mut us: u32,
+    split_days: bool,
+    options: BinlogValueOptions,
+) -> io::Result<Value> {
+    if (h, m, s, us) > (TIME_MAX_HOUR, 59, 59, 0) {
+        match options.time_out_of_range() {
+            TimeOutOfRange::Error => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "TIME value is out of range",
+                ))
+            }
+            TimeOutOfRange::Clamp => {
+                h = TIME_MAX_HOUR;
+                m = 59;
+                s = 59;
+                us = 0;
+            }
+        }
+    }
+
+    if (h, m, s, us) == (0, 0, 0, 0) {
+        neg = false;
+    }
+
+    // hours that doesn't fit into `u8` are always moved into the days part
+    let (d, h) = if split_days || h > u8::MAX as u32 {
+        (h / 24, h % 24)
+    } else {
+        (0, h)
+    };
+
+    Ok(Time(neg, d, h as u8, m, s, us))
+}
+
+impl<'de> BinlogValue<'de> {
+    /// Same as `deserialize_with_options` but a malformed value of a fixed-length type
+    /// is returned as a [`BinlogValue::Error`] placeholder.
+    ///
+    /// The whole value width is consumed in this case, so that the following values
+    /// are still parsed at the right offset. Errors for variable-length types
+    /// (strings, blobs, JSON, etc.) and unexpected end of the buffer are returned as is.
+    pub fn deserialize_lenient(
+        ctx: impl Into<BinlogValueCtx<'de>>,
+        options: BinlogValueOptions,
+        buf: &mut ParseBuf<'de>,
+    ) -> io::Result<Self> {
+        let ctx = ctx.into();
+        match fixed_value_len(ctx.col_type, ctx.col_meta) {
+            Some(len) => {
+                let mut value_buf: ParseBuf = buf.parse(len)?;
+                match Self::deserialize_with_options(ctx, options, &mut value_buf) {
+                    Ok(value) => Ok(value),
+                    Err(err) => Ok(BinlogValue::Error(BinlogValueError::new(ctx.col_type, err))),
+                }
+            }
+            None => Self::deserialize_with_options(ctx, options, buf),
+        }
+    }
+
+    /// Same as `deserialize_with_options` but decimals are decoded using the given `scratch`.
+    ///
+    /// Only the intermediate binary buffer is reused across calls: the returned
+    /// `BinlogValue::Decimal` still owns its digits. Use [`decimal::DecimalScratch::read_into`]
+    /// to also reuse the digits of a caller-owned `Decimal`.
+    pub fn deserialize_with_scratch(
+        ctx: impl Into<BinlogValueCtx<'de>>,
+        options: BinlogValueOptions,
+        scratch: &mut decimal::DecimalScratch,
+        buf: &mut ParseBuf<'de>,
+    ) -> io::Result<Self> {
+        let ctx = ctx.into();
+        match ctx.col_type {
+            ColumnType::MYSQL_TYPE_NEWDECIMAL => {
+                let (precision, scale) = match *ctx.col_meta {
+                    [precision, scale, ..] => (precision as usize, scale as usize),
+                    _ => {
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            "Malformed column metadata",
+                        ))
+                    }
+                };
+                let mut dec = decimal::Decimal::default();
+                scratch.read_into(&mut *buf, precision, scale, false, &mut dec)?;
+                Ok(BinlogValue::Decimal(dec))
+            }
+            _ => Self::deserialize_with_options(ctx, options, buf),
+        }
+    }
+
+    /// Same as `deserialize` but allows to specify deserialization options.
+    pub fn deserialize_with_options(
+        ctx: impl Into<BinlogValueCtx<'de>>,
+        options: BinlogValueOptions,
+        buf: &mut ParseBuf<'de>,
+    ) -> io::Result<Self> {
+        use ColumnType::*;
+
+        let BinlogValueCtx {
+            col_type,
+            col_meta,
+            is_unsigned,
+            is_partial,
+        } = ctx.into();
+
+        let mut length = 0_usize;
+
+        if col_type == MYSQL_TYPE_STRING {
+            length = string_max_len(col_meta);
+        }
+
+        match col_type {
+            MYSQL_TYPE_TINY | MYSQL_TYPE_SHORT | MYSQL_TYPE_LONG | MYSQL_TYPE_LONGLONG
+            | MYSQL_TYPE_FLOAT | MYSQL_TYPE_DOUBLE => {
+                let mut flags = ColumnFlags::empty();
+                flags.set(ColumnFlags::UNSIGNED_FLAG, is_unsigned);
+                match Value::deserialize_bin((col_type, flags), &mut *buf)? {
+                    UInt(x) => match options.uint_overflow() {
+                        UIntOverflow::Keep => Ok(BinlogValue::Value(UInt(x))),
+                        UIntOverflow::Error => Err(io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            "unsigned BIGINT value is out of range of i64",
+                        )),
+                        UIntOverflow::Saturate => Ok(BinlogValue::Value(Int(i64::MAX))),
+                    },
+                    value => Ok(BinlogValue::Value(value)),
+                }
+            }
+            MYSQL_TYPE_TIMESTAMP => {
+                let val: RawInt<LeU32> = buf.parse(())?;
+                Ok(BinlogValue::Value(Int(*val as i64)))
+            }
+            MYSQL_TYPE_INT24 => {
+                if is_unsigned {
+                    let val: RawInt<LeU24> = buf.parse(())?;
                     Ok(BinlogValue::Value(Int(*val as i64)))
                 } else {
                     let val: RawInt<LeI24> = buf.parse(())?;
-                    Ok(BinlogValue::Value(Int(*val as i64)))
+                    // sign-extend the 24-bit value
+                    Ok(BinlogValue::Value(Int(((*val << 8) >> 8) as i64)))
                 }
             }
             MYSQL_TYPE_TIME => {
-                let tmp: RawInt<LeU24> = buf.parse(())?;
-                let h = *tmp / 10000;
-                let m = (*tmp % 10000) / 100;
-                let s = *tmp % 100;
-                Ok(BinlogValue::Value(Time(
-                    false, 0, h as u8, m as u8, s as u8, 0,
-                )))
+                // signed `HHMMSS` integer (the legacy type doesn't store fractional seconds)
+                let tmp: RawInt<LeI24> = buf.parse(())?;
+                let tmp = (*tmp << 8) >> 8;
+                let abs = tmp.unsigned_abs();
+                let h = abs / 10000;
+                let m = (abs % 10000) / 100;
+                let s = abs % 100;
+                time_value(
+                    tmp < 0,
+                    h,
+                    m as u8,
+                    s as u8,
+                    0,
+                    options.split_time_days(),
+                    options,
+                )
+                .map(BinlogValue::Value)
             }
             MYSQL_TYPE_DATETIME => {
                 // read YYYYMMDDHHMMSS representaion
@@ -122,10 +2062,14 @@ impl<'de> MyDeserialize<'de> for BinlogValue<'de> {
                 )))
             }
             MYSQL_TYPE_YEAR => {
+                // the year is stored as an offset from 1900 (regardless of the display width),
+                // and `0` stands for the zero year
                 let y = *buf.parse::<RawInt<u8>>(())? as i32;
-                Ok(BinlogValue::Value(Bytes(
-                    (1900 + y).to_string().into_bytes(),
-                )))
+                let year = match y {
+                    0 => "0000".to_owned(),
+                    y => (1900 + y).to_string(),
+                };
+                Ok(BinlogValue::Value(Bytes(year.into_bytes())))
             }
             MYSQL_TYPE_NEWDATE => {
                 let tmp = *buf.parse::<RawInt<LeU24>>(())?;
@@ -161,8 +2105,9 @@ impl<'de> MyDeserialize<'de> for BinlogValue<'de> {
             }
             MYSQL_TYPE_TIME2 => {
                 let dec = col_meta[0];
-                my_time_packed_from_binary(&mut *buf, dec as u32)
-                    .map(time_from_packed)
+                let packed = my_time_packed_from_binary(&mut *buf, dec as u32)?;
+                let (neg, h, m, s, us) = time_parts_from_packed(packed);
+                time_value(neg, h, m, s, us, options.split_time_days(), options)
                     .map(BinlogValue::Value)
             }
             MYSQL_TYPE_JSON => {
@@ -178,6 +2123,33 @@ impl<'de> MyDeserialize<'de> for BinlogValue<'de> {
                     Ok(BinlogValue::Jsonb(json_value_buf.parse(())?))
                 }
             }
+            MYSQL_TYPE_TYPED_ARRAY => {
+                // element type followed by element metadata
+                let elem_type = col_meta
+                    .first()
+                    .and_then(|x| ColumnType::try_from(*x).ok())
+                    .ok_or_else(|| {
+                        io::Error::new(io::ErrorKind::InvalidData, "Malformed column metadata")
+                    })?;
+                let elem_meta = &col_meta[1..];
+
+                // typed array is stored as a JSONB array
+                length = *buf.parse::<RawInt<LeU32>>(())? as usize;
+                let mut array_buf: ParseBuf = buf.parse(length)?;
+                let array: jsonb::Value = array_buf.parse(())?;
+                let decode = |x: io::Result<jsonb::Value<'_>>| {
+                    typed_array_element(elem_type, elem_meta, &x?)
+                };
+                let elements = match array {
+                    jsonb::Value::SmallArray(ref x) => x.iter().map(decode).collect(),
+                    jsonb::Value::LargeArray(ref x) => x.iter().map(decode).collect(),
+                    _ => Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "Typed array isn't a JSON array",
+                    )),
+                };
+                elements.map(BinlogValue::TypedArray)
+            }
             MYSQL_TYPE_NEWDECIMAL => {
                 // precision is the maximum number of decimal digits
                 let precision = col_meta[0] as usize;
@@ -186,9 +2158,9 @@ impl<'de> MyDeserialize<'de> for BinlogValue<'de> {
 
                 let dec = decimal::Decimal::read_bin(&mut *buf, precision, scale, false)?;
 
-                Ok(BinlogValue::Value(Bytes(dec.to_string().into_bytes())))
+                Ok(BinlogValue::Decimal(dec))
             }
-            MYSQL_TYPE_ENUM => match col_meta[1] {
+            MYSQL_TYPE_ENUM => match enum_or_set_pack_len(col_meta)? {
                 1 => {
                     let val = buf.parse::<RawInt<u8>>(())?;
                     Ok(BinlogValue::Value(Int(*val as i64)))
@@ -200,7 +2172,7 @@ impl<'de> MyDeserialize<'de> for BinlogValue<'de> {
                 _ => Err(io::Error::new(io::ErrorKind::InvalidData, "Unknown ENUM")),
             },
             MYSQL_TYPE_SET => {
-                let nbytes = col_meta[1] as usize;
+                let nbytes = enum_or_set_pack_len(col_meta)?;
                 let bytes: &[u8] = buf.parse(nbytes)?;
                 Ok(BinlogValue::Value(Bytes(bytes.into())))
             }
@@ -220,7 +2192,11 @@ impl<'de> MyDeserialize<'de> for BinlogValue<'de> {
                 Ok(BinlogValue::Value(Bytes(bytes.into())))
             }
             MYSQL_TYPE_VARCHAR | MYSQL_TYPE_VAR_STRING => {
-                let type_len = (col_meta[0] as u16 | ((col_meta[1] as u16) << 8)) as usize;
+                let type_len = if col_type == MYSQL_TYPE_VARCHAR {
+                    (col_meta[0] as u16 | ((col_meta[1] as u16) << 8)) as usize
+                } else {
+                    var_string_max_len(col_meta)
+                };
                 let nbytes = if type_len < 256 {
                     *buf.parse::<RawInt<u8>>(())? as usize
                 } else {
@@ -246,25 +2222,2503 @@ impl<'de> MyDeserialize<'de> for BinlogValue<'de> {
     }
 }
 
-#[derive(Debug, thiserror::Error)]
-pub enum BinlogValueToValueError {
-    #[error("Can't convert Jsonb to Json: {}", _0)]
-    ToJson(#[from] JsonbToJsonError),
-    #[error("Impossible to convert JsonDiff to Value")]
-    JsonDiff,
+impl<'de> MyDeserialize<'de> for BinlogValue<'de> {
+    const SIZE: Option<usize> = None;
+    type Ctx = BinlogValueCtx<'de>;
+
+    fn deserialize(ctx: Self::Ctx, buf: &mut ParseBuf<'de>) -> io::Result<Self> {
+        Self::deserialize_with_options(ctx, BinlogValueOptions::default(), buf)
+    }
 }
 
-impl<'a> TryFrom<BinlogValue<'a>> for Value {
-    type Error = BinlogValueToValueError;
+impl BinlogValue<'_> {
+    /// Serializes `self` into its binary representation within a row image
+    /// (i.e. it is the inverse of [`MyDeserialize::deserialize`] given the same context).
+    ///
+    /// Typed arrays are not supported (their elements lose the original JSONB type).
+    /// Returns an `InvalidInput` error if `self` doesn't fit the column.
+    pub fn serialize<'a>(
+        &self,
+        ctx: impl Into<BinlogValueCtx<'a>>,
+        buf: &mut Vec<u8>,
+    ) -> io::Result<()> {
+        use ColumnType::*;
 
-    fn try_from(value: BinlogValue<'a>) -> Result<Self, Self::Error> {
-        match value {
-            BinlogValue::Value(x) => Ok(x),
-            BinlogValue::Jsonb(x) => {
-                let json = serde_json::Value::try_from(x)?;
-                Ok(Value::Bytes(Vec::from(json.to_string())))
+        let ctx = ctx.into();
+        let BinlogValueCtx {
+            col_type,
+            col_meta,
+            is_unsigned,
+            is_partial,
+        } = ctx;
+
+        let mismatch = || {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{:?} value doesn't fit {:?} column", self.kind(), col_type),
+            )
+        };
+        let meta = |i: usize| col_meta.get(i).copied().ok_or_else(mismatch);
+        let put_len = |buf: &mut Vec<u8>, width: usize, len: usize| {
+            if width < 8 && len >= 1 << (width * 8) {
+                return Err(mismatch());
             }
-            BinlogValue::JsonDiff(_) => Err(BinlogValueToValueError::JsonDiff),
+            buf.put_uint_le(len as u64, width);
+            Ok(())
+        };
+
+        let value = match self {
+            BinlogValue::Value(x) => x,
+            BinlogValue::Decimal(x) if col_type == MYSQL_TYPE_NEWDECIMAL => {
+                // the string form is padded to the column precision and scale below
+                return BinlogValue::Value(Bytes(x.to_string().into_bytes())).serialize(ctx, buf);
+            }
+            BinlogValue::Jsonb(x) if col_type == MYSQL_TYPE_JSON && !is_partial => {
+                let mut json = Vec::new();
+                x.serialize(&mut json);
+                put_len(buf, 4, json.len())?;
+                buf.extend_from_slice(&json);
+                return Ok(());
+            }
+            BinlogValue::JsonDiff(x) if col_type == MYSQL_TYPE_JSON && is_partial => {
+                let mut diffs = Vec::new();
+                for diff in x {
+                    diff.serialize(&mut diffs);
+                }
+                put_len(buf, 4, diffs.len())?;
+                buf.extend_from_slice(&diffs);
+                return Ok(());
+            }
+            _ => return Err(mismatch()),
+        };
+
+        match (col_type, value) {
+            (MYSQL_TYPE_TINY, Int(x)) => buf.put_u8(*x as u8),
+            (MYSQL_TYPE_SHORT, Int(x)) => buf.put_u16_le(*x as u16),
+            (MYSQL_TYPE_INT24, Int(x)) => buf.put_u24_le(*x as u32),
+            (MYSQL_TYPE_LONG, Int(x)) | (MYSQL_TYPE_TIMESTAMP, Int(x)) => buf.put_u32_le(*x as u32),
+            (MYSQL_TYPE_LONGLONG, Int(x)) => buf.put_i64_le(*x),
+            (MYSQL_TYPE_LONGLONG, UInt(x)) if is_unsigned => buf.put_u64_le(*x),
+            (MYSQL_TYPE_FLOAT, Float(x)) => buf.put_f32_le(*x),
+            (MYSQL_TYPE_DOUBLE, Double(x)) => buf.put_f64_le(*x),
+            (MYSQL_TYPE_TIME, Time(neg, d, h, m, s, _)) => {
+                let h = d * 24 + *h as u32;
+                let abs = (h * 10000 + *m as u32 * 100 + *s as u32) as i32;
+                buf.put_i24_le(if *neg { -abs } else { abs });
+            }
+            (MYSQL_TYPE_DATETIME, Date(y, m, d, h, i, s, _)) => {
+                let d_part = *y as u64 * 10000 + *m as u64 * 100 + *d as u64;
+                let t_part = *h as u64 * 10000 + *i as u64 * 100 + *s as u64;
+                buf.put_u64_le(d_part * 1_000_000 + t_part);
+            }
+            (MYSQL_TYPE_YEAR, Bytes(x)) => {
+                let y = std::str::from_utf8(x)
+                    .ok()
+                    .and_then(|x| x.parse::<u16>().ok())
+                    .and_then(|x| match x {
+                        0 => Some(0),
+                        x => x
+                            .checked_sub(1900)
+                            .filter(|x| (1..=u8::MAX as u16).contains(x)),
+                    })
+                    .ok_or_else(mismatch)?;
+                buf.put_u8(y as u8);
+            }
+            (MYSQL_TYPE_NEWDATE, Date(y, m, d, ..)) => {
+                buf.put_u24_le(((*y as u32) << 9) | ((*m as u32) << 5) | *d as u32)
+            }
+            (MYSQL_TYPE_BIT, Bytes(x)) => {
+                let nbits = meta(1)? as usize * 8 + (meta(0)? as usize);
+                if x.len() != (nbits + 7) / 8 {
+                    return Err(mismatch());
+                }
+                buf.extend_from_slice(x);
+            }
+            (MYSQL_TYPE_TIMESTAMP2, Bytes(x)) => {
+                let string = std::str::from_utf8(x).map_err(|_| mismatch())?;
+                let mut parts = string.splitn(2, '.');
+                let sec = parts.next().and_then(|x| x.parse().ok());
+                let usec = match parts.next() {
+                    Some(x) if x.len() == 6 => x.parse().ok(),
+                    Some(_) => None,
+                    None => Some(0),
+                };
+                match (sec, usec) {
+                    (Some(sec), Some(usec)) => {
+                        my_timestamp_to_binary(&mut *buf, sec, usec, meta(0)?)?
+                    }
+                    _ => return Err(mismatch()),
+                }
+            }
+            (MYSQL_TYPE_DATETIME2, Date(y, m, d, h, i, s, us)) => {
+                let packed = datetime_packed_from_parts(*y, *m, *d, *h, *i, *s, *us);
+                my_datetime_packed_to_binary(&mut *buf, packed, meta(0)? as u32)?;
+            }
+            (MYSQL_TYPE_TIME2, Time(neg, d, h, m, s, us)) => {
+                let packed = time_packed_from_parts(*neg, d * 24 + *h as u32, *m, *s, *us);
+                my_time_packed_to_binary(&mut *buf, packed, meta(0)? as u32)?;
+            }
+            (MYSQL_TYPE_NEWDECIMAL, Bytes(x)) => {
+                let (precision, scale) = (meta(0)? as usize, meta(1)? as usize);
+                let string = std::str::from_utf8(x).map_err(|_| mismatch())?;
+                let (sign, unsigned) = match string.strip_prefix('-') {
+                    Some(unsigned) => ("-", unsigned),
+                    None => ("", string),
+                };
+                let (int_part, frac_part) = match unsigned.find('.') {
+                    Some(pos) => (&unsigned[..pos], &unsigned[(pos + 1)..]),
+                    None => (unsigned, ""),
+                };
+                let intg = precision.checked_sub(scale).ok_or_else(mismatch)?;
+                if int_part.len() > intg || frac_part.len() > scale {
+                    return Err(mismatch());
+                }
+                // pad the value to the column precision and scale
+                let padded = format!(
+                    "{}{:0>intg$}.{:0<scale$}",
+                    sign,
+                    int_part,
+                    frac_part,
+                    intg = intg,
+                    scale = scale
+                );
+                let dec =
+                    decimal::Decimal::parse_bytes(padded.as_bytes()).map_err(|_| mismatch())?;
+                dec.write_bin(&mut *buf)?;
+            }
+            (MYSQL_TYPE_ENUM, Int(x)) => {
+                let width = enum_or_set_pack_len(col_meta)?;
+                if !(1..=2).contains(&width) {
+                    return Err(io::Error::new(io::ErrorKind::InvalidData, "Unknown ENUM"));
+                }
+                put_len(buf, width, *x as usize)?;
+            }
+            (MYSQL_TYPE_SET, Bytes(x)) => {
+                if x.len() != enum_or_set_pack_len(col_meta)? {
+                    return Err(mismatch());
+                }
+                buf.extend_from_slice(x);
+            }
+            (
+                MYSQL_TYPE_TINY_BLOB
+                | MYSQL_TYPE_MEDIUM_BLOB
+                | MYSQL_TYPE_LONG_BLOB
+                | MYSQL_TYPE_BLOB
+                | MYSQL_TYPE_GEOMETRY,
+                Bytes(x),
+            ) => {
+                let width = meta(0)? as usize;
+                if !(1..=4).contains(&width) {
+                    return Err(io::Error::new(io::ErrorKind::InvalidData, "Unknown BLOB"));
+                }
+                put_len(buf, width, x.len())?;
+                buf.extend_from_slice(x);
+            }
+            (MYSQL_TYPE_VARCHAR, Bytes(x)) => {
+                let type_len = (meta(0)? as u16 | ((meta(1)? as u16) << 8)) as usize;
+                put_len(buf, if type_len < 256 { 1 } else { 2 }, x.len())?;
+                buf.extend_from_slice(x);
+            }
+            (MYSQL_TYPE_VAR_STRING, Bytes(x)) => {
+                let type_len = var_string_max_len(col_meta);
+                put_len(buf, if type_len < 256 { 1 } else { 2 }, x.len())?;
+                buf.extend_from_slice(x);
+            }
+            (MYSQL_TYPE_STRING, Bytes(x)) => {
+                let width = if string_max_len(col_meta) < 256 { 1 } else { 2 };
+                put_len(buf, width, x.len())?;
+                buf.extend_from_slice(x);
+            }
+            _ => return Err(mismatch()),
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum BinlogValueToValueError {
+    #[error("Can't convert Jsonb to Json: {}", _0)]
+    ToJson(#[from] JsonbToJsonError),
+    #[error("Impossible to convert JsonDiff to Value")]
+    JsonDiff,
+    #[error("Impossible to convert TypedArray to Value")]
+    TypedArray,
+    #[error(transparent)]
+    Error(BinlogValueError),
+}
+
+impl<'a> TryFrom<BinlogValue<'a>> for Value {
+    type Error = BinlogValueToValueError;
+
+    fn try_from(value: BinlogValue<'a>) -> Result<Self, Self::Error> {
+        match value {
+            BinlogValue::Value(x) => Ok(x),
+            BinlogValue::Decimal(x) => Ok(Value::Bytes(x.to_string().into_bytes())),
+            BinlogValue::Jsonb(x) => {
+                let json = serde_json::Value::try_from(x)?;
+                Ok(Value::Bytes(Vec::from(json.to_string())))
+            }
+            BinlogValue::JsonDiff(_) => Err(BinlogValueToValueError::JsonDiff),
+            BinlogValue::TypedArray(_) => Err(BinlogValueToValueError::TypedArray),
+            BinlogValue::Error(x) => Err(BinlogValueToValueError::Error(x)),
+        }
+    }
+}
+
+/// Same as [`BinlogValue::to_json`] for a non-binary column.
+impl<'a> TryFrom<BinlogValue<'a>> for serde_json::Value {
+    type Error = BinlogValueToValueError;
+
+    fn try_from(value: BinlogValue<'a>) -> Result<Self, Self::Error> {
+        value.to_json(false)
+    }
+}
+
+/// Human-readable rendering of a value (e.g. for logging):
+///
+/// *   SQL NULL and numbers are rendered as is, decimals keep all their digits;
+/// *   bytes are rendered as quoted strings (see [`Value::as_sql`]), or in hex (i.e. `0x0AFF`)
+///     if they aren't valid UTF-8;
+/// *   temporal values are rendered the way MySql does it (i.e. `2021-12-31 23:59:59`);
+/// *   JSONB values are rendered as compact JSON, elements of typed arrays are rendered
+///     within brackets;
+/// *   partial JSON updates are rendered as `<OPERATION> <path> = <value>` items
+///     separated by `; ` (there is no value for `REMOVE`).
+impl fmt::Display for BinlogValue<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn jsonb(f: &mut fmt::Formatter<'_>, value: &jsonb::Value<'_>) -> fmt::Result {
+            match serde_json::Value::try_from(value) {
+                Ok(json) => write!(f, "{}", json),
+                Err(_) => write!(f, "{:?}", value),
+            }
+        }
+
+        match self {
+            BinlogValue::Value(Bytes(bytes)) => match std::str::from_utf8(bytes) {
+                Ok(_) => f.write_str(&Bytes(bytes.clone()).as_sql(false)),
+                Err(_) => {
+                    f.write_str("0x")?;
+                    bytes.iter().try_for_each(|x| write!(f, "{:02X}", x))
+                }
+            },
+            BinlogValue::Value(_) | BinlogValue::Decimal(_) => f.write_str(&self.to_string_lossy()),
+            BinlogValue::Jsonb(x) => jsonb(f, x),
+            BinlogValue::JsonDiff(diffs) => {
+                for (i, diff) in diffs.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str("; ")?;
+                    }
+                    write!(f, "{:?} {}", diff.operation(), diff.path_str())?;
+                    if let Some(value) = diff.value() {
+                        f.write_str(" = ")?;
+                        jsonb(f, value)?;
+                    }
+                }
+                Ok(())
+            }
+            BinlogValue::TypedArray(elements) => {
+                f.write_str("[")?;
+                for (i, element) in elements.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{}", element)?;
+                }
+                f.write_str("]")
+            }
+            BinlogValue::Error(x) => write!(f, "{}", x),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use super::*;
+    use crate::proto::MySerialize;
+
+    /// Returns binary representation of a decimal `value` as a `DECIMAL(precision, scale)`
+    /// (`value` must have exactly `scale` digits after the point).
+    fn decimal_bin(value: &str, precision: usize, scale: usize) -> Vec<u8> {
+        let (sign, value) = match value.strip_prefix('-') {
+            Some(value) => ("-", value),
+            None => ("", value),
+        };
+        let intg = value.find('.').unwrap_or(value.len());
+        let padding = "0".repeat(precision - scale - intg);
+        let mut bin = Vec::new();
+        decimal::Decimal::from_str(&format!("{}{}{}", sign, padding, value))
+            .unwrap()
+            .write_bin(&mut bin)
+            .unwrap();
+        bin
+    }
+
+    #[test]
+    #[cfg(feature = "rust_decimal")]
+    fn as_rust_decimal_with_scale() -> io::Result<()> {
+        let bin = decimal_bin("-12345678901234.500000", 20, 6);
+        let ctx = BinlogValueCtx::new(ColumnType::MYSQL_TYPE_NEWDECIMAL, &[20, 6][..]);
+        let value: BinlogValue = ParseBuf(&bin).parse(ctx)?;
+
+        let dec = value.as_rust_decimal_with_scale().unwrap();
+        assert_eq!(dec.scale(), 6);
+        assert_eq!(dec.to_string(), "-12345678901234.500000");
+
+        assert_eq!(BinlogValue::int(1).as_rust_decimal_with_scale(), None);
+
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "chrono")]
+    fn as_naive_datetime() {
+        let datetime = BinlogValue::datetime(2021, 12, 31, 23, 59, 59, 500_000)
+            .as_naive_datetime()
+            .unwrap();
+        assert_eq!(datetime.to_string(), "2021-12-31 23:59:59.500");
+
+        assert_eq!(
+            BinlogValue::datetime(2021, 13, 1, 0, 0, 0, 0).as_naive_datetime(),
+            None
+        );
+        assert_eq!(
+            BinlogValue::datetime(2021, 1, 0, 0, 0, 0, 0).as_naive_datetime(),
+            None
+        );
+        assert_eq!(
+            BinlogValue::datetime(2021, 1, 1, 24, 0, 0, 0).as_naive_datetime(),
+            None
+        );
+        assert_eq!(BinlogValue::int(1).as_naive_datetime(), None);
+    }
+
+    #[test]
+    #[cfg(feature = "chrono")]
+    fn to_chrono() {
+        use ColumnType::*;
+
+        let date = BinlogValue::datetime(2021, 12, 31, 0, 0, 0, 0);
+        assert_eq!(
+            date.to_chrono(MYSQL_TYPE_NEWDATE),
+            Some(ChronoTemporal::Date(
+                chrono::NaiveDate::from_ymd_opt(2021, 12, 31).unwrap()
+            ))
+        );
+        assert_eq!(
+            BinlogValue::datetime(0, 0, 0, 0, 0, 0, 0).to_chrono(MYSQL_TYPE_DATE),
+            None
+        );
+
+        let time = BinlogValue::Value(Time(true, 1, 2, 3, 4, 500_000));
+        assert_eq!(
+            time.to_chrono(MYSQL_TYPE_TIME2),
+            Some(ChronoTemporal::Time(-chrono::Duration::microseconds(
+                93_784_500_000
+            )))
+        );
+
+        let datetime = BinlogValue::datetime(2021, 12, 31, 23, 59, 59, 500_000);
+        let expected = chrono::NaiveDate::from_ymd_opt(2021, 12, 31)
+            .unwrap()
+            .and_hms_micro_opt(23, 59, 59, 500_000)
+            .unwrap();
+        assert_eq!(
+            datetime.to_chrono(MYSQL_TYPE_DATETIME2),
+            Some(ChronoTemporal::DateTime(expected))
+        );
+        assert_eq!(
+            BinlogValue::bytes("1640995199.500000").to_chrono(MYSQL_TYPE_TIMESTAMP2),
+            Some(ChronoTemporal::DateTime(expected))
+        );
+        assert_eq!(BinlogValue::int(0).to_chrono(MYSQL_TYPE_TIMESTAMP), None);
+
+        assert_eq!(date.to_chrono(MYSQL_TYPE_TIME2), None);
+        assert_eq!(BinlogValue::int(1).to_chrono(MYSQL_TYPE_LONG), None);
+    }
+
+    #[test]
+    fn resolve_enum() -> io::Result<()> {
+        let labels = ["small", "medium", "large"];
+
+        let ctx = BinlogValueCtx::new(ColumnType::MYSQL_TYPE_ENUM, &[1][..]);
+        let value: BinlogValue = ParseBuf(&[1]).parse(ctx)?;
+        assert_eq!(
+            value.resolve_enum(&labels),
+            Some(BinlogValue::bytes("small"))
+        );
+        let value: BinlogValue = ParseBuf(&[3]).parse(ctx)?;
+        assert_eq!(
+            value.resolve_enum(&labels),
+            Some(BinlogValue::bytes("large"))
+        );
+        let value: BinlogValue = ParseBuf(&[0]).parse(ctx)?;
+        assert_eq!(value.resolve_enum(&labels), Some(BinlogValue::bytes("")));
+        let value: BinlogValue = ParseBuf(&[4]).parse(ctx)?;
+        assert_eq!(value.resolve_enum(&labels), None);
+
+        assert_eq!(BinlogValue::int(-1).resolve_enum(&labels), None);
+        assert_eq!(BinlogValue::bytes("small").resolve_enum(&labels), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn enum_index() -> io::Result<()> {
+        let labels = ["small", "medium", "large"];
+
+        // 2-byte ENUM
+        let ctx = BinlogValueCtx::new(ColumnType::MYSQL_TYPE_ENUM, &[2][..]);
+        let value: BinlogValue = ParseBuf(&[3, 0]).parse(ctx)?;
+        assert_eq!(
+            value.resolve_enum(&labels),
+            Some(BinlogValue::bytes("large"))
+        );
+        assert_eq!(value.enum_index(), Some(3));
+
+        // error member
+        let value: BinlogValue = ParseBuf(&[0, 0]).parse(ctx)?;
+        assert_eq!(value.resolve_enum(&labels), Some(BinlogValue::bytes("")));
+        assert_eq!(value.enum_index(), Some(0));
+
+        assert_eq!(BinlogValue::int(-1).enum_index(), None);
+        assert_eq!(BinlogValue::bytes("large").enum_index(), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn decode_set() -> io::Result<()> {
+        let members = (1..=20).map(|i| format!("m{}", i)).collect::<Vec<_>>();
+        let members = members.iter().map(|x| x.as_str()).collect::<Vec<_>>();
+
+        // 20-member SET takes 3 bytes
+        let ctx = BinlogValueCtx::new(ColumnType::MYSQL_TYPE_SET, &[248, 3][..]);
+        let value: BinlogValue = ParseBuf(&[0b1000_0001, 0b0000_0001, 0b0000_1000]).parse(ctx)?;
+        assert_eq!(
+            value.decode_set(&members),
+            Some(vec!["m1", "m8", "m9", "m20"])
+        );
+
+        let value: BinlogValue = ParseBuf(&[0, 0, 0]).parse(ctx)?;
+        assert_eq!(value.decode_set(&members), Some(vec![]));
+
+        // bit of a non-existent member
+        let value: BinlogValue = ParseBuf(&[0, 0, 0b0001_0000]).parse(ctx)?;
+        assert_eq!(value.decode_set(&members), None);
+
+        assert_eq!(BinlogValue::int(1).decode_set(&members), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn decimal_variant() -> io::Result<()> {
+        let ctx = BinlogValueCtx::new(ColumnType::MYSQL_TYPE_NEWDECIMAL, &[10, 2][..]);
+        let bin = decimal_bin("-12.50", 10, 2);
+        let value: BinlogValue = ParseBuf(&bin).parse(ctx)?;
+        match value {
+            BinlogValue::Decimal(ref dec) => {
+                assert_eq!(dec.scale(), 2);
+                assert_eq!(dec.to_scaled_i128(), Some(-1250));
+            }
+            ref other => panic!("unexpected value {:?}", other),
+        }
+
+        assert_eq!(value.as_decimal_string().as_deref(), Some("-12.50"));
+        assert_eq!(value.to_string_lossy(), "-12.50");
+        assert_eq!(value.to_sql_literal(), "-12.50");
+        assert_eq!(value.decimal_as_scaled_i128(), Some((-1250, 2)));
+
+        let mut output = Vec::new();
+        value.serialize(ctx, &mut output)?;
+        assert_eq!(output, bin);
+
+        let value = Value::try_from(value.into_owned()).unwrap();
+        assert_eq!(value, Bytes(b"-12.50".to_vec()));
+
+        // decimal strings are still accepted
+        assert_eq!(
+            BinlogValue::bytes("1.50").as_decimal_string().as_deref(),
+            Some("1.50")
+        );
+        assert_eq!(BinlogValue::bytes("abc").as_decimal_string(), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn deserialize_with_scratch() -> io::Result<()> {
+        let values = [
+            ("123.40", 10, 2),
+            ("-0.01", 10, 2),
+            ("0.00", 10, 2),
+            ("-12345678901234567890.1234567890", 30, 10),
+            ("42", 4, 0),
+        ];
+        let mut scratch = decimal::DecimalScratch::new();
+        for (value, precision, scale) in values {
+            let bin = decimal_bin(value, precision, scale);
+            let col_meta = [precision as u8, scale as u8];
+            let ctx = BinlogValueCtx::new(ColumnType::MYSQL_TYPE_NEWDECIMAL, &col_meta[..]);
+            let mut buf = ParseBuf(&bin);
+            let decoded = BinlogValue::deserialize_with_scratch(
+                ctx,
+                BinlogValueOptions::new(),
+                &mut scratch,
+                &mut buf,
+            )?;
+            assert!(buf.is_empty());
+            assert_eq!(decoded, ParseBuf(&bin).parse(ctx)?);
+            assert_eq!(decoded.as_decimal_string().as_deref(), Some(value));
+        }
+
+        let ctx = BinlogValueCtx::new(ColumnType::MYSQL_TYPE_LONG, &[][..]);
+        let decoded = BinlogValue::deserialize_with_scratch(
+            ctx,
+            BinlogValueOptions::new(),
+            &mut scratch,
+            &mut ParseBuf(&[42, 0, 0, 0]),
+        )?;
+        assert_eq!(decoded, BinlogValue::int(42));
+
+        // short metadata
+        let ctx = BinlogValueCtx::new(ColumnType::MYSQL_TYPE_NEWDECIMAL, &[10][..]);
+        let err = BinlogValue::deserialize_with_scratch(
+            ctx,
+            BinlogValueOptions::new(),
+            &mut scratch,
+            &mut ParseBuf(&decimal_bin("1.00", 10, 2)),
+        )
+        .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        // decoding into a caller-owned decimal
+        let mut dec = decimal::Decimal::default();
+        for (value, precision, scale) in values {
+            let bin = decimal_bin(value, precision, scale);
+            scratch.read_into(&bin[..], precision, scale, false, &mut dec)?;
+            assert_eq!(dec.to_string(), value);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn to_json() -> io::Result<()> {
+        use serde_json::json;
+
+        assert_eq!(
+            BinlogValue::Value(NULL).to_json(false).unwrap(),
+            json!(null)
+        );
+        assert_eq!(BinlogValue::int(-1).to_json(false).unwrap(), json!(-1));
+        assert_eq!(
+            BinlogValue::Value(UInt(u64::MAX)).to_json(false).unwrap(),
+            json!(u64::MAX)
+        );
+        assert_eq!(
+            BinlogValue::Value(Double(0.5)).to_json(false).unwrap(),
+            json!(0.5)
+        );
+        assert_eq!(
+            BinlogValue::Value(Float(f32::NAN)).to_json(false).unwrap(),
+            json!(null)
+        );
+
+        // text vs binary
+        let text = BinlogValue::bytes("foo");
+        assert_eq!(
+            serde_json::Value::try_from(text.clone()).unwrap(),
+            json!("foo")
+        );
+        assert_eq!(text.to_json(true).unwrap(), json!("Zm9v"));
+        let invalid_utf8 = BinlogValue::bytes(vec![0xFF, 0x00]);
+        assert_eq!(invalid_utf8.to_json(false).unwrap(), json!("/wA="));
+
+        assert_eq!(
+            BinlogValue::datetime(2021, 12, 31, 23, 59, 59, 500_000)
+                .to_json(false)
+                .unwrap(),
+            json!("2021-12-31T23:59:59.500000")
+        );
+        assert_eq!(
+            BinlogValue::datetime(0, 0, 0, 0, 0, 0, 0)
+                .to_json(false)
+                .unwrap(),
+            json!("0000-00-00")
+        );
+        assert_eq!(
+            BinlogValue::Value(Time(true, 1, 2, 3, 4, 0))
+                .to_json(false)
+                .unwrap(),
+            json!("-026:03:04")
+        );
+
+        let doc = json!({"a": [1, "b", null]});
+        assert_eq!(BinlogValue::json(doc.clone()).to_json(false).unwrap(), doc);
+
+        let diffs = BinlogValue::JsonDiff(vec![
+            JsonDiff::new(
+                &b"$.a"[..],
+                crate::binlog::jsondiff::JsonDiffOperation::REPLACE,
+                Some(json!(1).into()),
+            ),
+            JsonDiff::new(
+                &b"$.b"[..],
+                crate::binlog::jsondiff::JsonDiffOperation::REMOVE,
+                None,
+            ),
+        ]);
+        assert_eq!(
+            diffs.to_json(false).unwrap(),
+            json!([
+                { "path": "$.a", "operation": "REPLACE", "value": 1 },
+                { "path": "$.b", "operation": "REMOVE" },
+            ])
+        );
+
+        let array = BinlogValue::TypedArray(vec![BinlogValue::int(1), BinlogValue::bytes("a")]);
+        assert_eq!(array.to_json(false).unwrap(), json!([1, "a"]));
+
+        let error = BinlogValue::Error(BinlogValueError::new(
+            ColumnType::MYSQL_TYPE_LONG,
+            io::Error::new(io::ErrorKind::InvalidData, "foo"),
+        ));
+        assert!(matches!(
+            error.to_json(false),
+            Err(BinlogValueToValueError::Error(_))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn typed_array_with_nulls() -> io::Result<()> {
+        let cases = [
+            (
+                ColumnType::MYSQL_TYPE_LONG,
+                vec![],
+                // [1, NULL, 3]
+                vec![
+                    0x02, 3, 0, 13, 0, // small array of 3 elements, 13 bytes long
+                    0x05, 1, 0, // inlined INT16
+                    0x04, 0, 0, // inlined NULL literal
+                    0x05, 3, 0, // inlined INT16
+                ],
+                vec![BinlogValue::int(1), BinlogValue::NULL, BinlogValue::int(3)],
+            ),
+            (
+                ColumnType::MYSQL_TYPE_VARCHAR,
+                vec![10, 0],
+                // ['a', NULL, 'b']
+                vec![
+                    0x02, 3, 0, 17, 0, // small array of 3 elements, 17 bytes long
+                    0x0C, 13, 0, // string at offset 13
+                    0x04, 0, 0, // inlined NULL literal
+                    0x0C, 15, 0, // string at offset 15
+                    1, b'a', 1, b'b',
+                ],
+                vec![
+                    BinlogValue::bytes("a"),
+                    BinlogValue::NULL,
+                    BinlogValue::bytes("b"),
+                ],
+            ),
+        ];
+
+        for (elem_type, elem_meta, array, expected) in cases {
+            let mut data = (array.len() as u32).to_le_bytes().to_vec();
+            data.extend(array);
+            let mut col_meta = vec![elem_type as u8];
+            col_meta.extend(elem_meta);
+
+            let ctx = BinlogValueCtx::new(ColumnType::MYSQL_TYPE_TYPED_ARRAY, &col_meta[..]);
+            let mut buf = ParseBuf(&data);
+            let value: BinlogValue = buf.parse(ctx)?;
+            assert!(buf.is_empty());
+            assert_eq!(value, BinlogValue::TypedArray(expected));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn typed_array_of_decimals() -> io::Result<()> {
+        let elements = ["1.50", "-20.05", "12345678.99"]
+            .iter()
+            .map(|x| {
+                let mut data = vec![10, 2];
+                data.extend(decimal_bin(x, 10, 2));
+                jsonb::Value::Opaque(jsonb::OpaqueValue::new(
+                    ColumnType::MYSQL_TYPE_NEWDECIMAL,
+                    data,
+                ))
+            })
+            .collect::<Vec<_>>();
+        let array = jsonb::ComplexValue::from_entries(&[], &elements).unwrap();
+
+        let mut array_bin = Vec::new();
+        jsonb::Value::SmallArray(array).serialize(&mut array_bin);
+        let mut data = (array_bin.len() as u32).to_le_bytes().to_vec();
+        data.extend(array_bin);
+
+        let col_meta = [ColumnType::MYSQL_TYPE_NEWDECIMAL as u8, 10, 2];
+        let ctx = BinlogValueCtx::new(ColumnType::MYSQL_TYPE_TYPED_ARRAY, &col_meta[..]);
+        let mut buf = ParseBuf(&data);
+        assert_eq!(
+            value_len(ctx.col_type(), ctx.col_meta(), buf.0)?,
+            data.len()
+        );
+        let value: BinlogValue = buf.parse(ctx)?;
+        assert!(buf.is_empty());
+        assert_eq!(
+            value,
+            BinlogValue::TypedArray(
+                ["1.50", "-20.05", "12345678.99"]
+                    .iter()
+                    .map(|x| {
+                        let bin = decimal_bin(x, 10, 2);
+                        BinlogValue::Decimal(
+                            decimal::Decimal::read_bin(&bin[..], 10, 2, false).unwrap(),
+                        )
+                    })
+                    .collect()
+            )
+        );
+
+        // metadata as stored within the table map event
+        let (meta, len) = ColumnType::MYSQL_TYPE_TYPED_ARRAY
+            .get_metadata(
+                &[ColumnType::MYSQL_TYPE_NEWDECIMAL as u8, 10, 2, 0xFF],
+                false,
+            )
+            .unwrap();
+        assert_eq!((meta, len), (&col_meta[..], 3));
+
+        Ok(())
+    }
+
+    #[test]
+    fn typed_array_of_varchars() -> io::Result<()> {
+        let elements = ["foo", "", "bar baz"]
+            .iter()
+            .map(|x| jsonb::Value::String(jsonb::JsonbString::new(x.as_bytes())))
+            .collect::<Vec<_>>();
+        let array = jsonb::ComplexValue::from_entries(&[], &elements).unwrap();
+
+        let mut array_bin = Vec::new();
+        jsonb::Value::SmallArray(array).serialize(&mut array_bin);
+        let mut data = (array_bin.len() as u32).to_le_bytes().to_vec();
+        data.extend(array_bin);
+
+        // VARCHAR(32) in utf8mb4 – max length is 128 bytes; element metadata of a VARCHAR
+        // takes three bytes within typed array metadata
+        let col_meta = [ColumnType::MYSQL_TYPE_VARCHAR as u8, 128, 0, 1];
+        let ctx = BinlogValueCtx::new(ColumnType::MYSQL_TYPE_TYPED_ARRAY, &col_meta[..]);
+        let mut buf = ParseBuf(&data);
+        let value: BinlogValue = buf.parse(ctx)?;
+        assert!(buf.is_empty());
+        assert_eq!(
+            value,
+            BinlogValue::TypedArray(vec![
+                BinlogValue::bytes("foo"),
+                BinlogValue::bytes(""),
+                BinlogValue::bytes("bar baz"),
+            ])
+        );
+
+        // element metadata is skipped correctly within the table map event
+        let (meta, len) = ColumnType::MYSQL_TYPE_TYPED_ARRAY
+            .get_metadata(
+                &[ColumnType::MYSQL_TYPE_VARCHAR as u8, 128, 0, 1, 0xFF],
+                false,
+            )
+            .unwrap();
+        assert_eq!((meta, len), (&col_meta[..], 4));
+
+        Ok(())
+    }
+
+    #[test]
+    fn to_string_lossy() {
+        assert_eq!(
+            BinlogValue::bytes(&b"foo\xFFbar"[..]).to_string_lossy(),
+            "foo\u{FFFD}bar"
+        );
+        assert!(matches!(
+            BinlogValue::bytes("foo").to_string_lossy(),
+            Cow::Borrowed("foo")
+        ));
+        assert_eq!(BinlogValue::int(-42).to_string_lossy(), "-42");
+        assert_eq!(BinlogValue::Value(Double(0.5)).to_string_lossy(), "0.5");
+        assert_eq!(BinlogValue::Value(NULL).to_string_lossy(), "NULL");
+        assert_eq!(
+            BinlogValue::datetime(2021, 12, 31, 23, 59, 59, 500_000).to_string_lossy(),
+            "2021-12-31 23:59:59.500000"
+        );
+        assert_eq!(
+            BinlogValue::Value(Time(true, 1, 2, 3, 4, 0)).to_string_lossy(),
+            "-026:03:04"
+        );
+        assert_eq!(
+            BinlogValue::json(serde_json::json!({"a": [1, "b"]})).to_string_lossy(),
+            r#"{"a":[1,"b"]}"#
+        );
+        assert_eq!(
+            BinlogValue::TypedArray(vec![BinlogValue::int(1), BinlogValue::Value(NULL)])
+                .to_string_lossy(),
+            "[1, NULL]"
+        );
+    }
+
+    #[test]
+    fn typed_array_to_json() {
+        let value = BinlogValue::TypedArray(vec![
+            BinlogValue::int(-1),
+            BinlogValue::Value(UInt(u64::MAX)),
+            BinlogValue::Value(NULL),
+            BinlogValue::int(42),
+        ]);
+        assert_eq!(
+            value.typed_array_to_json(),
+            Some(serde_json::json!([-1, u64::MAX, null, 42]))
+        );
+        assert_eq!(
+            BinlogValue::TypedArray(vec![BinlogValue::bytes("1.50")]).typed_array_to_json(),
+            Some(serde_json::json!(["1.50"]))
+        );
+        assert_eq!(BinlogValue::int(1).typed_array_to_json(), None);
+    }
+
+    #[test]
+    fn as_json_number() {
+        assert_eq!(
+            BinlogValue::int(-42).as_json_number(),
+            Some(serde_json::Number::from(-42))
+        );
+        assert_eq!(
+            BinlogValue::Value(UInt(u64::MAX)).as_json_number(),
+            Some(serde_json::Number::from(u64::MAX))
+        );
+        assert_eq!(
+            BinlogValue::Value(Double(0.25)).as_json_number(),
+            serde_json::Number::from_f64(0.25)
+        );
+        assert_eq!(BinlogValue::Value(Double(f64::NAN)).as_json_number(), None);
+        assert_eq!(
+            BinlogValue::bytes("-12.50").as_json_number(),
+            serde_json::Number::from_f64(-12.5)
+        );
+        assert_eq!(BinlogValue::bytes("foo").as_json_number(), None);
+        assert_eq!(BinlogValue::Value(NULL).as_json_number(), None);
+    }
+
+    #[test]
+    fn to_sql_literal() {
+        assert_eq!(BinlogValue::int(-42).to_sql_literal(), "-42");
+        assert_eq!(BinlogValue::bytes("it's").to_sql_literal(), r"'it\'s'");
+        assert_eq!(BinlogValue::Value(NULL).to_sql_literal(), "NULL");
+        assert_eq!(
+            BinlogValue::json(serde_json::json!({"a": "b"})).to_sql_literal(),
+            r#"'{\"a\":\"b\"}'"#
+        );
+        assert_eq!(BinlogValue::JsonDiff(vec![]).to_sql_literal(), "NULL");
+    }
+
+    #[test]
+    fn eq_scalar() {
+        assert!(BinlogValue::int(-1).eq_i64(-1));
+        assert!(!BinlogValue::Value(UInt(u64::MAX)).eq_i64(-1));
+        assert!(BinlogValue::Value(UInt(42)).eq_i64(42));
+        assert!(!BinlogValue::int(1).eq_i64(2));
+        assert!(!BinlogValue::bytes("1").eq_i64(1));
+
+        assert!(BinlogValue::bytes("foo").eq_bytes(b"foo"));
+        assert!(!BinlogValue::bytes("foo").eq_bytes(b"bar"));
+        assert!(!BinlogValue::int(1).eq_bytes(b"1"));
+
+        assert!(BinlogValue::Value(Double(0.5)).eq_f64(0.5));
+        assert!(BinlogValue::Value(Float(0.5)).eq_f64(0.5));
+        assert!(!BinlogValue::Value(Double(0.5)).eq_f64(0.25));
+        assert!(!BinlogValue::Value(Double(f64::NAN)).eq_f64(f64::NAN));
+        assert!(!BinlogValue::int(1).eq_f64(1.0));
+    }
+
+    #[test]
+    fn to_rfc3339() -> io::Result<()> {
+        use ColumnType::*;
+
+        // DATETIME(6) 2021-12-31 23:59:59.500000
+        let packed = ((2021_u64 * 13 + 12) << 5 | 31) << 17 | (23 << 12 | 59 << 6 | 59);
+        let mut data = (packed + 0x80_0000_0000).to_be_bytes()[3..].to_vec();
+        data.extend_from_slice(&500_000_u32.to_be_bytes()[1..]);
+        let ctx = BinlogValueCtx::new(MYSQL_TYPE_DATETIME2, &[6][..]);
+        let datetime: BinlogValue = ParseBuf(&data).parse(ctx)?;
+        assert_eq!(
+            datetime.to_rfc3339(MYSQL_TYPE_DATETIME2).as_deref(),
+            Some("2021-12-31T23:59:59.500000")
+        );
+        assert_eq!(datetime.to_rfc3339(MYSQL_TYPE_TIMESTAMP2), None);
+
+        // TIMESTAMP(6) 2021-12-31 23:59:59.500000 UTC
+        let mut data = 1_640_995_199_u32.to_be_bytes().to_vec();
+        data.extend_from_slice(&500_000_u32.to_be_bytes()[1..]);
+        let ctx = BinlogValueCtx::new(MYSQL_TYPE_TIMESTAMP2, &[6][..]);
+        let timestamp: BinlogValue = ParseBuf(&data).parse(ctx)?;
+        assert_eq!(
+            timestamp.to_rfc3339(MYSQL_TYPE_TIMESTAMP2).as_deref(),
+            Some("2021-12-31T23:59:59.500000Z")
+        );
+        assert_eq!(timestamp.to_rfc3339(MYSQL_TYPE_DATETIME2), None);
+
+        // TIMESTAMP 1970-01-01 00:00:01 UTC
+        let ctx = BinlogValueCtx::new(MYSQL_TYPE_TIMESTAMP, &[][..]);
+        let timestamp: BinlogValue = ParseBuf(&[1, 0, 0, 0]).parse(ctx)?;
+        assert_eq!(
+            timestamp.to_rfc3339(MYSQL_TYPE_TIMESTAMP).as_deref(),
+            Some("1970-01-01T00:00:01.000000Z")
+        );
+
+        assert_eq!(
+            BinlogValue::datetime(0, 0, 0, 0, 0, 0, 0).to_rfc3339(MYSQL_TYPE_DATETIME),
+            None
+        );
+        assert_eq!(BinlogValue::int(0).to_rfc3339(MYSQL_TYPE_TIMESTAMP), None);
+        assert_eq!(BinlogValue::int(1).to_rfc3339(MYSQL_TYPE_LONG), None);
+        assert_eq!(
+            BinlogValue::datetime(2000, 2, 29, 0, 0, 0, 0)
+                .to_rfc3339(MYSQL_TYPE_DATE)
+                .as_deref(),
+            Some("2000-02-29T00:00:00.000000")
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn as_timestamp() -> io::Result<()> {
+        let ctx = BinlogValueCtx::new(ColumnType::MYSQL_TYPE_TIMESTAMP, &[][..]);
+        let timestamp: BinlogValue = ParseBuf(&[0, 0, 0, 0]).parse(ctx)?;
+        assert_eq!(timestamp.as_timestamp(), Some((0, 0)));
+
+        let ctx = BinlogValueCtx::new(ColumnType::MYSQL_TYPE_TIMESTAMP2, &[0][..]);
+        let timestamp: BinlogValue = ParseBuf(&[0, 0, 0, 0]).parse(ctx)?;
+        assert_eq!(timestamp.as_timestamp(), Some((0, 0)));
+
+        // fractional part takes (dec + 1) / 2 bytes and is always scaled to microseconds
+        let secs = 1_640_995_199_u32.to_be_bytes();
+        for (dec, frac, micros) in [
+            (1, &[50][..], 500_000),
+            (2, &[99], 990_000),
+            (3, &[0x04, 0xD2], 123_400),
+            (4, &[0x04, 0xD2], 123_400),
+            (5, &[0x01, 0xE2, 0x40], 123_456),
+            (6, &[0x01, 0xE2, 0x40], 123_456),
+        ] {
+            let mut data = secs.to_vec();
+            data.extend_from_slice(frac);
+            let col_meta = [dec];
+            let ctx = BinlogValueCtx::new(ColumnType::MYSQL_TYPE_TIMESTAMP2, &col_meta[..]);
+            let mut buf = ParseBuf(&data);
+            let timestamp: BinlogValue = buf.parse(ctx)?;
+            assert!(buf.is_empty());
+            assert_eq!(timestamp.as_timestamp(), Some((1_640_995_199, micros)));
+        }
+
+        assert_eq!(BinlogValue::bytes("1.5").as_timestamp(), None);
+        assert_eq!(BinlogValue::bytes("foo").as_timestamp(), None);
+        assert_eq!(BinlogValue::int(-1).as_timestamp(), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn datetime2_fractional_seconds() -> io::Result<()> {
+        // 2021-01-02 03:04:05
+        let intpart = [0x99, 0xA8, 0x84, 0x31, 0x05];
+        for (dec, frac, micros) in [
+            (0, &[][..], 0),
+            (3, &[0x1A, 0x7C], 678_000),
+            (6, &[0x0A, 0x5B, 0xF5], 678_901),
+        ] {
+            let mut data = intpart.to_vec();
+            data.extend_from_slice(frac);
+            let col_meta = [dec];
+            let ctx = BinlogValueCtx::new(ColumnType::MYSQL_TYPE_DATETIME2, &col_meta[..]);
+            let mut buf = ParseBuf(&data);
+            let datetime: BinlogValue = buf.parse(ctx)?;
+            assert!(buf.is_empty());
+            assert_eq!(datetime, BinlogValue::datetime(2021, 1, 2, 3, 4, 5, micros));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn decimal_as_f64() -> io::Result<()> {
+        let bin = decimal_bin("3.14159", 10, 5);
+        let ctx = BinlogValueCtx::new(ColumnType::MYSQL_TYPE_NEWDECIMAL, &[10, 5][..]);
+        let value: BinlogValue = ParseBuf(&bin).parse(ctx)?;
+        assert_eq!(value.decimal_as_f64(), Some(314_159.0 / 100_000.0));
+
+        assert_eq!(BinlogValue::bytes("foo").decimal_as_f64(), None);
+        assert_eq!(BinlogValue::int(3).decimal_as_f64(), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn decimal_as_scaled_i128() -> io::Result<()> {
+        let bin = decimal_bin("123.45", 10, 2);
+        let ctx = BinlogValueCtx::new(ColumnType::MYSQL_TYPE_NEWDECIMAL, &[10, 2][..]);
+        let value: BinlogValue = ParseBuf(&bin).parse(ctx)?;
+        assert_eq!(value.decimal_as_scaled_i128(), Some((12345, 2)));
+
+        let bin = decimal_bin("-0.05", 10, 2);
+        let ctx = BinlogValueCtx::new(ColumnType::MYSQL_TYPE_NEWDECIMAL, &[10, 2][..]);
+        let value: BinlogValue = ParseBuf(&bin).parse(ctx)?;
+        assert_eq!(value.decimal_as_scaled_i128(), Some((-5, 2)));
+
+        assert_eq!(BinlogValue::Value(Int(1)).decimal_as_scaled_i128(), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn is_json_null() -> io::Result<()> {
+        let ctx = BinlogValueCtx::new(ColumnType::MYSQL_TYPE_JSON, &[4][..]);
+
+        // JSONB literal `null`
+        let value: BinlogValue = ParseBuf(&[2, 0, 0, 0, 0x04, 0x00]).parse(ctx)?;
+        assert!(value.is_json_null());
+
+        // JSONB literal `false`
+        let value: BinlogValue = ParseBuf(&[2, 0, 0, 0, 0x04, 0x02]).parse(ctx)?;
+        assert!(!value.is_json_null());
+
+        assert!(!BinlogValue::Value(Value::NULL).is_json_null());
+
+        Ok(())
+    }
+
+    #[test]
+    fn json_literals() -> io::Result<()> {
+        let ctx = BinlogValueCtx::new(ColumnType::MYSQL_TYPE_JSON, &[4][..]);
+
+        let value: BinlogValue = ParseBuf(&[2, 0, 0, 0, 0x04, 0x00]).parse(ctx)?;
+        assert_eq!(value, BinlogValue::Jsonb(jsonb::Value::Null));
+        assert!(value.is_json_null());
+        assert_eq!(value.as_json_bool(), None);
+
+        let value: BinlogValue = ParseBuf(&[2, 0, 0, 0, 0x04, 0x01]).parse(ctx)?;
+        assert_eq!(value, BinlogValue::Jsonb(jsonb::Value::Bool(true)));
+        assert!(!value.is_json_null());
+        assert_eq!(value.as_json_bool(), Some(true));
+
+        let value: BinlogValue = ParseBuf(&[2, 0, 0, 0, 0x04, 0x02]).parse(ctx)?;
+        assert_eq!(value, BinlogValue::Jsonb(jsonb::Value::Bool(false)));
+        assert!(!value.is_json_null());
+        assert_eq!(value.as_json_bool(), Some(false));
+
+        // unknown literal
+        assert!(ParseBuf(&[2, 0, 0, 0, 0x04, 0x03])
+            .parse::<BinlogValue>(ctx)
+            .is_err());
+
+        assert_eq!(BinlogValue::int(1).as_json_bool(), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn json_len() -> io::Result<()> {
+        let ctx = BinlogValueCtx::new(ColumnType::MYSQL_TYPE_JSON, &[4][..]);
+
+        // `{}`
+        let value: BinlogValue = ParseBuf(&[5, 0, 0, 0, 0x00, 0, 0, 4, 0]).parse(ctx)?;
+        assert_eq!(value.json_len(), Some(0));
+
+        // `[1, 2, 3]`
+        let data = [
+            14, 0, 0, 0, 0x02, 3, 0, 13, 0, 0x05, 1, 0, 0x05, 2, 0, 0x05, 3, 0,
+        ];
+        let value: BinlogValue = ParseBuf(&data).parse(ctx)?;
+        assert_eq!(value.json_len(), Some(3));
+
+        // `true`
+        let value: BinlogValue = ParseBuf(&[2, 0, 0, 0, 0x04, 0x01]).parse(ctx)?;
+        assert_eq!(value.json_len(), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn split_time_days() -> io::Result<()> {
+        // 50:00:00
+        let data = 500_000_u32.to_le_bytes();
+        let ctx = BinlogValueCtx::new(ColumnType::MYSQL_TYPE_TIME, &[][..]);
+
+        let value: BinlogValue = ParseBuf(&data[..3]).parse(ctx)?;
+        assert_eq!(value, BinlogValue::Value(Time(false, 0, 50, 0, 0, 0)));
+
+        let options = BinlogValueOptions::new().with_split_time_days(true);
+        let value = BinlogValue::deserialize_with_options(ctx, options, &mut ParseBuf(&data[..3]))?;
+        assert_eq!(value, BinlogValue::Value(Time(false, 2, 2, 0, 0, 0)));
+
+        // TIME2 100:30:00 and -100:30:00
+        let ctx = BinlogValueCtx::new(ColumnType::MYSQL_TYPE_TIME2, &[0][..]);
+        for (data, neg) in [([0x86, 0x47, 0x80], false), ([0x79, 0xB8, 0x80], true)] {
+            let value: BinlogValue = ParseBuf(&data).parse(ctx)?;
+            assert_eq!(value, BinlogValue::Value(Time(neg, 0, 100, 30, 0, 0)));
+
+            let packed = my_time_packed_from_binary(&data[..], 0)?;
+            assert_eq!(time_parts_from_packed(packed), (neg, 100, 30, 0, 0));
+
+            let value = BinlogValue::deserialize_with_options(ctx, options, &mut ParseBuf(&data))?;
+            assert_eq!(value, BinlogValue::Value(Time(neg, 4, 4, 30, 0, 0)));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn into_value_with_charset() {
+        let value = BinlogValue::bytes(&b"caf\xe9 \x80"[..]);
+        assert_eq!(
+            value
+                .clone()
+                .into_value_with_charset(Charset::Latin1)
+                .unwrap(),
+            Bytes("café €".as_bytes().to_vec())
+        );
+        assert_eq!(
+            value
+                .clone()
+                .into_value_with_charset(Charset::Binary)
+                .unwrap(),
+            Bytes(b"caf\xe9 \x80".to_vec())
+        );
+        assert_eq!(
+            value.into_value_with_charset(Charset::Utf8).unwrap(),
+            Bytes("caf\u{FFFD} \u{FFFD}".as_bytes().to_vec())
+        );
+        assert_eq!(
+            BinlogValue::int(42)
+                .into_value_with_charset(Charset::Latin1)
+                .unwrap(),
+            Int(42)
+        );
+        assert_eq!(Charset::from_collation_id(8), Some(Charset::Latin1));
+        assert_eq!(Charset::from_collation_id(255), Some(Charset::Utf8));
+        assert_eq!(
+            Charset::from_collation_id(BINARY_CHARSET),
+            Some(Charset::Binary)
+        );
+    }
+
+    #[test]
+    fn tiny_signedness() -> io::Result<()> {
+        let ctx = BinlogValueCtx::new(ColumnType::MYSQL_TYPE_TINY, &[][..]);
+        let value: BinlogValue = ParseBuf(&[0xFF]).parse(ctx)?;
+        assert_eq!(value, BinlogValue::Value(Int(-1)));
+
+        let ctx = BinlogValueCtx::new(ColumnType::MYSQL_TYPE_TINY, &[][..]).with_unsigned(true);
+        let value: BinlogValue = ParseBuf(&[0xFF]).parse(ctx)?;
+        assert_eq!(value, BinlogValue::Value(Int(255)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn enum_pack_length() -> io::Result<()> {
+        let data = [0x02, 0x01];
+
+        // standalone enum: `[real_type, pack_length]`
+        let ctx = BinlogValueCtx::new(ColumnType::MYSQL_TYPE_ENUM, &[247, 2][..]);
+        let value: BinlogValue = ParseBuf(&data).parse(ctx)?;
+        assert_eq!(value, BinlogValue::Value(Int(0x0102)));
+
+        // enum recovered from a STRING column with the real type byte stripped: `[pack_length]`
+        let ctx = BinlogValueCtx::new(ColumnType::MYSQL_TYPE_ENUM, &[1][..]);
+        let mut buf = ParseBuf(&data);
+        let value: BinlogValue = buf.parse(ctx)?;
+        assert_eq!(value, BinlogValue::Value(Int(2)));
+        assert_eq!(buf.len(), 1);
+
+        let ctx = BinlogValueCtx::new(ColumnType::MYSQL_TYPE_ENUM, &[][..]);
+        let err = ParseBuf(&data).parse::<BinlogValue>(ctx).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        Ok(())
+    }
+
+    #[test]
+    fn negative_and_fractional_time() -> io::Result<()> {
+        // TIME(6) -00:00:01.500000 and 838:59:59.000000
+        let ctx = BinlogValueCtx::new(ColumnType::MYSQL_TYPE_TIME2, &[6][..]);
+        for (neg, h, m, s, us) in [(true, 0, 0, 1, 500_000), (false, 838, 59, 59, 0)] {
+            let mut data = Vec::new();
+            let packed = time_packed_from_parts(neg, h, m, s, us);
+            my_time_packed_to_binary(&mut data, packed, 6)?;
+            assert_eq!(time_parts_from_packed(packed), (neg, h, m, s, us));
+
+            let value: BinlogValue = ParseBuf(&data).parse(ctx)?;
+            let (d, h) = if h > u8::MAX as u32 {
+                (h / 24, h % 24)
+            } else {
+                (0, h)
+            };
+            assert_eq!(value, BinlogValue::Value(Time(neg, d, h as u8, m, s, us)));
+        }
+
+        // legacy TIME -00:00:01 and -838:59:59
+        let ctx = BinlogValueCtx::new(ColumnType::MYSQL_TYPE_TIME, &[][..]);
+        for (raw, expected) in [
+            (-1_i32, Time(true, 0, 0, 0, 1, 0)),
+            (-8_385_959, Time(true, 34, 22, 59, 59, 0)),
+        ] {
+            let data = raw.to_le_bytes();
+            let value: BinlogValue = ParseBuf(&data[..3]).parse(ctx)?;
+            assert_eq!(value, BinlogValue::Value(expected));
+
+            let mut output = Vec::new();
+            value.serialize(ctx, &mut output)?;
+            assert_eq!(output, &data[..3]);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn display() {
+        use crate::binlog::jsondiff::JsonDiffOperation;
+
+        let cases = [
+            (BinlogValue::NULL, "NULL"),
+            (BinlogValue::int(-42), "-42"),
+            (BinlogValue::Value(UInt(u64::MAX)), "18446744073709551615"),
+            (BinlogValue::Value(Double(0.5)), "0.5"),
+            (
+                BinlogValue::Decimal(decimal::Decimal::from_str("-0012.50").unwrap()),
+                "-12.50",
+            ),
+            (BinlogValue::bytes("it's"), r"'it\'s'"),
+            (BinlogValue::bytes(vec![0x0A, 0xFF]), "0x0AFF"),
+            (BinlogValue::bytes(""), "''"),
+            (
+                BinlogValue::datetime(2021, 12, 31, 23, 59, 59, 0),
+                "2021-12-31 23:59:59",
+            ),
+            (
+                BinlogValue::datetime(2021, 12, 31, 23, 59, 59, 500_000),
+                "2021-12-31 23:59:59.500000",
+            ),
+            (BinlogValue::Value(Time(true, 1, 2, 3, 4, 0)), "-026:03:04"),
+            (
+                BinlogValue::json(serde_json::json!({"a": [1, "b", null]})),
+                r#"{"a":[1,"b",null]}"#,
+            ),
+            (
+                BinlogValue::JsonDiff(vec![
+                    JsonDiff::new(
+                        &b"$.a"[..],
+                        JsonDiffOperation::REPLACE,
+                        Some(serde_json::json!({"b": 1}).into()),
+                    ),
+                    JsonDiff::new(&b"$.c"[..], JsonDiffOperation::REMOVE, None),
+                ]),
+                r#"REPLACE $.a = {"b":1}; REMOVE $.c"#,
+            ),
+            (
+                BinlogValue::TypedArray(vec![
+                    BinlogValue::int(1),
+                    BinlogValue::NULL,
+                    BinlogValue::bytes("a"),
+                ]),
+                "[1, NULL, 'a']",
+            ),
+        ];
+        for (value, expected) in cases.iter() {
+            assert_eq!(value.to_string(), *expected);
+        }
+
+        let error = BinlogValue::Error(BinlogValueError::new(
+            ColumnType::MYSQL_TYPE_LONG,
+            io::Error::new(io::ErrorKind::InvalidData, "foo"),
+        ));
+        assert!(error.to_string().contains("foo"));
+    }
+
+    #[test]
+    fn typed_accessors() {
+        let decimal = BinlogValue::Decimal(decimal::Decimal::from_str("-1.50").unwrap());
+        let json = BinlogValue::json(serde_json::json!(1));
+        let diffs = BinlogValue::JsonDiff(vec![]);
+
+        // (value, as_i64, as_u64, as_f64, as_str)
+        let cases = [
+            (BinlogValue::int(-1), Some(-1), None, Some(-1.0), None),
+            (
+                BinlogValue::Value(UInt(u64::MAX)),
+                None,
+                Some(u64::MAX),
+                Some(u64::MAX as f64),
+                None,
+            ),
+            (
+                BinlogValue::Value(Int(1_640_995_199)),
+                Some(1_640_995_199),
+                Some(1_640_995_199),
+                Some(1_640_995_199.0),
+                None,
+            ),
+            (BinlogValue::Value(Float(0.5)), None, None, Some(0.5), None),
+            (
+                BinlogValue::Value(Double(0.25)),
+                None,
+                None,
+                Some(0.25),
+                None,
+            ),
+            (decimal, None, None, Some(-1.5), Some("-1.50")),
+            (BinlogValue::bytes("foo"), None, None, None, Some("foo")),
+            (
+                BinlogValue::bytes("1640995199.500000"),
+                None,
+                None,
+                None,
+                Some("1640995199.500000"),
+            ),
+            (BinlogValue::bytes(vec![0xFF]), None, None, None, None),
+            (
+                BinlogValue::datetime(2021, 12, 31, 0, 0, 0, 0),
+                None,
+                None,
+                None,
+                None,
+            ),
+            (BinlogValue::NULL, None, None, None, None),
+            (json, None, None, None, None),
+            (diffs, None, None, None, None),
+        ];
+        for (value, i, u, f, s) in cases.iter() {
+            assert_eq!(value.as_i64(), *i, "{:?}", value);
+            assert_eq!(value.as_u64(), *u, "{:?}", value);
+            assert_eq!(value.as_f64(), *f, "{:?}", value);
+            assert_eq!(value.as_str().as_deref(), *s, "{:?}", value);
+        }
+
+        assert_eq!(BinlogValue::bytes(vec![0xFF]).as_bytes(), Some(&[0xFF][..]));
+        assert_eq!(BinlogValue::int(1).as_bytes(), None);
+        assert_eq!(BinlogValue::json(serde_json::json!("foo")).as_bytes(), None);
+    }
+
+    #[test]
+    fn blob_prefix() {
+        let value = BinlogValue::bytes("foobar");
+        assert_eq!(value.blob_prefix(3), Some(&b"foo"[..]));
+        assert_eq!(value.blob_prefix(0), Some(&b""[..]));
+        assert_eq!(value.blob_prefix(100), Some(&b"foobar"[..]));
+        assert_eq!(BinlogValue::int(1).blob_prefix(3), None);
+        assert_eq!(BinlogValue::NULL.blob_prefix(3), None);
+    }
+
+    #[test]
+    fn deserialize_row() -> io::Result<()> {
+        use ColumnType::*;
+
+        let col_types = [
+            MYSQL_TYPE_TINY,
+            MYSQL_TYPE_LONG,
+            MYSQL_TYPE_VARCHAR,
+            MYSQL_TYPE_TINY,
+        ];
+        let metas: [&[u8]; 4] = [&[], &[], &[10, 0], &[]];
+        // the second column is absent, so the null bitmap is about columns 0, 2 and 3
+        let cols = BitSlice::<u8>::from_slice(&[0b1101]);
+        let null_bitmap = BitSlice::<u8>::from_slice(&[0b010]);
+
+        let mut buf = ParseBuf(b"\x01\x02");
+        let values = BinlogValue::deserialize_row(&col_types, &metas, cols, null_bitmap, &mut buf)?;
+        assert!(buf.is_empty());
+        assert_eq!(
+            values,
+            vec![
+                Some(BinlogValue::int(1)),
+                None,
+                Some(BinlogValue::NULL),
+                Some(BinlogValue::int(2)),
+            ]
+        );
+        assert!(values[2].as_ref().unwrap().is_null());
+        assert!(!values[3].as_ref().unwrap().is_null());
+
+        let err =
+            BinlogValue::deserialize_row(&col_types, &metas[..3], cols, null_bitmap, &mut buf)
+                .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+
+        Ok(())
+    }
+
+    #[test]
+    fn var_string() -> io::Result<()> {
+        use ColumnType::*;
+
+        // table maps carry no metadata for the legacy VAR_STRING
+        let ctx = BinlogValueCtx::new(MYSQL_TYPE_VAR_STRING, &[][..]);
+        assert_eq!(
+            MYSQL_TYPE_VAR_STRING.get_metadata(&[10, 0], false),
+            Some((&[][..], 0))
+        );
+
+        let mut buf = ParseBuf(b"\x03abc\x00");
+        assert_eq!(buf.parse::<BinlogValue>(ctx)?, BinlogValue::bytes("abc"));
+        assert_eq!(buf.parse::<BinlogValue>(ctx)?, BinlogValue::bytes(""));
+        assert!(buf.is_empty());
+        assert_eq!(
+            super::binlog_value_len(ctx.col_type(), ctx.col_meta(), false, &ParseBuf(b"\x03abc"))?,
+            4
+        );
+
+        let mut output = Vec::new();
+        BinlogValue::bytes("abc").serialize(ctx, &mut output)?;
+        assert_eq!(output, b"\x03abc");
+
+        // otherwise it is encoded just like VARCHAR
+        for meta in [&[10, 0][..], &[0x2C, 0x01][..]] {
+            let mut varchar = Vec::new();
+            let mut var_string = Vec::new();
+            BinlogValue::bytes("abc")
+                .serialize(BinlogValueCtx::new(MYSQL_TYPE_VARCHAR, meta), &mut varchar)?;
+            BinlogValue::bytes("abc").serialize(
+                BinlogValueCtx::new(MYSQL_TYPE_VAR_STRING, meta),
+                &mut var_string,
+            )?;
+            assert_eq!(varchar, var_string);
+            let value: BinlogValue =
+                ParseBuf(&var_string).parse(BinlogValueCtx::new(MYSQL_TYPE_VAR_STRING, meta))?;
+            assert_eq!(value, BinlogValue::bytes("abc"));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn year() -> io::Result<()> {
+        let ctx = BinlogValueCtx::new(ColumnType::MYSQL_TYPE_YEAR, &[][..]);
+        for (byte, year) in [(1, "1901"), (121, "2021"), (255, "2155"), (0, "0000")] {
+            let data = [byte];
+            let value: BinlogValue = ParseBuf(&data).parse(ctx)?;
+            assert_eq!(value, BinlogValue::bytes(year));
+
+            let mut output = Vec::new();
+            value.serialize(ctx, &mut output)?;
+            assert_eq!(output, [byte]);
+        }
+
+        for year in ["1900", "2156"] {
+            let err = BinlogValue::bytes(year)
+                .serialize(ctx, &mut Vec::new())
+                .unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn time_out_of_range() -> io::Result<()> {
+        // 838:60:00 (legacy TIME is a signed 24-bit integer, so it can't hold 900:00:00)
+        let data = 8_386_000_u32.to_le_bytes();
+        let ctx = BinlogValueCtx::new(ColumnType::MYSQL_TYPE_TIME, &[][..]);
+
+        let err = ParseBuf(&data[..3]).parse::<BinlogValue>(ctx).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        let options = BinlogValueOptions::new().with_time_out_of_range(TimeOutOfRange::Clamp);
+        let value = BinlogValue::deserialize_with_options(ctx, options, &mut ParseBuf(&data[..3]))?;
+        assert_eq!(value, BinlogValue::Value(Time(false, 34, 22, 59, 59, 0)));
+
+        // 838:59:59 is the maximum valid value
+        let data = 8_385_959_u32.to_le_bytes();
+        let value: BinlogValue = ParseBuf(&data[..3]).parse(ctx)?;
+        assert_eq!(value, BinlogValue::Value(Time(false, 34, 22, 59, 59, 0)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn uint_overflow() -> io::Result<()> {
+        use ColumnType::*;
+
+        let max = u64::MAX.to_le_bytes();
+        for options in [
+            BinlogValueOptions::new(),
+            BinlogValueOptions::new().with_uint_overflow(UIntOverflow::Error),
+        ] {
+            for (col_type, expected) in [
+                (MYSQL_TYPE_TINY, u8::MAX as i64),
+                (MYSQL_TYPE_SHORT, u16::MAX as i64),
+                (MYSQL_TYPE_INT24, 0xFFFFFF),
+                (MYSQL_TYPE_LONG, u32::MAX as i64),
+            ] {
+                let ctx = BinlogValueCtx::new(col_type, &[][..]).with_unsigned(true);
+                let value =
+                    BinlogValue::deserialize_with_options(ctx, options, &mut ParseBuf(&max))?;
+                assert_eq!(value, BinlogValue::Value(Int(expected)), "{:?}", col_type);
+                let ctx = BinlogValueCtx::new(col_type, &[][..]);
+                let value =
+                    BinlogValue::deserialize_with_options(ctx, options, &mut ParseBuf(&max))?;
+                assert_eq!(value, BinlogValue::Value(Int(-1)), "{:?}", col_type);
+            }
+        }
+
+        let ctx = BinlogValueCtx::new(MYSQL_TYPE_LONGLONG, &[][..]).with_unsigned(true);
+        let value: BinlogValue = ParseBuf(&max).parse(ctx)?;
+        assert_eq!(value, BinlogValue::Value(UInt(u64::MAX)));
+
+        let options = BinlogValueOptions::new().with_uint_overflow(UIntOverflow::Error);
+        let err =
+            BinlogValue::deserialize_with_options(ctx, options, &mut ParseBuf(&max)).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        let small = (i64::MAX as u64).to_le_bytes();
+        let value = BinlogValue::deserialize_with_options(ctx, options, &mut ParseBuf(&small))?;
+        assert_eq!(value, BinlogValue::Value(Int(i64::MAX)));
+
+        let options = BinlogValueOptions::new().with_uint_overflow(UIntOverflow::Saturate);
+        let value = BinlogValue::deserialize_with_options(ctx, options, &mut ParseBuf(&max))?;
+        assert_eq!(value, BinlogValue::Value(Int(i64::MAX)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn serialize_round_trip() -> io::Result<()> {
+        use ColumnType::*;
+
+        let datetime = ((2021_u64 * 13 + 12) << 5 | 31) << 17 | (23 << 12 | 59 << 6 | 59);
+        let datetime = (datetime + 0x80_0000_0000).to_be_bytes()[3..].to_vec();
+        let mut json = Vec::new();
+        jsonb::Value::from(serde_json::json!({"a": [1, "b", null]})).serialize(&mut json);
+        let json = [&(json.len() as u32).to_le_bytes()[..], &json[..]].concat();
+        // REPLACE `$.a` with `1`
+        let json_diff = [9, 0, 0, 0, 0, 3, b'$', b'.', b'a', 3, 0x05, 0x01, 0x00];
+        let cases: Vec<(BinlogValueCtx<'_>, Vec<u8>)> = vec![
+            (BinlogValueCtx::new(MYSQL_TYPE_TINY, &[]), vec![0xFE]),
+            (
+                BinlogValueCtx::new(MYSQL_TYPE_TINY, &[]).with_unsigned(true),
+                vec![0xFE],
+            ),
+            (BinlogValueCtx::new(MYSQL_TYPE_SHORT, &[]), vec![0x34, 0x92]),
+            (
+                BinlogValueCtx::new(MYSQL_TYPE_INT24, &[]),
+                vec![0xFF, 0xFF, 0xFF],
+            ),
+            (
+                BinlogValueCtx::new(MYSQL_TYPE_INT24, &[]).with_unsigned(true),
+                vec![0xFF, 0xFF, 0xFF],
+            ),
+            (
+                BinlogValueCtx::new(MYSQL_TYPE_LONG, &[]),
+                vec![1, 2, 3, 0x84],
+            ),
+            (BinlogValueCtx::new(MYSQL_TYPE_LONGLONG, &[]), vec![0xFF; 8]),
+            (
+                BinlogValueCtx::new(MYSQL_TYPE_LONGLONG, &[]).with_unsigned(true),
+                vec![0xFF; 8],
+            ),
+            (
+                BinlogValueCtx::new(MYSQL_TYPE_FLOAT, &[4]),
+                1.5_f32.to_le_bytes().to_vec(),
+            ),
+            (
+                BinlogValueCtx::new(MYSQL_TYPE_DOUBLE, &[8]),
+                (-2.25_f64).to_le_bytes().to_vec(),
+            ),
+            (
+                BinlogValueCtx::new(MYSQL_TYPE_TIMESTAMP, &[]),
+                1_640_995_199_u32.to_le_bytes().to_vec(),
+            ),
+            (
+                BinlogValueCtx::new(MYSQL_TYPE_TIME, &[]),
+                8_385_959_u32.to_le_bytes()[..3].to_vec(),
+            ),
+            (
+                BinlogValueCtx::new(MYSQL_TYPE_DATETIME, &[]),
+                20_211_231_235_959_u64.to_le_bytes().to_vec(),
+            ),
+            (BinlogValueCtx::new(MYSQL_TYPE_YEAR, &[]), vec![121]),
+            (
+                BinlogValueCtx::new(MYSQL_TYPE_NEWDATE, &[]),
+                vec![0x9F, 0xCB, 0x0F],
+            ),
+            (
+                BinlogValueCtx::new(MYSQL_TYPE_BIT, &[2, 1]),
+                vec![0x02, 0xFF],
+            ),
+            (
+                BinlogValueCtx::new(MYSQL_TYPE_TIMESTAMP2, &[0]),
+                vec![0x61, 0xCF, 0x99, 0x7F],
+            ),
+            (
+                BinlogValueCtx::new(MYSQL_TYPE_TIMESTAMP2, &[3]),
+                vec![0x61, 0xCF, 0x99, 0x7F, 0x01, 0xF4],
+            ),
+            (
+                BinlogValueCtx::new(MYSQL_TYPE_DATETIME2, &[0]),
+                datetime.clone(),
+            ),
+            (
+                BinlogValueCtx::new(MYSQL_TYPE_DATETIME2, &[2]),
+                [&datetime[..], &[50]].concat(),
+            ),
+            (
+                BinlogValueCtx::new(MYSQL_TYPE_DATETIME2, &[6]),
+                [&datetime[..], &[0x07, 0xA1, 0x20]].concat(),
+            ),
+            (
+                BinlogValueCtx::new(MYSQL_TYPE_TIME2, &[0]),
+                vec![0x79, 0xB8, 0x80],
+            ),
+            (
+                BinlogValueCtx::new(MYSQL_TYPE_TIME2, &[2]),
+                vec![0x79, 0xB8, 0x80, 0xCE],
+            ),
+            (
+                BinlogValueCtx::new(MYSQL_TYPE_TIME2, &[4]),
+                vec![0x80, 0x10, 0x00, 0x13, 0x88],
+            ),
+            (
+                BinlogValueCtx::new(MYSQL_TYPE_TIME2, &[6]),
+                vec![0x7F, 0xEF, 0xFF, 0xF8, 0x5E, 0xE0],
+            ),
+            (BinlogValueCtx::new(MYSQL_TYPE_JSON, &[4]), json),
+            (
+                BinlogValueCtx::new(MYSQL_TYPE_JSON, &[4]).with_partial(true),
+                json_diff.to_vec(),
+            ),
+            (
+                BinlogValueCtx::new(MYSQL_TYPE_NEWDECIMAL, &[10, 2]),
+                decimal_bin("-12.50", 10, 2),
+            ),
+            (
+                BinlogValueCtx::new(MYSQL_TYPE_NEWDECIMAL, &[10, 2]),
+                decimal_bin("0.05", 10, 2),
+            ),
+            (
+                BinlogValueCtx::new(MYSQL_TYPE_NEWDECIMAL, &[65, 30]),
+                decimal_bin(&format!("1.{:0<30}", 5), 65, 30),
+            ),
+            (BinlogValueCtx::new(MYSQL_TYPE_ENUM, &[1]), vec![3]),
+            (
+                BinlogValueCtx::new(MYSQL_TYPE_ENUM, &[247, 2]),
+                vec![0x01, 0x01],
+            ),
+            (
+                BinlogValueCtx::new(MYSQL_TYPE_SET, &[248, 2]),
+                vec![0x03, 0x00],
+            ),
+            (
+                BinlogValueCtx::new(MYSQL_TYPE_TINY_BLOB, &[1]),
+                b"\x03abc".to_vec(),
+            ),
+            (
+                BinlogValueCtx::new(MYSQL_TYPE_BLOB, &[2]),
+                b"\x03\x00abc".to_vec(),
+            ),
+            (
+                BinlogValueCtx::new(MYSQL_TYPE_MEDIUM_BLOB, &[3]),
+                b"\x03\x00\x00abc".to_vec(),
+            ),
+            (
+                BinlogValueCtx::new(MYSQL_TYPE_LONG_BLOB, &[4]),
+                b"\x03\x00\x00\x00abc".to_vec(),
+            ),
+            (
+                BinlogValueCtx::new(MYSQL_TYPE_GEOMETRY, &[4]),
+                b"\x02\x00\x00\x00ab".to_vec(),
+            ),
+            (
+                BinlogValueCtx::new(MYSQL_TYPE_VARCHAR, &[10, 0]),
+                b"\x03abc".to_vec(),
+            ),
+            (
+                BinlogValueCtx::new(MYSQL_TYPE_VARCHAR, &[0x2C, 0x01]),
+                b"\x03\x00abc".to_vec(),
+            ),
+            (
+                BinlogValueCtx::new(MYSQL_TYPE_VAR_STRING, &[10, 0]),
+                b"\x03abc".to_vec(),
+            ),
+            (
+                BinlogValueCtx::new(MYSQL_TYPE_STRING, &[0xFE, 10]),
+                b"\x03abc".to_vec(),
+            ),
+            (
+                BinlogValueCtx::new(MYSQL_TYPE_STRING, &[0xEE, 44]),
+                b"\x03\x00abc".to_vec(),
+            ),
+        ];
+
+        for &column_type in super::supported_binlog_column_types() {
+            if column_type != MYSQL_TYPE_TYPED_ARRAY {
+                assert!(
+                    cases.iter().any(|(ctx, _)| ctx.col_type() == column_type),
+                    "{:?}",
+                    column_type
+                );
+            }
+        }
+
+        for (ctx, data) in cases {
+            let col_type = ctx.col_type();
+            let mut buf = ParseBuf(&data);
+            let value: BinlogValue = buf.parse(ctx)?;
+            assert!(buf.is_empty(), "{:?}", col_type);
+            let mut output = Vec::new();
+            value.serialize(ctx, &mut output)?;
+            assert_eq!(output, data, "{:?} {:?}", col_type, value);
+        }
+
+        let ctx = BinlogValueCtx::new(MYSQL_TYPE_VARCHAR, &[10, 0][..]);
+        let err = BinlogValue::int(1)
+            .serialize(ctx, &mut Vec::new())
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        let err = BinlogValue::bytes("x".repeat(256))
+            .serialize(ctx, &mut Vec::new())
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+        let ctx = BinlogValueCtx::new(MYSQL_TYPE_NEWDECIMAL, &[4, 2][..]);
+        let err = BinlogValue::bytes("123.45")
+            .serialize(ctx, &mut Vec::new())
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+
+        Ok(())
+    }
+
+    #[test]
+    fn supported_binlog_column_types() {
+        // zero value of temporal types
+        let data = [0x80_u8; 64];
+        for column_type in (0..=u8::MAX).filter_map(|x| ColumnType::try_from(x).ok()) {
+            let ctx = BinlogValueCtx::new(column_type, &[4, 2][..]);
+            let result = ParseBuf(&data).parse::<BinlogValue>(ctx);
+            let is_supported = match result {
+                Ok(_) => true,
+                Err(err) => err.to_string() != "Don't know how to handle column",
+            };
+            assert_eq!(
+                is_supported,
+                super::supported_binlog_column_types().contains(&column_type),
+                "{:?}",
+                column_type
+            );
+            assert_eq!(
+                is_supported,
+                value_len(column_type, &[4, 2], &data).is_ok(),
+                "{:?}",
+                column_type
+            );
+        }
+    }
+
+    #[test]
+    fn timestamp2_epoch() -> io::Result<()> {
+        let ctx = BinlogValueCtx::new(ColumnType::MYSQL_TYPE_TIMESTAMP2, &[0][..]);
+        let value: BinlogValue = ParseBuf(&[0, 0, 0, 0]).parse(ctx)?;
+        assert_eq!(value, BinlogValue::bytes("0"));
+
+        let ctx = BinlogValueCtx::new(ColumnType::MYSQL_TYPE_TIMESTAMP2, &[6][..]);
+        let value: BinlogValue = ParseBuf(&[0, 0, 0, 0, 0, 0, 0]).parse(ctx)?;
+        assert_eq!(value, BinlogValue::bytes("0"));
+        let value: BinlogValue = ParseBuf(&[0, 0, 0, 0, 0, 0, 1]).parse(ctx)?;
+        assert_eq!(value, BinlogValue::bytes("0.000001"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn scan() -> io::Result<()> {
+        let mut data = vec![42, 0, 0, 0, 3, b'a', b'b', b'c'];
+        data.extend(decimal_bin("1.50", 10, 2));
+        data.extend([2, 0, b'x', b'y']);
+
+        let mut buf = ParseBuf(&data);
+        let ctxs = [
+            BinlogValueCtx::new(ColumnType::MYSQL_TYPE_LONG, &[][..]),
+            BinlogValueCtx::new(ColumnType::MYSQL_TYPE_VARCHAR, &[10, 0][..]),
+            BinlogValueCtx::new(ColumnType::MYSQL_TYPE_NEWDECIMAL, &[10, 2][..]),
+            BinlogValueCtx::new(ColumnType::MYSQL_TYPE_BLOB, &[2][..]),
+        ];
+        let raw = ctxs
+            .iter()
+            .map(|ctx| BinlogValue::scan(*ctx, &mut buf))
+            .collect::<io::Result<Vec<_>>>()?;
+        assert!(buf.is_empty());
+
+        assert_eq!(raw[0].data(), &[42, 0, 0, 0]);
+        assert_eq!(raw[1].data(), b"\x03abc");
+        assert_eq!(raw[3].data(), b"\x02\x00xy");
+        assert_eq!(raw[1].to_value()?, BinlogValue::bytes("abc"));
+
+        assert_eq!(
+            BinlogValue::scan(ctxs[1], &mut ParseBuf(&data[4..7]))
+                .unwrap_err()
+                .kind(),
+            io::ErrorKind::UnexpectedEof
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn packed_temporal_raw() -> io::Result<()> {
+        let time = [0x7F, 0xFE, 0xFD, 0xF3, 0xE8];
+        let datetime = [0x99, 0xA8, 0x84, 0x31, 0x05, 0x0A, 0x5B, 0xF5];
+        let timestamp = [0x61, 0xCF, 0x99, 0x7F, 0x13, 0x88];
+        let cases: Vec<(ColumnType, u8, &[u8])> = vec![
+            (ColumnType::MYSQL_TYPE_TIME2, 3, &time),
+            (ColumnType::MYSQL_TYPE_DATETIME2, 6, &datetime),
+            (ColumnType::MYSQL_TYPE_DATETIME2, 0, &datetime[..5]),
+            (ColumnType::MYSQL_TYPE_TIMESTAMP2, 4, &timestamp),
+        ];
+
+        for (col_type, dec, data) in cases {
+            let col_meta = [dec];
+            let ctx = BinlogValueCtx::new(col_type, &col_meta[..]);
+            let raw = BinlogValue::scan(ctx, &mut ParseBuf(data))?;
+            let (ty, packed, raw_dec) = raw.packed_temporal_raw().unwrap();
+            assert_eq!((ty, raw_dec), (col_type, dec));
+
+            let mut output = Vec::new();
+            match col_type {
+                ColumnType::MYSQL_TYPE_TIME2 => {
+                    my_time_packed_to_binary(&mut output, packed, dec as u32)?
+                }
+                ColumnType::MYSQL_TYPE_DATETIME2 => {
+                    my_datetime_packed_to_binary(&mut output, packed, dec as u32)?
+                }
+                _ => my_timestamp_to_binary(
+                    &mut output,
+                    my_packed_time_get_int_part(packed) as u32,
+                    my_packed_time_get_frac_part(packed) as u32,
+                    dec,
+                )?,
+            }
+            assert_eq!(output, data);
+        }
+
+        let ctx = BinlogValueCtx::new(ColumnType::MYSQL_TYPE_LONG, &[][..]);
+        let raw = BinlogValue::scan(ctx, &mut ParseBuf(&[1, 0, 0, 0]))?;
+        assert_eq!(raw.packed_temporal_raw(), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn deserialize_at() {
+        let ctx_long = BinlogValueCtx::new(ColumnType::MYSQL_TYPE_LONG, &[][..]);
+        let ctx_datetime2 = BinlogValueCtx::new(ColumnType::MYSQL_TYPE_DATETIME2, &[6][..]);
+
+        // a LONG followed by a truncated DATETIME(6)
+        let data = [42, 0, 0, 0, 0x80, 0, 0, 0];
+        let mut offset = 0;
+        let value = BinlogValue::deserialize_at(ctx_long, &data, &mut offset).unwrap();
+        assert_eq!(value, BinlogValue::int(42));
+        assert_eq!(offset, 4);
+
+        let err = BinlogValue::deserialize_at(ctx_datetime2, &data, &mut offset).unwrap_err();
+        assert_eq!(err.offset, 4);
+        assert_eq!(err.column_type, ColumnType::MYSQL_TYPE_DATETIME2);
+        assert_eq!(err.error.kind(), io::ErrorKind::UnexpectedEof);
+        assert!(err
+            .to_string()
+            .starts_with("failed at byte 4 parsing MYSQL_TYPE_DATETIME2"));
+        assert_eq!(offset, 4);
+
+        let err = io::Error::from(err);
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn decimal_formatted() {
+        let value = BinlogValue::bytes("1234567.89");
+        assert_eq!(value.decimal_formatted(','), Some("1,234,567.89".into()));
+        assert_eq!(value.decimal_formatted(' '), Some("1 234 567.89".into()));
+
+        assert_eq!(
+            BinlogValue::bytes("-123456").decimal_formatted(','),
+            Some("-123,456".into())
+        );
+        assert_eq!(
+            BinlogValue::bytes("999.5").decimal_formatted(','),
+            Some("999.5".into())
+        );
+        assert_eq!(BinlogValue::bytes("abc").decimal_formatted(','), None);
+        assert_eq!(BinlogValue::int(1000).decimal_formatted(','), None);
+    }
+
+    #[test]
+    fn long_char_length_prefix() -> io::Result<()> {
+        // CHAR whose max byte length is 256 (e.g. `CHAR(64)` in utf8mb4):
+        // the high bits of the length are xored into the real type byte
+        let col_meta = [(ColumnType::MYSQL_TYPE_STRING as u8 & !0x30) | 0x20, 0];
+        assert_eq!(string_max_len(&col_meta), 256);
+
+        let ctx = BinlogValueCtx::new(ColumnType::MYSQL_TYPE_STRING, &col_meta[..]);
+        let data = [3, 0, b'a', b'b', b'c'];
+        let value: BinlogValue = ParseBuf(&data).parse(ctx)?;
+        assert_eq!(value, BinlogValue::bytes("abc"));
+        assert_eq!(BinlogValue::scan(ctx, &mut ParseBuf(&data))?.data(), &data);
+
+        // CHAR whose max byte length is 255 still uses 1-byte prefix
+        let col_meta = [ColumnType::MYSQL_TYPE_STRING as u8, 255];
+        let ctx = BinlogValueCtx::new(ColumnType::MYSQL_TYPE_STRING, &col_meta[..]);
+        let value: BinlogValue = ParseBuf(&data[1..]).parse(ctx)?;
+        assert_eq!(value, BinlogValue::bytes(""));
+
+        Ok(())
+    }
+
+    #[test]
+    fn char_max_len() -> io::Result<()> {
+        use crate::binlog::schema::ColumnSchema;
+
+        // metadata as written by `Field_string::save_field_metadata`
+        let col_meta = |length: usize| {
+            [
+                ColumnType::MYSQL_TYPE_STRING as u8 ^ ((length & 0x300) >> 4) as u8,
+                length as u8,
+            ]
+        };
+
+        // CHAR(64) in latin1 and utf8mb4, CHAR(85) and CHAR(255) in utf8mb4
+        for (length, prefix_len) in [(64, 1), (255, 1), (256, 2), (340, 2), (1020, 2)] {
+            let col_meta = col_meta(length);
+            assert_eq!(string_max_len(&col_meta), length);
+            let schema = ColumnSchema::from_json(&serde_json::json!({
+                "type": "char",
+                "length": length,
+            }))?;
+            assert_eq!(schema.column_meta(), &col_meta[..]);
+
+            let mut data = vec![3, 0];
+            data.truncate(prefix_len);
+            data.extend_from_slice(b"abc\xFF");
+            let ctx = BinlogValueCtx::new(ColumnType::MYSQL_TYPE_STRING, &col_meta[..]);
+            let mut buf = ParseBuf(&data);
+            let value: BinlogValue = buf.parse(ctx)?;
+            assert_eq!(value, BinlogValue::bytes("abc"));
+            assert_eq!(buf.0, b"\xFF");
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn zero_byte0_string_length() -> io::Result<()> {
+        // if the first metadata byte is zero, then the max length is read from the metadata
+        // as a little-endian u16
+        let col_meta = [0, 1];
+        assert_eq!(string_max_len(&col_meta), 256);
+
+        let ctx = BinlogValueCtx::new(ColumnType::MYSQL_TYPE_STRING, &col_meta[..]);
+        let data = [2, 0, b'a', b'b'];
+        let mut buf = ParseBuf(&data);
+        let value: BinlogValue = buf.parse(ctx)?;
+        assert!(buf.is_empty());
+        assert_eq!(value, BinlogValue::bytes("ab"));
+
+        // short length still uses 1-byte prefix
+        let col_meta = [0, 0];
+        assert_eq!(string_max_len(&col_meta), 0);
+        let ctx = BinlogValueCtx::new(ColumnType::MYSQL_TYPE_STRING, &col_meta[..]);
+        let mut buf = ParseBuf(&[1, b'x']);
+        let value: BinlogValue = buf.parse(ctx)?;
+        assert!(buf.is_empty());
+        assert_eq!(value, BinlogValue::bytes("x"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn short_value_of_long_varchar() -> io::Result<()> {
+        // VARCHAR(300) uses a 2-byte length prefix regardless of the stored value length
+        let col_meta = 300_u16.to_le_bytes();
+        let ctx = BinlogValueCtx::new(ColumnType::MYSQL_TYPE_VARCHAR, &col_meta[..]);
+        let data = [5, 0, b'h', b'e', b'l', b'l', b'o', 42, 0, 0, 0];
+        let mut buf = ParseBuf(&data);
+        let value: BinlogValue = buf.parse(ctx)?;
+        assert_eq!(value, BinlogValue::bytes("hello"));
+
+        // the next column isn't corrupted
+        let value: BinlogValue =
+            buf.parse(BinlogValueCtx::new(ColumnType::MYSQL_TYPE_LONG, &[][..]))?;
+        assert!(buf.is_empty());
+        assert_eq!(value, BinlogValue::int(42));
+
+        // VARCHAR(255) uses a 1-byte length prefix
+        let col_meta = 255_u16.to_le_bytes();
+        let ctx = BinlogValueCtx::new(ColumnType::MYSQL_TYPE_VARCHAR, &col_meta[..]);
+        let mut buf = ParseBuf(b"\x05hello");
+        let value: BinlogValue = buf.parse(ctx)?;
+        assert!(buf.is_empty());
+        assert_eq!(value, BinlogValue::bytes("hello"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn geometry_point() -> io::Result<()> {
+        // `POINT(1 2)` with SRID 0: 4-byte SRID followed by the WKB payload
+        let mut wkb = vec![0, 0, 0, 0, 1, 1, 0, 0, 0];
+        wkb.extend_from_slice(&1.0_f64.to_le_bytes());
+        wkb.extend_from_slice(&2.0_f64.to_le_bytes());
+
+        // GEOMETRY columns always use the 4-byte length prefix
+        let mut data = (wkb.len() as u32).to_le_bytes().to_vec();
+        data.extend_from_slice(&wkb);
+
+        let ctx = BinlogValueCtx::new(ColumnType::MYSQL_TYPE_GEOMETRY, &[4][..]);
+        let mut buf = ParseBuf(&data);
+        let value: BinlogValue = buf.parse(ctx)?;
+        assert!(buf.is_empty());
+        assert_eq!(value, BinlogValue::Value(Value::Bytes(wkb)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn split_row_columns() -> io::Result<()> {
+        let mut body = vec![42, 0, 0, 0, 3, b'a', b'b', b'c'];
+        body.extend(decimal_bin("1.50", 10, 2));
+
+        let col_types = [
+            ColumnType::MYSQL_TYPE_LONG,
+            ColumnType::MYSQL_TYPE_VARCHAR,
+            ColumnType::MYSQL_TYPE_BLOB,
+            ColumnType::MYSQL_TYPE_NEWDECIMAL,
+        ];
+        let metas = [&[][..], &[10, 0], &[2], &[10, 2]];
+        let null_bitmap = BitSlice::<u8>::from_slice(&[0b0100]);
+
+        let columns = super::split_row_columns(&body, &col_types, &metas, null_bitmap)?;
+        assert_eq!(columns.len(), 4);
+        assert_eq!(columns[0], Some(&[42, 0, 0, 0][..]));
+        assert_eq!(columns[1], Some(&b"\x03abc"[..]));
+        assert_eq!(columns[2], None);
+        assert_eq!(
+            columns
+                .iter()
+                .flatten()
+                .copied()
+                .collect::<Vec<_>>()
+                .concat(),
+            body
+        );
+
+        let err =
+            super::split_row_columns(&body[..6], &col_types, &metas, null_bitmap).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+        assert_eq!(
+            err.to_string(),
+            "unexpected EOF at offset 4, 2 bytes remaining"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn row_image() -> io::Result<()> {
+        let mut body = vec![42, 0, 0, 0];
+        body.extend_from_slice(&[0x00, 0x01]);
+        body.extend_from_slice(&[0xAB; 256]);
+        body.extend_from_slice(b"\x03abc\xFF");
+
+        let col_types = [
+            ColumnType::MYSQL_TYPE_LONG,
+            ColumnType::MYSQL_TYPE_BLOB,
+            ColumnType::MYSQL_TYPE_TINY,
+            ColumnType::MYSQL_TYPE_VARCHAR,
+            ColumnType::MYSQL_TYPE_TINY,
+        ];
+        let metas = [&[][..], &[2], &[], &[10, 0], &[]];
+        let null_bitmap = BitSlice::<u8>::from_slice(&[0b0100]);
+        let unsigned = BitSlice::<u8>::from_slice(&[0b1_0000]);
+
+        let mut image =
+            RowImage::new(&body, &col_types, &metas, null_bitmap)?.with_unsigned(unsigned);
+        let (i, value) = image.next().unwrap()?;
+        assert_eq!((i, value), (0, BinlogValue::int(42)));
+
+        assert_eq!(image.skip_column()?, Some(1));
+        assert_eq!(image.column_index(), 2);
+        assert_eq!(image.remaining(), b"\x03abc\xFF");
+
+        let values = image.collect::<io::Result<Vec<_>>>()?;
+        assert_eq!(
+            values,
+            vec![
+                (2, BinlogValue::Value(NULL)),
+                (3, BinlogValue::bytes("abc")),
+                (4, BinlogValue::int(255)),
+            ]
+        );
+
+        let mut image = RowImage::new(&body[..100], &col_types, &metas, null_bitmap)?;
+        image.next().unwrap()?;
+        let err = image.skip_column().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+        assert_eq!(
+            err.to_string(),
+            "unexpected EOF at offset 4, 96 bytes remaining"
+        );
+
+        let mut image = RowImage::new(&body[..100], &col_types, &metas, null_bitmap)?;
+        image.skip_column()?;
+        let err = image.next().unwrap().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "unexpected EOF at offset 4, 96 bytes remaining"
+        );
+        assert_eq!(image.remaining().len(), 96);
+
+        Ok(())
+    }
+
+    #[test]
+    fn row_image_spans() -> io::Result<()> {
+        let mut body = vec![42, 0, 0, 0];
+        body.extend_from_slice(b"\x02\x00ab");
+        body.extend_from_slice(b"\x03abc\xFF");
+
+        let col_types = [
+            ColumnType::MYSQL_TYPE_LONG,
+            ColumnType::MYSQL_TYPE_BLOB,
+            ColumnType::MYSQL_TYPE_TINY,
+            ColumnType::MYSQL_TYPE_VARCHAR,
+            ColumnType::MYSQL_TYPE_TINY,
+        ];
+        let metas = [&[][..], &[2], &[], &[10, 0], &[]];
+        let null_bitmap = BitSlice::<u8>::from_slice(&[0b0100]);
+
+        let mut image = RowImage::new(&body, &col_types, &metas, null_bitmap)?;
+        let mut spans = Vec::new();
+        while let Some(item) = image.next_with_span() {
+            let (i, value, span) = item?;
+            assert_eq!(i, spans.len());
+            spans.push((value, span));
+        }
+        assert_eq!(
+            spans.iter().map(|(_, span)| *span).collect::<Vec<_>>(),
+            vec![(0, 4), (4, 4), (8, 0), (8, 4), (12, 1)]
+        );
+        // spans are contiguous and cover the whole body
+        let mut end = 0;
+        for (_, (start, len)) in &spans {
+            assert_eq!(*start, end);
+            end = start + len;
+        }
+        assert_eq!(end, body.len());
+
+        // overwrite a single value in place
+        let (start, len) = spans[0].1;
+        let mut value = Vec::new();
+        BinlogValue::int(7).serialize(
+            BinlogValueCtx::new(ColumnType::MYSQL_TYPE_LONG, &[]),
+            &mut value,
+        )?;
+        let mut edited = body.clone();
+        edited[start..start + len].copy_from_slice(&value);
+        let mut image = RowImage::new(&edited, &col_types, &metas, null_bitmap)?;
+        assert_eq!(image.next().unwrap()?, (0, BinlogValue::int(7)));
+        assert_eq!(&edited[4..], &body[4..]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn binlog_value_len() -> io::Result<()> {
+        use ColumnType::*;
+
+        // BLOB length prefixes of every width
+        for prefix_len in 1..=4 {
+            let mut data = vec![0; prefix_len];
+            data[0] = 3;
+            data.extend_from_slice(b"abc\xFF");
+            let buf = ParseBuf(&data);
+            let len = super::binlog_value_len(MYSQL_TYPE_BLOB, &[prefix_len as u8], false, &buf)?;
+            assert_eq!(len, prefix_len + 3);
+            assert_eq!(buf.len(), data.len());
+        }
+
+        // CHAR(300) (the high bits of the length are stored in the first metadata byte)
+        let data = b"\x02\x00ab";
+        let len = super::binlog_value_len(MYSQL_TYPE_STRING, &[0xEE, 44], false, &ParseBuf(data))?;
+        assert_eq!(len, 4);
+        // CHAR(10)
+        let len = super::binlog_value_len(MYSQL_TYPE_STRING, &[0xFE, 10], false, &ParseBuf(data))?;
+        assert_eq!(len, 3);
+
+        let data = 42_u32.to_le_bytes();
+        let len = super::binlog_value_len(MYSQL_TYPE_LONG, &[], true, &ParseBuf(&data))?;
+        assert_eq!(len, 4);
+        let len = super::binlog_value_len(MYSQL_TYPE_DATETIME2, &[3], false, &ParseBuf(&[0; 7]))?;
+        assert_eq!(len, 7);
+
+        let err =
+            super::binlog_value_len(MYSQL_TYPE_BLOB, &[2], false, &ParseBuf(&[3])).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+
+        Ok(())
+    }
+
+    #[test]
+    fn estimate_row_bytes() {
+        let col_types = [
+            ColumnType::MYSQL_TYPE_LONG,
+            ColumnType::MYSQL_TYPE_VARCHAR,
+            ColumnType::MYSQL_TYPE_NEWDECIMAL,
+            ColumnType::MYSQL_TYPE_VARCHAR,
+            ColumnType::MYSQL_TYPE_BLOB,
+            ColumnType::MYSQL_TYPE_DATETIME2,
+            ColumnType::MYSQL_TYPE_JSON,
+        ];
+        let metas = [&[][..], &[10, 0], &[10, 2], &[0, 4], &[2], &[6], &[4]];
+        assert_eq!(
+            super::estimate_row_bytes(&col_types, &metas),
+            Some(4 + 1 + 5 + 2 + 2 + 8 + 4)
+        );
+
+        assert_eq!(super::estimate_row_bytes(&col_types[..1], &metas), None);
+        assert_eq!(
+            super::estimate_row_bytes(&[ColumnType::MYSQL_TYPE_VARCHAR], &[&[10]]),
+            None
+        );
+        assert_eq!(
+            super::estimate_row_bytes(&[ColumnType::MYSQL_TYPE_NULL], &[&[]]),
+            None
+        );
+    }
+
+    #[test]
+    fn as_bit_vec() -> io::Result<()> {
+        // BIT(10)
+        let col_meta = [2, 1];
+        let ctx = BinlogValueCtx::new(ColumnType::MYSQL_TYPE_BIT, &col_meta[..]);
+        let data = [0b0000_0010, 0b0000_0101];
+
+        let value: BinlogValue = ParseBuf(&data).parse(ctx)?;
+        let bits = value.as_bit_vec(&col_meta).unwrap();
+        assert_eq!(bits.len(), 10);
+        assert_eq!(
+            bits,
+            [true, false, false, false, false, false, false, true, false, true]
+        );
+
+        let raw = BinlogValue::scan(ctx, &mut ParseBuf(&data))?;
+        assert_eq!(raw.as_bit_vec(), Some(bits));
+
+        // BIT(8)
+        assert_eq!(value.as_bit_vec(&[0, 1]), None);
+        assert_eq!(
+            BinlogValue::bytes(vec![0x81])
+                .as_bit_vec(&[0, 1])
+                .unwrap()
+                .len(),
+            8
+        );
+        assert_eq!(BinlogValue::int(1).as_bit_vec(&col_meta), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn binlog_value_ctx() -> io::Result<()> {
+        let ctx = BinlogValueCtx::new(ColumnType::MYSQL_TYPE_JSON, &[4])
+            .with_unsigned(true)
+            .with_partial(true);
+        assert_eq!(
+            (
+                ctx.col_type(),
+                ctx.col_meta(),
+                ctx.is_unsigned(),
+                ctx.is_partial()
+            ),
+            (ColumnType::MYSQL_TYPE_JSON, &[4][..], true, true)
+        );
+
+        // the tuple form is still accepted
+        let tuple = (ColumnType::MYSQL_TYPE_TINY, &[][..], true, false);
+        let ctx = BinlogValueCtx::from(tuple);
+        assert_eq!(
+            ctx,
+            BinlogValueCtx::new(ColumnType::MYSQL_TYPE_TINY, &[]).with_unsigned(true)
+        );
+        let value = BinlogValue::deserialize_with_options(
+            tuple,
+            BinlogValueOptions::new(),
+            &mut ParseBuf(&[0xFF]),
+        )?;
+        assert_eq!(value, ParseBuf(&[0xFF]).parse(ctx)?);
+        assert_eq!(value, BinlogValue::Value(Int(255)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn deserialize_expecting() -> io::Result<()> {
+        let ctx = BinlogValueCtx::new(ColumnType::MYSQL_TYPE_LONG, &[][..]);
+        let value =
+            BinlogValue::deserialize_expecting(ctx, &mut ParseBuf(&[42, 0, 0, 0]), ValueKind::Int)?;
+        assert_eq!(value, BinlogValue::int(42));
+
+        let ctx = BinlogValueCtx::new(ColumnType::MYSQL_TYPE_VARCHAR, &[10, 0][..]);
+        let mut buf = ParseBuf(b"\x03abc");
+        let err = BinlogValue::deserialize_expecting(ctx, &mut buf, ValueKind::Int).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(
+            err.to_string(),
+            "expected Int value for MYSQL_TYPE_VARCHAR column, got Bytes"
+        );
+
+        assert_eq!(BinlogValue::Value(NULL).kind(), ValueKind::Null);
+        assert_eq!(
+            BinlogValue::json(serde_json::json!(1)).kind(),
+            ValueKind::Jsonb
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn deserialize_with_trace() -> io::Result<()> {
+        let jsonb = jsonb::Value::from(serde_json::json!({"a": [1, "b"]}));
+        let mut jsonb_bin = Vec::new();
+        jsonb.serialize(&mut jsonb_bin);
+
+        let mut data = (jsonb_bin.len() as u32).to_le_bytes().to_vec();
+        data.extend_from_slice(&jsonb_bin);
+        data.extend_from_slice(&[42, 0, 0, 0]);
+
+        let ctx = BinlogValueCtx::new(ColumnType::MYSQL_TYPE_JSON, &[4][..]);
+        let mut buf = ParseBuf(&data);
+        let (value, trace) = BinlogValue::deserialize_with_trace(ctx, &mut buf)?;
+        assert_eq!(trace, &data[..data.len() - 4]);
+        assert_eq!(buf.len(), 4);
+
+        // traced bytes round-trip
+        match value {
+            BinlogValue::Jsonb(value) => {
+                assert_eq!(value, jsonb);
+                let mut serialized = Vec::new();
+                value.serialize(&mut serialized);
+                assert_eq!(serialized, &trace[4..]);
+            }
+            other => panic!("unexpected value {:?}", other),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn deserialize_with_meta_offset() -> io::Result<()> {
+        let col_meta = [10, 0, 10, 2];
+        let mut data = vec![42, 0, 0, 0, 3, b'a', b'b', b'c'];
+        data.extend(decimal_bin("1.50", 10, 2));
+
+        let mut buf = ParseBuf(&data);
+        let mut offset = 0;
+
+        let ctx = BinlogValueCtx::new(ColumnType::MYSQL_TYPE_LONG, &col_meta[..]);
+        let value = BinlogValue::deserialize_with_meta_offset(ctx, &mut offset, &mut buf)?;
+        assert_eq!(value, BinlogValue::Value(Int(42)));
+        assert_eq!(offset, 0);
+
+        let ctx = BinlogValueCtx::new(ColumnType::MYSQL_TYPE_VARCHAR, &col_meta[..]);
+        let value = BinlogValue::deserialize_with_meta_offset(ctx, &mut offset, &mut buf)?;
+        assert_eq!(value, BinlogValue::Value(Bytes(b"abc".to_vec())));
+        assert_eq!(offset, 2);
+
+        let ctx = BinlogValueCtx::new(ColumnType::MYSQL_TYPE_NEWDECIMAL, &col_meta[..]);
+        let value = BinlogValue::deserialize_with_meta_offset(ctx, &mut offset, &mut buf)?;
+        assert_eq!(value.kind(), ValueKind::Decimal);
+        assert_eq!(value.as_decimal_string().as_deref(), Some("1.50"));
+        assert_eq!(offset, 4);
+
+        assert!(buf.is_empty());
+
+        let ctx = BinlogValueCtx::new(ColumnType::MYSQL_TYPE_NEWDECIMAL, &col_meta[..]);
+        assert!(BinlogValue::deserialize_with_meta_offset(ctx, &mut offset, &mut buf).is_err());
+
+        Ok(())
+    }
+
+    #[cfg(feature = "nightly")]
+    mod benches {
+        use super::*;
+
+        const COUNT: usize = 1_000_000;
+        const CTX: BinlogValueCtx<'static> =
+            BinlogValueCtx::new(ColumnType::MYSQL_TYPE_NEWDECIMAL, &[20, 6]);
+
+        fn decimals() -> Vec<u8> {
+            let bin = decimal_bin("-12345678901234.500000", 20, 6);
+            bin.repeat(COUNT)
+        }
+
+        #[bench]
+        fn bench_deserialize_decimals(bencher: &mut test::Bencher) {
+            let data = decimals();
+            bencher.bytes = data.len() as u64;
+            bencher.iter(|| {
+                let mut buf = ParseBuf(&data);
+                for _ in 0..COUNT {
+                    test::black_box(buf.parse::<BinlogValue>(CTX).unwrap());
+                }
+            });
+        }
+
+        #[bench]
+        fn bench_deserialize_decimals_with_scratch(bencher: &mut test::Bencher) {
+            let data = decimals();
+            let mut scratch = decimal::DecimalScratch::new();
+            bencher.bytes = data.len() as u64;
+            bencher.iter(|| {
+                let mut buf = ParseBuf(&data);
+                for _ in 0..COUNT {
+                    test::black_box(
+                        BinlogValue::deserialize_with_scratch(
+                            CTX,
+                            BinlogValueOptions::new(),
+                            &mut scratch,
+                            &mut buf,
+                        )
+                        .unwrap(),
+                    );
+                }
+            });
+        }
+
+        #[bench]
+        fn bench_read_decimals_into(bencher: &mut test::Bencher) {
+            let data = decimals();
+            let mut scratch = decimal::DecimalScratch::new();
+            let mut dec = decimal::Decimal::default();
+            bencher.bytes = data.len() as u64;
+            bencher.iter(|| {
+                let mut buf = ParseBuf(&data);
+                for _ in 0..COUNT {
+                    scratch.read_into(&mut buf, 20, 6, false, &mut dec).unwrap();
+                    test::black_box(&dec);
+                }
+            });
         }
     }
 }
@@ -130,6 +130,15 @@ macro_rules! eat_num {
     };
 }
 
+macro_rules! peek_num {
+    ($name:ident, $checked:ident, $t:ident) => {
+        #[doc = "Returns a number from the head of the buffer without consuming it. Returns `None` if buffer is too small."]
+        pub fn $name(&self) -> Option<$t> {
+            ParseBuf(self.0).$checked()
+        }
+    };
+}
+
 impl<'a> ParseBuf<'a> {
     /// Returns `T: MyDeserialize` deserialized from `self`.
     ///
@@ -167,6 +176,27 @@ impl<'a> ParseBuf<'a> {
         self.0.len()
     }
 
+    /// Returns the number of bytes that aren't consumed yet (same as `len`).
+    pub fn remaining(&self) -> usize {
+        self.len()
+    }
+
+    /// Returns the number of bytes consumed since `origin`, i.e. the offset of `self`
+    /// within the `origin` buffer it was split from.
+    ///
+    /// The offset is computed from the buffer addresses, so it stays accurate for sub-buffers
+    /// (such as ones returned by `eat_buf` or `parse`). Returns `None` if `self`
+    /// doesn't lie within `origin`.
+    pub fn position_in(&self, origin: &[u8]) -> Option<usize> {
+        let start = origin.as_ptr() as usize;
+        let pos = self.0.as_ptr() as usize;
+        if pos >= start && pos + self.len() <= start + origin.len() {
+            Some(pos - start)
+        } else {
+            None
+        }
+    }
+
     /// Skips the given number of bytes.
     ///
     /// Afterwards self contains elements `[cnt, len)`.
@@ -214,6 +244,16 @@ impl<'a> ParseBuf<'a> {
         Some(Self(self.checked_eat(n)?))
     }
 
+    /// Returns elements `[0, n)` without consuming them. Returns `None` if buffer is too small.
+    pub fn peek_slice(&self, n: usize) -> Option<&'a [u8]> {
+        self.0.get(..n)
+    }
+
+    peek_num!(checked_peek_u8, checked_eat_u8, u8);
+    peek_num!(checked_peek_u16_le, checked_eat_u16_le, u16);
+    peek_num!(checked_peek_u24_le, checked_eat_u24_le, u32);
+    peek_num!(checked_peek_u32_le, checked_eat_u32_le, u32);
+
     pub fn eat_all(&mut self) -> &'a [u8] {
         self.eat(self.len())
     }
@@ -414,4 +454,53 @@ mod tests {
         assert_eq!(buf.clone().eat_u56_le(), 0x0006050403020100);
         assert_eq!(buf.clone().eat_u56_be(), 0x0000010203040506);
     }
+
+    #[test]
+    fn position_in() {
+        let data = [1, 2, 3, 4, 5, 6];
+        let mut buf = ParseBuf(&data);
+        assert_eq!((buf.position_in(&data), buf.remaining()), (Some(0), 6));
+
+        buf.eat_u8();
+        let mut sub_buf = buf.eat_buf(3);
+        assert_eq!((buf.position_in(&data), buf.remaining()), (Some(4), 2));
+        assert_eq!(sub_buf.position_in(&data), Some(1));
+        sub_buf.eat_u16_le();
+        assert_eq!(
+            (sub_buf.position_in(&data), sub_buf.remaining()),
+            (Some(3), 1)
+        );
+
+        buf.eat_all();
+        assert_eq!((buf.position_in(&data), buf.remaining()), (Some(6), 0));
+
+        let other = [1, 2, 3];
+        assert_eq!(ParseBuf(&other).position_in(&data), None);
+        assert_eq!(ParseBuf(&data).position_in(&data[1..]), None);
+    }
+
+    #[test]
+    fn peek() {
+        let mut buf = ParseBuf(&[1, 2, 3, 4, 5]);
+        assert_eq!(buf.checked_peek_u8(), Some(1));
+        assert_eq!(buf.checked_peek_u16_le(), Some(0x0201));
+        assert_eq!(buf.checked_peek_u24_le(), Some(0x030201));
+        assert_eq!(buf.checked_peek_u32_le(), Some(0x04030201));
+        assert_eq!(buf.peek_slice(5), Some(&[1, 2, 3, 4, 5][..]));
+        assert_eq!(buf.len(), 5);
+
+        // a peek followed by an eat reads the same bytes
+        assert_eq!(buf.checked_peek_u16_le(), buf.checked_eat_u16_le());
+        assert_eq!(buf.peek_slice(2), buf.checked_eat(2));
+        assert_eq!(buf.checked_peek_u8(), Some(5));
+        assert_eq!(buf.checked_peek_u8(), buf.checked_eat_u8());
+        assert!(buf.is_empty());
+
+        // a peek past the end
+        let buf = ParseBuf(&[1, 2, 3]);
+        assert_eq!(buf.checked_peek_u32_le(), None);
+        assert_eq!(buf.peek_slice(4), None);
+        assert_eq!(ParseBuf(&[]).checked_peek_u8(), None);
+        assert_eq!(buf.len(), 3);
+    }
 }
@@ -68,6 +68,7 @@
 //!
 //! | Feature        | Description                                 | Default |
 //! | -------------- | ------------------------------------------- | ------- |
+//! | `apache-avro`  | Enables binlog rows conversion to Avro      | 🔴      |
 //! | `bigdecimal`   | Enables `bigdecimal` v0.2.x types support   | 🔴      |
 //! | `bigdecimal03` | Enables `bigdecimal` v0.3.x types support   | 🟢      |
 //! | `chrono`       | Enables `chrono` types support              | 🔴      |
@@ -92,6 +93,9 @@ extern crate test;
 #[macro_use]
 pub mod bitflags_ext;
 
+#[cfg(feature = "apache-avro")]
+pub use apache_avro;
+
 #[cfg(feature = "bigdecimal")]
 pub use bigdecimal;
 
@@ -36,6 +36,18 @@ pub(crate) fn unexpected_buf_eof() -> io::Error {
     )
 }
 
+/// Same as `unexpected_buf_eof` but reports the offset of the failed read
+/// and the number of remaining bytes (see [`crate::io::ParseBuf::position_in`]).
+pub(crate) fn unexpected_buf_eof_at(offset: usize, remaining: usize) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::UnexpectedEof,
+        format!(
+            "unexpected EOF at offset {}, {} bytes remaining",
+            offset, remaining
+        ),
+    )
+}
+
 /// Splits server 'version' string into three numeric pieces.
 ///
 /// It'll return `(0, 0, 0)` in case of error.
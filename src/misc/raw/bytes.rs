@@ -297,7 +297,7 @@ impl BytesRepr for VarLen {
     type Ctx = ();
 
     fn serialize(text: &[u8], buf: &mut Vec<u8>) {
-        buf.put_lenenc_int(text.len() as u64);
+        RawInt::<VarLen>::new(text.len() as u32).serialize(buf);
         buf.put_slice(text);
     }
 
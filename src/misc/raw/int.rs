@@ -305,9 +305,9 @@ impl IntRepr for VarLen {
             if val != 0 {
                 byte |= 0x80;
                 buf.put_u8(byte);
-                break;
             } else {
                 buf.put_u8(byte);
+                break;
             }
         }
     }
@@ -337,3 +337,39 @@ impl IntRepr for VarLen {
         ))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::misc::raw::bytes::RawBytes;
+
+    #[test]
+    fn var_len_round_trip() -> io::Result<()> {
+        for (value, repr) in [
+            (0, &[0x00][..]),
+            (127, &[0x7F]),
+            (128, &[0x80, 0x01]),
+            (16_383, &[0xFF, 0x7F]),
+            (16_384, &[0x80, 0x80, 0x01]),
+            (u32::MAX, &[0xFF, 0xFF, 0xFF, 0xFF, 0x0F]),
+        ] {
+            let mut buf = Vec::new();
+            RawInt::<VarLen>::new(value).serialize(&mut buf);
+            assert_eq!(buf, repr, "{}", value);
+            let mut parse_buf = ParseBuf(&buf);
+            assert_eq!(*parse_buf.parse::<RawInt<VarLen>>(())?, value);
+            assert!(parse_buf.is_empty());
+        }
+
+        // the length prefix of `VarLen` bytes is a `VarLen` integer (not a lenenc one)
+        let text = vec![b'x'; 16_384];
+        let mut buf = Vec::new();
+        RawBytes::<VarLen>::new(&text[..]).serialize(&mut buf);
+        assert_eq!(&buf[..3], &[0x80, 0x80, 0x01]);
+        assert_eq!(&buf[3..], &text[..]);
+        let bytes: RawBytes<VarLen> = ParseBuf(&buf).parse(())?;
+        assert_eq!(bytes.as_bytes(), &text[..]);
+
+        Ok(())
+    }
+}